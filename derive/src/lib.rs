@@ -0,0 +1,102 @@
+//  #[derive(Structure)] maps a Rust struct to a PPL structure datatype.  It implements ppl::embed::Structure,
+//  and the conversions between the struct and Value, for a struct with named fields whose types all convert.
+//
+//      #[derive(Structure)]
+//      #[ppl(name = "point")]
+//      struct Point { x: i32, y: i32 }
+//
+//  The datatype is named after the struct and each selector after its field.  #[ppl(name = "...")] on the
+//  struct or a field chooses another name.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{parse_macro_input, Attribute, Data, DeriveInput, Error, Fields, LitStr};
+
+
+#[proc_macro_derive(Structure, attributes(ppl))]
+pub fn derive_structure(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match expand(&input) {
+        Ok(tokens) => tokens.into(),
+        Err(error) => error.to_compile_error().into(),
+    }
+}
+
+fn expand(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let ident = &input.ident;
+    if !input.generics.params.is_empty() {
+        return Err(Error::new_spanned(&input.generics, "Structure can't be derived for a generic struct"));
+    }
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => return Err(Error::new_spanned(ident, "Structure can only be derived for a struct with named fields")),
+        },
+        _ => return Err(Error::new_spanned(ident, "Structure can only be derived for a struct")),
+    };
+
+    let datatype = ppl_name(&input.attrs)?.unwrap_or_else(|| ident.to_string());
+    let mut members = Vec::new();
+    let mut selectors = Vec::new();
+    let mut types = Vec::new();
+    for field in fields {
+        let member = field.ident.as_ref().unwrap();
+        selectors.push(match ppl_name(&field.attrs)? {
+            Some(name) => name,
+            None => member.to_string().trim_start_matches("r#").to_string(),
+        });
+        members.push(member);
+        types.push(&field.ty);
+    }
+
+    Ok(quote! {
+        impl ::ppl::embed::Datatype for #ident {
+            fn datatype() -> ::std::string::String {
+                ::std::string::String::from(#datatype)
+            }
+        }
+
+        impl ::ppl::embed::Structure for #ident {
+            fn definition() -> ::ppl::embed::MetaStructure {
+                ::ppl::embed::MetaStructure::new(#datatype, ::std::vec![#(
+                    (#selectors, <#types as ::ppl::embed::Datatype>::datatype())),*])
+            }
+        }
+
+        impl ::std::convert::From<#ident> for ::ppl::embed::Value {
+            fn from(v: #ident) -> ::ppl::embed::Value {
+                ::ppl::embed::structure_value(#datatype, ::std::vec![#(
+                    (#selectors, <#types as ::ppl::embed::Datatype>::datatype(), ::ppl::embed::Value::from(v.#members))),*])
+            }
+        }
+
+        impl ::std::convert::TryFrom<&::ppl::embed::Value> for #ident {
+            type Error = ::ppl::embed::PplError;
+
+            fn try_from(v: &::ppl::embed::Value) -> ::std::result::Result<#ident, ::ppl::embed::PplError> {
+                let structure = ::ppl::embed::structure_of(v, #datatype)?;
+                ::std::result::Result::Ok(#ident {
+                    #(#members: ::std::convert::TryFrom::try_from(&structure.member(#selectors)?)?),*
+                })
+            }
+        }
+    })
+}
+
+//  The name given by #[ppl(name = "...")], if any
+
+fn ppl_name(attrs: &[Attribute]) -> syn::Result<Option<String>> {
+    let mut name = None;
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("ppl")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("name") {
+                name = Some(meta.value()?.parse::<LitStr>()?.value());
+                Ok(())
+            } else {
+                Err(meta.error("expected name = \"...\""))
+            }
+        })?;
+    }
+    Ok(name)
+}
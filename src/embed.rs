@@ -0,0 +1,285 @@
+//  This module is the interface for programs that embed PPL.  An Interpreter owns a workspace, in which the host
+//  can evaluate PPL, read and write variables, and call functions.  The host can also add functions of its own,
+//  written as Rust closures.  Values and errors cross the interface as Value and PplError.
+
+use std::{collections::HashMap, rc::Rc, sync::{Arc, atomic::AtomicBool}};
+
+use crate::{
+    execution::{self, definition, runtime::{executable::Executable, invocation::ExecutionState}, value::{Cell, structure::{StructureInstance, StructureInstanceMember}}},
+    parser::tree::{Node, OperationNode},
+    sequencer,
+    symbols::{metadata::{ArgumentMechanism, FormalArgument, FunctionArgumentList, FunctionClass, FunctionDescription, FunctionImplementation, HostFunction, MetaDataTypeName, VariableDescription}, name::Name},
+    utility::Set,
+    workspace::WorkSpace};
+
+pub use crate::{error::PplError, execution::value::{Value, complex::Complex, decimal::Decimal, rational::Rational}};
+pub use crate::symbols::metadata::MetaStructure;
+#[cfg(feature = "derive")]
+pub use ppl_derive::Structure;
+
+
+//  A formal argument of a host function.  PPL converts each argument to its datatype ("int", "string", or
+//  "general" for anything) before the host sees it.
+
+#[derive(Clone, Debug)]
+pub struct Argument {
+    name: String,
+    datatype: String,
+}
+
+impl Argument {
+    pub fn new(name: &str, datatype: &str) -> Argument {
+        Argument { name: String::from(name), datatype: String::from(datatype) }
+    }
+}
+
+//  The PPL datatype of the values a Rust type converts to
+
+pub trait Datatype {
+    fn datatype() -> String;
+}
+
+impl Datatype for bool {
+    fn datatype() -> String {
+        String::from("bool")
+    }
+}
+
+impl Datatype for i32 {
+    fn datatype() -> String {
+        String::from("int")
+    }
+}
+
+impl Datatype for i64 {
+    fn datatype() -> String {
+        String::from("int64")
+    }
+}
+
+#[cfg(feature = "bignum")]
+impl Datatype for num_bigint::BigInt {
+    fn datatype() -> String {
+        String::from("bignum")
+    }
+}
+
+impl Datatype for Rational {
+    fn datatype() -> String {
+        String::from("rational")
+    }
+}
+
+impl Datatype for Decimal {
+    fn datatype() -> String {
+        String::from("decimal")
+    }
+}
+
+impl Datatype for Complex {
+    fn datatype() -> String {
+        String::from("complex")
+    }
+}
+
+impl Datatype for f32 {
+    fn datatype() -> String {
+        String::from("real")
+    }
+}
+
+impl Datatype for f64 {
+    fn datatype() -> String {
+        String::from("dbl")
+    }
+}
+
+impl Datatype for char {
+    fn datatype() -> String {
+        String::from("char")
+    }
+}
+
+impl Datatype for String {
+    fn datatype() -> String {
+        String::from("string")
+    }
+}
+
+impl<T> Datatype for Vec<T> {
+    fn datatype() -> String {
+        String::from("tuple")
+    }
+}
+
+impl<K, V> Datatype for HashMap<K,V> {
+    fn datatype() -> String {
+        String::from("tuple")
+    }
+}
+
+impl Datatype for Value {
+    fn datatype() -> String {
+        String::from("general")
+    }
+}
+
+//  A Rust struct that maps to a PPL structure datatype, with a selector for each field.  #[derive(Structure)],
+//  with the "derive" feature, writes this and the conversions for a struct whose fields all convert.  The
+//  datatype is named after the struct and the selectors after the fields, unless #[ppl(name = "...")] says
+//  otherwise.  PPL names can't hold an underscore, so a field like first_name needs renaming.
+
+pub trait Structure: Datatype + Into<Value> + for<'a> TryFrom<&'a Value, Error = PplError> {
+    fn definition() -> MetaStructure;
+}
+
+//  What the derived conversions build on
+
+#[doc(hidden)]
+pub fn structure_value(datatype: &str, members: Vec<(&str, String, Value)>) -> Value {
+    Value::Structure(StructureInstance::from_string(&String::from(datatype),
+        members.into_iter().map(|(name, member_datatype, value)| StructureInstanceMember::new(name, &member_datatype, value)).collect()))
+}
+
+#[doc(hidden)]
+pub fn structure_of(value: &Value, datatype: &str) -> Result<StructureInstance,PplError> {
+    let structure = value.as_structure()?;
+    if structure.as_datatype().as_string() != datatype {
+        return Err(ppl_error!(Type, "{} is not a {}", value, datatype));
+    }
+    Ok(structure)
+}
+
+pub struct Interpreter {
+    workspace: WorkSpace,
+}
+
+impl Default for Interpreter {
+    fn default() -> Interpreter {
+        Interpreter::new()
+    }
+}
+
+impl Interpreter {
+    pub fn new() -> Interpreter {
+        Interpreter { workspace: WorkSpace::new() }
+    }
+
+    //  For what the interface doesn't cover
+
+    pub fn workspace(&self) -> &WorkSpace {
+        &self.workspace
+    }
+
+    //  Evaluates PPL as immediate mode would, returning the value it displays
+
+    pub fn evaluate(&self, source: &str) -> Result<String,PplError> {
+        execution::evaluate(source, &self.workspace)
+    }
+
+    //  Adds a function written in Rust.  Without a return type, whatever the closure returns is discarded, as
+    //  for the builtins that return nothing.
+
+    pub fn register<F>(&self, name: &str, arguments: &[Argument], return_type: Option<&str>, help_text: &str, function: F) -> Result<(),PplError>
+    where F: Fn(&[Value]) -> Result<Value,PplError> + 'static
+    {
+        if !Name::is_name_a_legal_identifier(name) {
+            return Err(ppl_error!(Name, "{} is not a legal function name", name));
+        }
+        for datatype in arguments.iter().map(|argument| &argument.datatype).chain(return_type.map(String::from).as_ref()) {
+            self.workspace.resolve_datatype(datatype)?;
+        }
+        self.workspace.add_system_function(name, FunctionDescription {
+            name: Name::from_str(name),
+            arguments: FunctionArgumentList::Fixed(arguments.iter().map(|argument| FormalArgument {
+                name: Name::from_str(&argument.name),
+                mechanism: ArgumentMechanism::ByValue,
+                datatype: MetaDataTypeName::from_str(&argument.datatype) }).collect()),
+            local_variables: None,
+            return_value: return_type.map(MetaDataTypeName::from_str),
+            implementation_class: FunctionImplementation::System(FunctionClass::Host(HostFunction(Rc::new(function)))),
+            help_text: String::from(help_text) });
+        Ok(())
+    }
+
+    //  Defines the structure datatype for a Rust struct, so PPL can make and take its values
+
+    pub fn define<T: Structure>(&self) -> Result<(),PplError> {
+        let structure = T::definition();
+        if self.workspace.contains_any(structure.name.as_str()) {
+            return Err(ppl_error!(Name, "{} is already defined", structure.name));
+        }
+        for name in std::iter::once(&structure.name).chain(structure.members.iter().map(|member| &member.name)) {
+            if !Name::is_name_a_legal_identifier(name.as_str()) {
+                return Err(ppl_error!(Name, "{} is not a legal PPL name", name));
+            }
+        }
+        for member in &structure.members {
+            self.workspace.resolve_datatype(&member.data_type.as_string())?;
+        }
+        definition::define_structure(structure, &self.workspace);
+        Ok(())
+    }
+
+    pub fn get_variable(&self, name: &str) -> Option<Value> {
+        self.workspace.try_get_variable(name).map(|v| v.cell.borrow().as_ref_to_value().clone())
+    }
+
+    //  Assigns to the variable if there is one, or else creates it in the workspace
+
+    pub fn set_variable(&self, name: &str, value: &Value) -> Result<(),PplError> {
+        match self.workspace.try_get_variable(name) {
+            Some(v) => v.cell.borrow().set_value(value),
+            None => {
+                if !Name::is_name_a_legal_identifier(name) {
+                    return Err(ppl_error!(Name, "{} is not a legal variable name", name));
+                }
+                Cell::validate_value(value)?;
+                self.workspace.add_variable(name, VariableDescription { cell: Cell::new(value.clone()) });
+                Ok(())
+            },
+        }
+    }
+
+    //  Calls a function, builtin or user-defined, with values rather than PPL source for its arguments.  This
+    //  builds the executable the parser would for name(arguments...), with the arguments as constants.
+
+    pub fn call(&self, name: &str, arguments: &[Value]) -> Result<Value,PplError> {
+        let source = format!("{}({})", name, arguments.iter().map(|a| a.to_string()).collect::<Vec<String>>().join(", "));
+        let mut executable = Executable::new(&source);
+        let mut node_indices = Set::new();
+        let argument_indices: Vec<usize> = arguments.iter().map(|argument| executable.add_node(Node::Value(argument.clone()))).collect();
+        argument_indices.iter().for_each(|index| node_indices.add(*index));
+        let call_index = executable.add_node(Node::Operation(OperationNode::from_str(name, argument_indices)));
+        node_indices.add(call_index);
+        node_indices.add(executable.add_node(Node::StatementEnd(call_index)));
+        executable.add_statement(1, 0, source.len(), &node_indices);
+
+        //  A call from a host function, made while PPL is running, leaves cleaning up to the evaluation it's in
+
+        let nested = self.workspace.current_invocation().is_some_and(|invocation| invocation.get_execution_state() == ExecutionState::Executing);
+        if !nested {
+            self.workspace.set_last_statement_value(&Value::Empty);
+        }
+        let result = sequencer::start_execution(&Rc::new(executable), &self.workspace);
+        if result.is_err() && !nested {
+            match self.workspace.get_stopped_invocation_depth() {
+                Some(depth) => self.workspace.unwind_invocations(depth),
+                None => self.workspace.reset(),
+            }
+        }
+        Ok(result?.unwrap_or(Value::Empty))
+    }
+
+    //  Setting the flag interrupts whatever PPL is running, as ^C does at the terminal
+
+    pub fn interrupt_flag(&self) -> Arc<AtomicBool> {
+        self.workspace.get_execution_sentinal_as_atomicbool()
+    }
+
+    #[cfg(feature = "signal-hook")]
+    pub fn interrupt_on_sigint(&self) -> Result<(),PplError> {
+        signal_hook::flag::register(signal_hook::consts::SIGINT, self.interrupt_flag())?;
+        Ok(())
+    }
+}
@@ -0,0 +1,232 @@
+//  This module defines the errors reported by the lexer, the parser and the interpreter.  Each error
+//  carries its kind, so that embedding code can tell a syntax error from a type error or an interrupt,
+//  and where it happened: the function and line that were executing, or the position in the source.
+
+use std::fmt;
+
+use crate::lexical::{LineNumber, TokenPosition};
+
+
+//  Creates a PplError of the given kind from a format string, for example
+//  ppl_error!(Name, "{} not found", name)
+
+macro_rules! ppl_error {
+    ($kind:ident, $($arg:tt)*) => {
+        $crate::error::PplError::$kind($crate::error::ErrorDetail::new(format!($($arg)*)))
+    };
+}
+
+
+#[derive(Debug,Clone,PartialEq)]
+pub struct SourceSpan {
+    pub line_number: LineNumber,
+    pub column: u32,
+    pub length: u32,
+    pub source_line: String,
+}
+
+impl SourceSpan {
+
+    //  Locates a token position in the source it came from
+
+    pub fn new(source: &str, position: &TokenPosition) -> SourceSpan {
+        let source_line = source.lines().nth((position.line_number as usize).saturating_sub(1))
+            .or_else(|| source.lines().last()).unwrap_or(source);
+        SourceSpan {
+            line_number: position.line_number,
+            column: position.column,
+            length: 1,
+            source_line: String::from(source_line),
+        }
+    }
+}
+
+//  One entry in a traceback: a function that was executing (or immediate mode, which has no name), the
+//  line it had reached, and the source of the statement on that line
+
+#[derive(Debug,Clone,PartialEq)]
+pub struct Frame {
+    pub function_name: Option<String>,
+    pub line_number: LineNumber,
+    pub source: String,
+}
+
+impl fmt::Display for Frame {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.function_name {
+            Some(function_name) => write!(f, "@{} [{}] {}", function_name, self.line_number, self.source),
+            None => write!(f, "@[{}] {}", self.line_number, self.source),
+        }
+    }
+}
+
+#[derive(Debug,Clone,PartialEq)]
+pub struct ErrorDetail {
+    pub message: String,
+    pub source_name: Option<String>,
+    pub function_name: Option<String>,
+    pub line_number: Option<LineNumber>,
+    pub span: Option<Box<SourceSpan>>,
+    pub traceback: Vec<Frame>,
+}
+
+impl ErrorDetail {
+    pub fn new(message: String) -> ErrorDetail {
+        ErrorDetail { message, source_name: None, function_name: None, line_number: None, span: None, traceback: Vec::new() }
+    }
+}
+
+#[derive(Debug,Clone,PartialEq)]
+pub enum PplError {
+    Syntax(ErrorDetail),
+    Name(ErrorDetail),
+    Type(ErrorDetail),
+    Argument(ErrorDetail),
+    Index(ErrorDetail),
+    Arithmetic(ErrorDetail),
+    Io(ErrorDetail),
+    Interrupt(ErrorDetail),
+    Stop(ErrorDetail),
+    Assertion(ErrorDetail),
+    Limit(ErrorDetail),
+    Runtime(ErrorDetail),
+}
+
+impl PplError {
+
+    //  A syntax error, pointing at a position in the source
+
+    pub fn syntax(source: &str, position: &TokenPosition, message: &str) -> PplError {
+        let span = SourceSpan::new(source, position);
+        PplError::Syntax(ErrorDetail {
+            message: String::from(message),
+            source_name: None,
+            function_name: None,
+            line_number: Some(span.line_number),
+            span: Some(Box::new(span)),
+            traceback: Vec::new(),
+        })
+    }
+
+    pub fn detail(&self) -> &ErrorDetail {
+        match self {
+            PplError::Syntax(detail) | PplError::Name(detail) | PplError::Type(detail) | PplError::Argument(detail) |
+            PplError::Index(detail) | PplError::Arithmetic(detail) | PplError::Io(detail) | PplError::Interrupt(detail) |
+            PplError::Stop(detail) | PplError::Assertion(detail) |
+            PplError::Limit(detail) | PplError::Runtime(detail) => detail,
+        }
+    }
+
+    fn detail_mut(&mut self) -> &mut ErrorDetail {
+        match self {
+            PplError::Syntax(detail) | PplError::Name(detail) | PplError::Type(detail) | PplError::Argument(detail) |
+            PplError::Index(detail) | PplError::Arithmetic(detail) | PplError::Io(detail) | PplError::Interrupt(detail) |
+            PplError::Stop(detail) | PplError::Assertion(detail) |
+            PplError::Limit(detail) | PplError::Runtime(detail) => detail,
+        }
+    }
+
+    pub fn message(&self) -> &str {
+        self.detail().message.as_str()
+    }
+
+    pub fn source_name(&self) -> Option<&str> {
+        self.detail().source_name.as_deref()
+    }
+
+    pub fn function_name(&self) -> Option<&str> {
+        self.detail().function_name.as_deref()
+    }
+
+    pub fn line_number(&self) -> Option<LineNumber> {
+        self.detail().line_number
+    }
+
+    pub fn span(&self) -> Option<&SourceSpan> {
+        self.detail().span.as_deref()
+    }
+
+    //  The frames that were active when the error happened, innermost first
+
+    pub fn traceback(&self) -> &[Frame] {
+        self.detail().traceback.as_slice()
+    }
+
+    //  Records the file (or workspace item) the failing source came from, and its line there.  Immediate mode's
+    //  frames count lines from the start of the source, so they move to the same place.
+
+    pub fn within(mut self, source_name: &str, line_number: Option<LineNumber>) -> PplError {
+        let detail = self.detail_mut();
+        detail.source_name = Some(String::from(source_name));
+        if let Some(line_number) = line_number {
+            detail.line_number = Some(line_number);
+            for frame in detail.traceback.iter_mut().filter(|frame| frame.function_name.is_none()) {
+                frame.line_number += line_number.saturating_sub(1);
+            }
+        }
+        self
+    }
+
+    //  Records where a runtime error happened.  The innermost location wins, so an error that has
+    //  already been located keeps its function and line as it propagates out through its callers.
+
+    pub fn located(mut self, function_name: Option<String>, line_number: Option<LineNumber>) -> PplError {
+        let detail = self.detail_mut();
+        if detail.line_number.is_none() && detail.function_name.is_none() {
+            detail.function_name = function_name;
+            detail.line_number = line_number;
+        }
+        self
+    }
+
+    //  Like the location, the traceback is taken where the error happened, before the frames unwind
+
+    pub fn with_traceback(mut self, traceback: Vec<Frame>) -> PplError {
+        let detail = self.detail_mut();
+        if detail.traceback.is_empty() {
+            detail.traceback = traceback;
+        }
+        self
+    }
+}
+
+impl fmt::Display for PplError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.source_name(), self.line_number()) {
+            (Some(source_name), Some(line_number)) => write!(f, "{}:{}: ", source_name, line_number)?,
+            (Some(source_name), None) => write!(f, "{}: ", source_name)?,
+            (None, _) => {},
+        }
+        match self.span() {
+            Some(span) => {
+                let column = if span.column > 0 { span.column - 1 } else { 0 };
+                write!(f, "\n{}\n{}^\n{}", span.source_line, " ".repeat(column as usize), self.message())?;
+            },
+            None => write!(f, "{}", self.message())?,
+        }
+        for frame in self.traceback() {
+            write!(f, "\n{}", frame)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for PplError {}
+
+impl From<String> for PplError {
+    fn from(message: String) -> PplError {
+        PplError::Runtime(ErrorDetail::new(message))
+    }
+}
+
+impl From<&str> for PplError {
+    fn from(message: &str) -> PplError {
+        PplError::Runtime(ErrorDetail::new(String::from(message)))
+    }
+}
+
+impl From<std::io::Error> for PplError {
+    fn from(e: std::io::Error) -> PplError {
+        PplError::Io(ErrorDetail::new(e.to_string()))
+    }
+}
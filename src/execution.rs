@@ -6,13 +6,14 @@ use crate::{
     workspace::{WorkSpace, debug::DebugOption, GeneralSymbol}, 
     execution::value::Value,
     parser::{ Parser, tree::{Node, OperationNode, ReferenceNode }}, 
-    symbols::metadata::{ FunctionClass, FunctionDescription, FunctionImplementation, VariableDescription, SelectorDescription, MetaDataType, FunctionArgumentList, ArgumentMechanism}, sequencer,
-    utility::convert_escape_sequences};
+    symbols::metadata::{ FunctionClass, FunctionDescription, FunctionImplementation, VariableDescription, SelectorDescription, MetaDataType, FunctionArgumentList, ArgumentMechanism}, sequencer};
 
 use self::value::{structure::SelectorInstance, sequence::SequenceInstance, recursion_detector, SymbolicReference};
+use crate::error::PplError;
 
 
 pub mod definition;
+pub mod editor;
 pub mod functions;
 pub mod runtime;
 pub mod sentinal;
@@ -24,12 +25,12 @@ pub mod value;
 
 
 
-fn assemble_argument_list(f: &Rc<FunctionDescription>, actual_argument_list: &Vec<usize>, workspace: &WorkSpace) -> Result<Vec<Value>,String> {
+fn assemble_argument_list(f: &Rc<FunctionDescription>, actual_argument_list: &Vec<usize>, workspace: &WorkSpace) -> Result<Vec<Value>,PplError> {
      let mut actual_argument_values = Vec::new();
     match f.arguments {
         FunctionArgumentList::Fixed(ref formal_args) => {
             if formal_args.len() != actual_argument_list.len() {
-                return Err(format!("Incorrect number of arguments to {}", f.name));
+                return Err(ppl_error!(Argument, "Incorrect number of arguments to {}", f.name));
             }
             for i in (0..actual_argument_list.len()).rev() {
                 let formal_arg = &formal_args[i];
@@ -52,7 +53,7 @@ fn assemble_argument_list(f: &Rc<FunctionDescription>, actual_argument_list: &Ve
                     },
                     ArgumentMechanism::ByValue => {
                         match workspace.pop_value() {
-                            Value::Selector(ref selector) => return Err(format!("{} is not a value", selector)),
+                            Value::Selector(ref selector) => return Err(ppl_error!(Type, "{} is not a value", selector)),
                             Value::Symbol(ref symbol) => actual_argument_values.push(evaluate_identifier_by_value(symbol, workspace)?),
                             Value::ValueByReference(cell_ref) => actual_argument_values.push(cell_ref.cell.borrow().as_ref_to_value().clone()),
                             Value::LogicalLink(link) => actual_argument_values.push(link.as_ref_to_value().clone()),
@@ -67,7 +68,7 @@ fn assemble_argument_list(f: &Rc<FunctionDescription>, actual_argument_list: &Ve
     Ok(actual_argument_values)
 }
 
-fn construct(datatype: &Rc<MetaDataType>, actual_argument_list: &Vec<usize>, workspace: &WorkSpace) -> Result<(),String> {
+fn construct(datatype: &Rc<MetaDataType>, actual_argument_list: &Vec<usize>, workspace: &WorkSpace) -> Result<(),PplError> {
     //  Assemble the actual argument values
 
     let mut actual_argument_values = Vec::new();
@@ -82,34 +83,32 @@ fn construct(datatype: &Rc<MetaDataType>, actual_argument_list: &Vec<usize>, wor
     Ok(())
 }
 
-pub fn evaluate(s: &str, workspace: &WorkSpace) -> Result<String,String> {
+pub fn evaluate(s: &str, workspace: &WorkSpace) -> Result<String,PplError> {
     let result = evaluate_internal(s, workspace);
     
 
-    let mut result_string = match &result {
-        Ok(r) => r.clone(),
-        Err(e) => e.clone(),
-    };
+    //  Report where execution is.  An error carries its own traceback, which says so instead.
 
-    match workspace.current_invocation() {
-        Some(invocation) => {
-            match (invocation.get_current_line_number(), invocation.get_current_statement_source(), invocation.get_fib()) {
-                (Some(l), Some(s), Some(fib)) => {
-                    result_string += format!("\n@{} [{}] {}\t", fib.function_description.name, l, convert_escape_sequences(s.as_str())).as_str();
-                },
-                (Some(l), Some(s), None) => {
-                    result_string += format!("\n@[{}] {}\t", l, s).as_str();
-                },
-                (_, _, _) => {},
-            }
-        },
-        None => workspace.reset(),
+    let location = match workspace.traceback().first() {
+        Some(frame) => format!("\n{}\t", frame),
+        None => String::new(),
+    };
+    if workspace.current_invocation().is_none() {
+        workspace.reset();
     }
 
     match workspace.current_invocation() {
         Some(invocation) => {
             match invocation.get_execution_state() {
-                runtime::invocation::ExecutionState::NotExecuting | runtime::invocation::ExecutionState::Executing => workspace.reset(),
+                runtime::invocation::ExecutionState::NotExecuting | runtime::invocation::ExecutionState::Executing => {
+
+                    //  A mistake in immediate mode shouldn't cost the user the function they stopped
+
+                    match workspace.get_stopped_invocation_depth() {
+                        Some(depth) => workspace.unwind_invocations(depth),
+                        None => workspace.reset(),
+                    }
+                },
                 runtime::invocation::ExecutionState::Stopped => {},
                 runtime::invocation::ExecutionState::Resumed => panic!("internal error"),
             }
@@ -118,24 +117,24 @@ pub fn evaluate(s: &str, workspace: &WorkSpace) -> Result<String,String> {
     }
 
     match result {
-        Ok(_) => Ok(result_string),
-        Err(_) => Err(result_string),
+        Ok(result_string) => Ok(result_string + location.as_str()),
+        Err(e) => Err(e),
     }
 }
 
-pub fn evaluate_identifier_by_value(reference: &SymbolicReference, workspace: &WorkSpace) -> Result<Value,String> {
+pub fn evaluate_identifier_by_value(reference: &SymbolicReference, workspace: &WorkSpace) -> Result<Value,PplError> {
     let value_stack_size = workspace.get_value_stack_size();
     match reference.as_symbol() {
         GeneralSymbol::Datatype(d) => execute_value(&SequenceInstance::construct_string_sequence(&d.as_string()), workspace)?,
         GeneralSymbol::Function(ref f) => execute_function(f, &vec![], workspace)?,
         GeneralSymbol::Selector(s) => execute_selector_reference(s, workspace)?,
         GeneralSymbol::Variable(v) => execute_variable_reference_by_value(v, workspace)?,
-        GeneralSymbol::Unresolved(u) => return Err(format!("{} not found", u)),
+        GeneralSymbol::Unresolved(u) => return Err(ppl_error!(Name, "{} not found", u)),
     }
     Ok(workspace.try_pop_value(value_stack_size).unwrap_or(Value::Empty))
 }
 
-pub fn evaluate_internal(s: &str, workspace: &WorkSpace) -> Result<String,String> {
+pub fn evaluate_internal(s: &str, workspace: &WorkSpace) -> Result<String,PplError> {
     let parser = Parser::new(s, workspace);
     let executable = parser.parse(s)?;
     if workspace.debug_options.borrow().is_set(&DebugOption::Parse) {
@@ -147,13 +146,11 @@ pub fn evaluate_internal(s: &str, workspace: &WorkSpace) -> Result<String,String
             recursion_detector::Cycle::start();
             Ok(format!("{}", r.unwrap_or(Value::Empty)))
         },
-        Err(e) => {
-            Err(format!("{}", e))
-        },
+        Err(e) => Err(e),
     }
 }
 
-pub fn evaluate_with_diverted_stdout(s: &str, workspace: &WorkSpace) -> Result<String,String> {
+pub fn evaluate_with_diverted_stdout(s: &str, workspace: &WorkSpace) -> Result<String,PplError> {
     workspace.enable_alternate_print_destination();
     let result = evaluate_internal(s, workspace);
     match result {
@@ -170,7 +167,7 @@ pub fn evaluate_with_diverted_stdout(s: &str, workspace: &WorkSpace) -> Result<S
     }
 }
 
-pub fn execute(node: &Node, workspace: &WorkSpace) -> Result<(),String>{
+pub fn execute(node: &Node, workspace: &WorkSpace) -> Result<(),PplError>{
     recursion_detector::Cycle::start();
 
     match node {
@@ -205,7 +202,7 @@ pub fn execute(node: &Node, workspace: &WorkSpace) -> Result<(),String>{
 //     }
 // }
 
-fn execute_function(f: &Rc<FunctionDescription>, actual_argument_list: &Vec<usize>, workspace: &WorkSpace) -> Result<(),String> {
+fn execute_function(f: &Rc<FunctionDescription>, actual_argument_list: &Vec<usize>, workspace: &WorkSpace) -> Result<(),PplError> {
 
     //  Assemble the actual argument values, then invoke the function
 
@@ -244,6 +241,12 @@ fn execute_function(f: &Rc<FunctionDescription>, actual_argument_list: &Vec<usiz
                 FunctionClass::NullValuedVarying(func) => {
                     func(&actual_argument_values, workspace)?;
                 },
+                FunctionClass::Host(func) => {
+                    let value = (func.0)(&actual_argument_values)?;
+                    if f.return_value.is_some() {
+                        workspace.push_value(&value);
+                    }
+                },
             }
         },
         FunctionImplementation::User(_) => {
@@ -257,29 +260,35 @@ fn execute_function(f: &Rc<FunctionDescription>, actual_argument_list: &Vec<usiz
     Ok(())
 }
 
-fn execute_function_return(workspace: &WorkSpace) -> Result<(),String> {
+fn execute_function_return(workspace: &WorkSpace) -> Result<(),PplError> {
     let invocation = workspace.current_invocation().unwrap();
     let fib = invocation.get_fib().unwrap();
-    if let Some(v) = workspace.try_get_variable(fib.function_description.name.as_str()) {
-        workspace.push_value(&*v.cell.borrow().as_ref_to_value());
-    } else {
-        workspace.push_value(&Value::Empty);
-    }
+    let value = match workspace.try_get_variable(fib.function_description.name.as_str()) {
+        Some(v) => v.cell.borrow().as_ref_to_value().clone(),
+        None => Value::Empty,
+    };
+    runtime::debug::log_return(&fib.function_description, &value, workspace)?;
+    workspace.push_value(&value);
     workspace.end_invocation();
     Ok(())
 }
 
-fn execute_identifier_by_reference(r: &ReferenceNode, workspace: &WorkSpace) -> Result<(),String> {
+fn execute_identifier_by_reference(r: &ReferenceNode, workspace: &WorkSpace) -> Result<(),PplError> {
     execute_value(&Value::from_reference(r, workspace)?, workspace)
 }
 
-fn execute_operation(op: &OperationNode, workspace: &WorkSpace) -> Result<(),String> {
+fn execute_operation(op: &OperationNode, workspace: &WorkSpace) -> Result<(),PplError> {
 
     //  Look for a function that's compatible with the argument list
 
     let candidates = workspace.try_get_functions(&op.get_name());
+    if workspace.is_library_function(&op.get_name()) && workspace.try_get_user_function(&op.get_name()).is_none() {
+        if let Some(f) = select_homonym(&candidates, op.get_actual_argument_list(), workspace)? {
+            return execute_function(&f, op.get_actual_argument_list(), workspace);
+        }
+    }
     match  candidates.len() {
-        0 => Err(format!("Function '{}' not found", op.get_name())),
+        0 => Err(ppl_error!(Name, "Function '{}' not found", op.get_name())),
         _ => {
             for candidate in &candidates {
                 match candidate {
@@ -298,17 +307,43 @@ fn execute_operation(op: &OperationNode, workspace: &WorkSpace) -> Result<(),Str
                     _ => panic!("internal error"),
                 }
             }
-            Err(format!("Wrong number of arguments to function {}", op.get_name()))
+            Err(ppl_error!(Argument, "Wrong number of arguments to function {}", op.get_name()))
         },
     }
 }
 
-fn execute_identifier_by_value(r: &ReferenceNode, workspace: &WorkSpace) -> Result<(),String> {
+//  The homonyms of a library function that take the same number of arguments are told apart by the datatypes of
+//  the values passed, so that sqrt(2) and sqrt(dbl(2)) can call different functions.  The first whose arguments
+//  the values match exactly is chosen, or failing that the first they convert to.  If there's neither, the caller
+//  falls back on the first with the right number of arguments, which reports the conversion error.  Other homonyms,
+//  and a user function that hides a library one, are only told apart by the number of arguments.
+
+fn select_homonym(candidates: &[GeneralSymbol], actual_argument_list: &Vec<usize>, workspace: &WorkSpace) -> Result<Option<Rc<FunctionDescription>>,PplError> {
+    let homonyms: Vec<&Rc<FunctionDescription>> = candidates.iter().filter_map(|candidate| match candidate {
+        GeneralSymbol::Function(f) if f.is_compatible_function(actual_argument_list) => Some(f),
+        _ => None,
+    }).collect();
+    if homonyms.len() < 2 {
+        return Ok(None);
+    }
+
+    let actual_argument_values = workspace.peek_values(actual_argument_list.len());
+    for exactly in [true, false] {
+        for f in &homonyms {
+            if f.accepts_arguments(&actual_argument_values, exactly, workspace)? {
+                return Ok(Some((*f).clone()));
+            }
+        }
+    }
+    Ok(None)
+}
+
+fn execute_identifier_by_value(r: &ReferenceNode, workspace: &WorkSpace) -> Result<(),PplError> {
     workspace.push_value(&Value::from_reference(r, workspace)?);
     Ok(())
 }
 
-fn execute_selection_by_reference(workspace: &WorkSpace) -> Result<(), String> {
+fn execute_selection_by_reference(workspace: &WorkSpace) -> Result<(),PplError> {
     let mut index = workspace.pop_value();
     if let Value::Symbol(symbol) = &index {
         index = evaluate_identifier_by_value(symbol, workspace)?;
@@ -317,7 +352,7 @@ fn execute_selection_by_reference(workspace: &WorkSpace) -> Result<(), String> {
     execute_selection_by_reference_internal(&base_value, &index, workspace)
 }
 
-fn execute_selection_by_reference_internal(base_value: &Value, index: &Value, workspace: &WorkSpace) -> Result<(), String> {
+fn execute_selection_by_reference_internal(base_value: &Value, index: &Value, workspace: &WorkSpace) -> Result<(),PplError> {
     match &base_value {
         Value::Sequence(ref seq) => {
             return seq.access_cell_by_reference(index.as_i32()?, workspace);
@@ -326,7 +361,7 @@ fn execute_selection_by_reference_internal(base_value: &Value, index: &Value, wo
             if let Value::Selector(ref selector) = index {
                 return structure.access_field_by_reference(selector, workspace);
             } else {
-                return Err(format!("{} is not a field selector", index));
+                return Err(ppl_error!(Index, "{} is not a field selector", index));
             }
         },
         Value::Symbol(symbol) => {
@@ -343,19 +378,19 @@ fn execute_selection_by_reference_internal(base_value: &Value, index: &Value, wo
                                 if workspace.debug_options.borrow().is_set(&crate::workspace::debug::DebugOption::DataConversion) {
                                     dbg!(&index);
                                 }
-                            return Err(format!("{} is not a field selector", index));
+                            return Err(ppl_error!(Index, "{} is not a field selector", index));
                             }
                         },
                         _ => {},
                     }  
                 },
-                GeneralSymbol::Unresolved(u) => return Err(format!("{} not found", u)),
+                GeneralSymbol::Unresolved(u) => return Err(ppl_error!(Name, "{} not found", u)),
                 _ => {},
             }
             if workspace.debug_options.borrow().is_set(&crate::workspace::debug::DebugOption::DataConversion) {
                 dbg!(&base_value);
             }
-            Err(format!("{} is not indexable", base_value))
+            Err(ppl_error!(Index, "{} is not indexable", base_value))
         },
         Value::ValueByReference(v) => {
             execute_selection_by_reference_internal(&*v.cell.borrow().as_ref_to_value(), index, workspace)
@@ -367,7 +402,7 @@ fn execute_selection_by_reference_internal(base_value: &Value, index: &Value, wo
             if workspace.debug_options.borrow().is_set(&crate::workspace::debug::DebugOption::DataConversion) {
                 dbg!(&base_value);
             }
-             Err(format!("{} is not indexable", base_value))
+             Err(ppl_error!(Index, "{} is not indexable", base_value))
         },
     }
 }
@@ -423,9 +458,9 @@ fn execute_selection_by_reference_internal(base_value: &Value, index: &Value, wo
 //     }
 // }
 
-fn execute_selector(s: &Rc<SelectorDescription>, actual_argument_list: &Vec<usize>, workspace: &WorkSpace) -> Result<(),String> {
+fn execute_selector(s: &Rc<SelectorDescription>, actual_argument_list: &Vec<usize>, workspace: &WorkSpace) -> Result<(),PplError> {
     if actual_argument_list.len() != 1 {
-        return Err(format!("Selectors can only operate on one structure"));
+        return Err(ppl_error!(Index, "Selectors can only operate on one structure"));
     }
 
     //  Assemble the actual argument values
@@ -449,7 +484,7 @@ fn execute_selector(s: &Rc<SelectorDescription>, actual_argument_list: &Vec<usiz
                         }
                     }
                 }
-                return Err(format!("{} does not have a {} field", structure.as_string(), s.member.as_string()));
+                return Err(ppl_error!(Index, "{} does not have a {} field", structure.as_string(), s.member.as_string()));
             },
             Value::ValueByReference(cell_reference) => {
                 if let Value::Structure(structure) =  &*cell_reference.cell.borrow().as_ref_to_value() {
@@ -463,34 +498,34 @@ fn execute_selector(s: &Rc<SelectorDescription>, actual_argument_list: &Vec<usiz
                             }
                         }
                     }
-                    return Err(format!("{} does not have a {} field", structure.as_string(), s.member.as_string()));
+                    return Err(ppl_error!(Index, "{} does not have a {} field", structure.as_string(), s.member.as_string()));
                 } else {
                     if workspace.debug_options.borrow().is_set(&crate::workspace::debug::DebugOption::DataConversion) {
                         dbg!(&actual_argument_value);
                     }
-                        return Err(format!("Selectors can only target a structure"))
+                        return Err(ppl_error!(Index, "Selectors can only target a structure"))
                 }
             },
             _ => {
                 if workspace.debug_options.borrow().is_set(&crate::workspace::debug::DebugOption::DataConversion) {
                     dbg!(&actual_argument_value);
                 }
-                return Err(format!("Selectors can only target a structure"))
+                return Err(ppl_error!(Index, "Selectors can only target a structure"))
             },
         }
     }
 }
 
-fn execute_selector_reference(selector: &Rc<SelectorDescription>, workspace: &WorkSpace) -> Result<(),String> {
+fn execute_selector_reference(selector: &Rc<SelectorDescription>, workspace: &WorkSpace) -> Result<(),PplError> {
     execute_value(&Value::Selector(SelectorInstance::from_description(&selector)), workspace)
 }
 
-pub fn execute_statement_end(workspace: &WorkSpace) -> Result<(),String> {
+pub fn execute_statement_end(workspace: &WorkSpace) -> Result<(),PplError> {
     let invocation = workspace.current_invocation().unwrap();
     match workspace.try_pop_value(invocation.get_base_stack_size()) {
         Some(value) => {
             match value {
-                Value::Selector(sel) => return Err(format!("{} is not a value", sel.member)),
+                Value::Selector(sel) => return Err(ppl_error!(Type, "{} is not a value", sel.member)),
                 Value::Symbol(symbol) => {
                     if let GeneralSymbol::Function(_) = symbol.as_symbol() {
                         // We're going to evaluate the function, and so doing, need to re-execute this node
@@ -570,17 +605,25 @@ pub fn execute_statement_end(workspace: &WorkSpace) -> Result<(),String> {
 //     }
 // }
 
-fn execute_user_function(f: &Rc<FunctionDescription>, actual_argument_values: &Vec<Value>, workspace: &WorkSpace) -> Result<(),String> {
+fn execute_user_function(f: &Rc<FunctionDescription>, actual_argument_values: &Vec<Value>, workspace: &WorkSpace) -> Result<(),PplError> {
+    workspace.limits.borrow().check_depth(workspace.get_call_depth() + 1)?;
     functions::prepare_udf (f, actual_argument_values, workspace)?;
+    runtime::debug::log_call(f, actual_argument_values, workspace)?;
+    if let Some(profile) = workspace.profile.borrow_mut().as_mut() {
+        profile.record_call(f.name.as_str());
+    }
+    if let Some(coverage) = workspace.coverage.borrow_mut().as_mut() {
+        coverage.record_call(f.name.as_str());
+    }
     functions::execute_udf (f, workspace)?;
     Ok(())
 }
 
-fn execute_value(value: &Value, workspace: &WorkSpace) -> Result<(),String> {
+fn execute_value(value: &Value, workspace: &WorkSpace) -> Result<(),PplError> {
     workspace.push_value(value);
     Ok(())
 }
 
-fn execute_variable_reference_by_value(v: &Rc<VariableDescription>, workspace: &WorkSpace) -> Result<(),String> {
+fn execute_variable_reference_by_value(v: &Rc<VariableDescription>, workspace: &WorkSpace) -> Result<(),PplError> {
     execute_value(&*v.cell.borrow().as_ref_to_value(), workspace)
 }
@@ -5,15 +5,16 @@ use std::rc::Rc;
 
 use crate::parser::tree::{DefinitionNode, DefinitionType, SequenceDefinition, StructureDefinition};
 use crate::symbols::datatype::RootDataType;
-use crate::symbols::metadata::{MetaDataTypeName, MetaDataType, MetaSequence, MetaStructureMember, SelectorDescription, MetaStructure, FunctionDescription, MetaAlternate};
+use crate::symbols::metadata::{MetaDataTypeName, MetaDataType, MetaSequence, MetaStructureMember, SelectorDescription, MetaStructure, FunctionDescription, FunctionImplementation, MetaAlternate};
 use crate::symbols::name::Name;
 use crate::workspace::WorkSpace;
+use crate::error::PplError;
 
 
 
 
 
-fn execute_alternate_definition(name: &String, alternates: &Vec<usize>, workspace: &WorkSpace) -> Result<(),String> {
+fn execute_alternate_definition(name: &String, alternates: &Vec<usize>, workspace: &WorkSpace) -> Result<(),PplError> {
 
     //  Assemble the alternates
 
@@ -30,9 +31,18 @@ fn execute_alternate_definition(name: &String, alternates: &Vec<usize>, workspac
     Ok(())
 }
 
-pub fn execute_definition(d: &DefinitionNode, workspace: &WorkSpace) -> Result<(),String> {
-    if workspace.contains_any(d.as_string().as_str()) {
-        return Err(format!("{} is already defined", d.as_string()));
+pub fn execute_definition(d: &DefinitionNode, workspace: &WorkSpace) -> Result<(),PplError> {
+    if workspace.contains_any(d.as_string().as_str()) && !is_hiding_library_function(d, workspace) {
+
+        //  Programs written before complex was built in define it themselves, which is harmless if they define
+        //  it the same way
+
+        if let DefinitionType::Structure(structure_definition) = d.get() {
+            if is_existing_structure(&d.as_string(), structure_definition, workspace) {
+                return Ok(());
+            }
+        }
+        return Err(ppl_error!(Name, "{} is already defined", d.as_string()));
     }
 
     match  d.get() {
@@ -51,17 +61,31 @@ pub fn execute_definition(d: &DefinitionNode, workspace: &WorkSpace) -> Result<(
     }
 }
 
-fn execute_function_definition(name: &String, def: &FunctionDescription, workspace: &WorkSpace) -> Result<(),String> {
+fn execute_function_definition(name: &String, def: &FunctionDescription, workspace: &WorkSpace) -> Result<(),PplError> {
     match workspace.try_get_any(name.as_str()) {
         crate::workspace::GeneralSymbol::Unresolved(_) => {
             workspace.add_user_function(name, def);
             Ok(())
         },
-        _ => Err(format!("{} already exists", name)),
+        crate::workspace::GeneralSymbol::Function(f) if is_library_function(&f, workspace) => {
+            workspace.add_user_function(name, def);
+            Ok(())
+        },
+        _ => Err(ppl_error!(Name, "{} already exists", name)),
     }
 }
 
-fn execute_sequence_definition(name: &String, def: &SequenceDefinition, workspace: &WorkSpace) -> Result<(),String> {
+//  A program's own function can take the name of one in the standard library, such as max, and hides it
+
+fn is_hiding_library_function(d: &DefinitionNode, workspace: &WorkSpace) -> bool {
+    matches!(d.get(), DefinitionType::Function(_)) && workspace.is_library_function(d.as_string().as_str())
+}
+
+fn is_library_function(f: &FunctionDescription, workspace: &WorkSpace) -> bool {
+    matches!(f.implementation_class, FunctionImplementation::System(_)) && workspace.is_library_function(f.name.as_str())
+}
+
+fn execute_sequence_definition(name: &String, def: &SequenceDefinition, workspace: &WorkSpace) -> Result<(),PplError> {
 
     //  And define the new datatype
 
@@ -71,7 +95,7 @@ fn execute_sequence_definition(name: &String, def: &SequenceDefinition, workspac
     Ok(())
 }
 
-fn execute_structure_definition(name: &String, def: &StructureDefinition, workspace: &WorkSpace) -> Result<(),String> {
+fn execute_structure_definition(name: &String, def: &StructureDefinition, workspace: &WorkSpace) -> Result<(),PplError> {
 
     // Validate the members
 
@@ -83,23 +107,52 @@ fn execute_structure_definition(name: &String, def: &StructureDefinition, worksp
         meta_members.push(meta_member);
     }
 
-    //  And define the new datatype
+    define_structure(MetaStructure { name: Name::from_string(name), members: meta_members }, workspace);
+    Ok(())
+}
+
+fn is_existing_structure(name: &str, def: &StructureDefinition, workspace: &WorkSpace) -> bool {
+    let Some(datatype) = workspace.try_get_datatype(name) else {
+        return false;
+    };
+    match datatype.root_data_type() {
+        RootDataType::Structure(structure) => structure.members.len() == def.members.len() &&
+            structure.members.iter().zip(&def.members).all(|(existing, member)|
+                existing.name.as_str().eq_ignore_ascii_case(member.name.as_str()) &&
+                existing.data_type.as_string().eq_ignore_ascii_case(&member.datatype.as_string())),
+        _ => false,
+    }
+}
+
+//  Adds a structure datatype, and the selectors for its members
 
-    for meta_member in &meta_members {
+pub fn define_structure(structure: MetaStructure, workspace: &WorkSpace) {
+    let name = structure.name.as_string();
+    for meta_member in &structure.members {
         let selector_description = workspace.try_get_selector(&meta_member.name.as_string());
         match selector_description {
             Some(_) => {
-                selector_description.unwrap().structures.borrow_mut().push(Name::from_string(name));
+                selector_description.unwrap().structures.borrow_mut().push(Name::from_string(&name));
             },
             None => {
                 workspace.add_selector(meta_member.name.as_str(), 
-                    SelectorDescription { member: Name::from_name(&meta_member.name), structures: Rc::new(RefCell::new(vec![ Name::from_string(name)]))});
+                    SelectorDescription { member: Name::from_name(&meta_member.name), structures: Rc::new(RefCell::new(vec![ Name::from_string(&name)]))});
             },
         }
     }
 
     workspace.add_datatype(name.as_str(), 
-        MetaDataType::from_string(name, 
-            RootDataType::Structure(MetaStructure { name: Name::from_string(name), members: meta_members })));
-    Ok(())
+        MetaDataType::from_string(&name, RootDataType::Structure(structure)));
+}
+
+pub fn is_function_definition_start(line: &str) -> bool {
+    //  A line starting with $ (and no equal sign) opens a function definition, which runs until a line
+    //  holding just $, or an empty line
+
+    let line = line.trim_end();
+    line.len() > 1 && line.starts_with('$') && !line.contains('=')
+}
+
+pub fn is_function_definition_end(line: &str) -> bool {
+    line.trim().is_empty() || line.trim() == "$"
 }
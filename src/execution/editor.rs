@@ -0,0 +1,277 @@
+//  This module implements PPL's line editor, which reopens an existing function with $name.
+//
+//  While editing, lines are addressed by number in square brackets:
+//
+//      [3] text    --  replace line 3 (or add it, if there is no line 3)
+//      [2.5] text  --  insert a line between lines 2 and 3
+//      [~4]        --  delete line 4
+//      [3]         --  make line 3 the current line, without changing it
+//      [?]         --  display the function as it currently stands
+//      text        --  replace (or add) the current line
+//      $           --  end editing, renumber the lines and redefine the function
+//
+//  Line numbers don't change while editing; fractional lines are only renumbered when editing ends. Each
+//  change is re-parsed as it is made so that syntax errors show up right away, but the function itself is
+//  only replaced when editing ends, and only if the final text parses.
+
+use std::rc::Rc;
+
+use crate::{lexical::LineNumber,
+    parser::Parser,
+    symbols::metadata::{FunctionBody, FunctionDescription, FunctionImplementation},
+    workspace::WorkSpace};
+
+use super::{evaluate_internal, runtime::statement::Statement};
+use crate::error::PplError;
+
+
+//  Positions are held in thousandths of a line, so that [2.5] and [2.25] sort without floating point fuzz
+
+const POSITION_SCALE: u32 = 1000;
+
+#[derive(Debug,Clone,Copy,PartialEq,Eq,PartialOrd,Ord)]
+struct Position(u32);
+
+impl Position {
+    fn from_line_number(line_number: LineNumber) -> Position {
+        Position(line_number * POSITION_SCALE)
+    }
+
+    fn parse(s: &str) -> Result<(Position, u32),PplError> {
+        let invalid = || ppl_error!(Argument, "{} is not a valid line number", s);
+        let (whole, fraction) = match s.split_once('.') {
+            Some((whole, fraction)) => (whole, fraction),
+            None => (s, ""),
+        };
+        if whole.is_empty() || fraction.len() > 3 || !whole.chars().chain(fraction.chars()).all(|c| c.is_ascii_digit()) {
+            return Err(invalid());
+        }
+        let whole = whole.parse::<u32>().map_err(|_| invalid())?;
+        let mut thousandths = 0;
+        let mut increment = POSITION_SCALE;
+        for c in fraction.chars() {
+            increment /= 10;
+            thousandths += c.to_digit(10).unwrap() * increment;
+        }
+        whole.checked_mul(POSITION_SCALE)
+            .and_then(|p| p.checked_add(thousandths))
+            .map(|p| (Position(p), increment))
+            .ok_or_else(invalid)
+    }
+
+    //  The position the next line typed goes to, which can run off the end of the numbers
+
+    fn next(self, increment: u32) -> Result<Position,PplError> {
+        self.0.checked_add(increment)
+            .map(Position)
+            .ok_or_else(|| ppl_error!(Argument, "{} is not a valid line number", as_line_number_text(self.0 as u64 + increment as u64)))
+    }
+}
+
+impl std::fmt::Display for Position {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", as_line_number_text(self.0 as u64))
+    }
+}
+
+fn as_line_number_text(thousandths: u64) -> String {
+    let scale = POSITION_SCALE as u64;
+    let fraction = thousandths % scale;
+    if fraction == 0 {
+        format!("{}", thousandths / scale)
+    } else {
+        format!("{}.{}", thousandths / scale, format!("{:03}", fraction).trim_end_matches('0'))
+    }
+}
+
+struct EditorLine {
+    position: Position,
+    original_line_number: Option<LineNumber>,   //  The line this was in the function as opened, if any
+    text: String,
+}
+
+pub struct FunctionEditor {
+    name: String,
+    header: String,
+    lines: Vec<EditorLine>,
+    current_position: Position,
+    increment: u32,
+    stops: Vec<LineNumber>,
+    traces: Vec<LineNumber>,
+}
+
+
+impl FunctionEditor {
+    pub fn apply(&mut self, command: &str, workspace: &WorkSpace) -> Result<String,PplError> {
+        let command = command.trim_end_matches(['\r', '\n']);
+        let text = match command.trim_start().strip_prefix('[') {
+            Some(rest) => {
+                let (address, text) = rest.split_once(']').ok_or_else(|| ppl_error!(Syntax, "Missing ] in {}", command))?;
+                let address = address.trim();
+                if address == "?" {
+                    return Ok(self.as_listing());
+                }
+                if let Some(address) = address.strip_prefix('~') {
+                    let (position, _) = Position::parse(address.trim())?;
+                    return self.delete(position, workspace);
+                }
+                let (position, increment) = Position::parse(address)?;
+                self.current_position = position;
+                self.increment = increment;
+                if text.trim().is_empty() {
+                    return Ok(String::new());
+                }
+                text.trim_start()
+            },
+            None => command,
+        };
+        if self.current_position.0 == 0 && !function_name(text).eq_ignore_ascii_case(&self.name) {
+            return Err(ppl_error!(Argument, "The header can't rename {}; define the new function separately", self.name));
+        }
+        self.replace(text)?;
+        Ok(self.validate(workspace))
+    }
+
+    fn as_listing(&self) -> String {
+        let mut result = format!("{}\n", self.header);
+        for line in &self.lines {
+            result += format!("[{}] {}\n", line.position, line.text).as_str();
+        }
+        result
+    }
+
+    pub fn as_source(&self) -> String {
+        let mut source = self.header.clone();
+        for line in &self.lines {
+            source += "\n";
+            source += line.text.as_str();
+        }
+        source += "\n$";
+        source
+    }
+
+    pub fn close(self, workspace: &WorkSpace) -> Result<(),PplError> {
+        let source = self.as_source();
+        Parser::new(source.as_str(), workspace).parse(source.as_str())?;
+
+        let original = workspace.try_get_user_function(&self.name);
+        workspace.remove(&self.name);
+        if let Err(e) = evaluate_internal(source.as_str(), workspace) {
+            if let Some(original) = original {
+                workspace.add_user_function_by_reference(&self.name, original);
+            }
+            return Err(e);
+        }
+
+        //  Carry the stop and trace settings over to wherever their lines ended up.  Each statement is found by the
+        //  line it starts on, since one that spans lines, like an if and its else, has no statement on the others.
+
+        if let Some(body) = workspace.try_get_user_function(&self.name).and_then(|f| user_function_body(&f)) {
+            for statement_index in 1..=body.executable.get_statement_count() {
+                let statement = body.executable.get_statement(statement_index).unwrap();
+                let line = (statement.as_line_number() as usize).checked_sub(1).and_then(|index| self.lines.get(index));
+                if let Some(original_line_number) = line.and_then(|line| line.original_line_number) {
+                    statement.set_stop(self.stops.contains(&original_line_number));
+                    statement.set_trace(self.traces.contains(&original_line_number));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn delete(&mut self, position: Position, workspace: &WorkSpace) -> Result<String,PplError> {
+        match self.lines.iter().position(|line| line.position == position) {
+            Some(index) => {
+                self.lines.remove(index);
+                Ok(self.validate(workspace))
+            },
+            None => Err(ppl_error!(Runtime, "There is no line {}", position)),
+        }
+    }
+
+    pub fn is_edit_request(line: &str, workspace: &WorkSpace) -> Option<String> {
+        let name = line.trim().strip_prefix('$')?.trim();
+        if !name.is_empty() && crate::symbols::name::Name::is_name_a_legal_identifier(name) && workspace.try_get_user_function(name).is_some() {
+            Some(String::from(name))
+        } else {
+            None
+        }
+    }
+
+    pub fn open(name: &str, workspace: &WorkSpace) -> Result<FunctionEditor,PplError> {
+        let f = workspace.try_get_user_function(name).ok_or_else(|| ppl_error!(Name, "{} is not a user function", name))?;
+        let body = user_function_body(&f).ok_or_else(|| ppl_error!(Name, "{} is not a user function", name))?;
+
+        let mut source_lines = body.executable.as_source().lines();
+        let header = String::from(source_lines.next().unwrap_or_default().trim());
+        let mut lines = Vec::new();
+        for (line_number, text) in (1..).zip(source_lines) {
+            lines.push(EditorLine { position: Position::from_line_number(line_number), original_line_number: Some(line_number), text: String::from(text.trim()) });
+        }
+
+        //  The closing $ is supplied again when the function is rebuilt
+
+        if lines.last().is_some_and(|line| line.text == "$") {
+            lines.pop();
+        }
+
+        let mut stops = Vec::new();
+        let mut traces = Vec::new();
+        for statement_index in 1..=body.executable.get_statement_count() {
+            let statement: Rc<Statement> = body.executable.get_statement(statement_index).unwrap();
+            if statement.is_stop_set() {
+                stops.push(statement.as_line_number());
+            }
+            if statement.is_trace_set() {
+                traces.push(statement.as_line_number());
+            }
+        }
+
+        let next_line_number = lines.len() as LineNumber + 1;
+        Ok(FunctionEditor {
+            name: String::from(name),
+            header,
+            lines,
+            current_position: Position::from_line_number(next_line_number),
+            increment: POSITION_SCALE,
+            stops,
+            traces })
+    }
+
+    pub fn prompt(&self) -> String {
+        format!("[{}] ", self.current_position)
+    }
+
+    fn replace(&mut self, text: &str) -> Result<(),PplError> {
+        let next_position = self.current_position.next(self.increment)?;
+        if self.current_position.0 == 0 {
+            self.header = String::from(text);
+        } else {
+            match self.lines.binary_search_by(|line| line.position.cmp(&self.current_position)) {
+                Ok(index) => self.lines[index].text = String::from(text),
+                Err(index) => self.lines.insert(index, EditorLine { position: self.current_position, original_line_number: None, text: String::from(text) }),
+            }
+        }
+        self.current_position = next_position;
+        Ok(())
+    }
+
+    fn validate(&self, workspace: &WorkSpace) -> String {
+        let source = self.as_source();
+        match Parser::new(source.as_str(), workspace).parse(source.as_str()) {
+            Ok(_) => String::new(),
+            Err(e) => e.to_string(),
+        }
+    }
+}
+
+fn function_name(header: &str) -> String {
+    header.trim_start_matches('$').chars().take_while(|c| c.is_alphanumeric() || *c == '.').collect()
+}
+
+fn user_function_body(f: &Rc<FunctionDescription>) -> Option<Rc<FunctionBody>> {
+    match &f.implementation_class {
+        FunctionImplementation::User(body) => Some(body.clone()),
+        FunctionImplementation::System(_) => None,
+    }
+}
@@ -4,11 +4,13 @@ use std::cell::RefCell;
 use std::rc::Rc;
 
 use crate::{execution::value::{Value, Cell},
+            parser::Parser,
             symbols::{SymbolTable, 
                       metadata::{FunctionArgumentList, FunctionDescription, FunctionImplementation, VariableDescription, ArgumentMechanism}, name::Name},
             workspace::{WorkSpace, debug::DebugOption}};
 
 use super::runtime::statement::Statement;
+use crate::error::PplError;
 
 
 
@@ -30,7 +32,7 @@ impl FunctionInvocationBlock {
 
 
 
-pub fn prepare_udf(f: &Rc<FunctionDescription>, actual_argument_values: &Vec<Value>, workspace: &WorkSpace) -> Result<Value,String> {
+pub fn prepare_udf(f: &Rc<FunctionDescription>, actual_argument_values: &Vec<Value>, workspace: &WorkSpace) -> Result<Value,PplError> {
     if let FunctionImplementation::User(body) = &f.implementation_class {
         if body.executable.get_statement_count() < 1 {
             return Ok(Value::Empty);
@@ -93,13 +95,13 @@ pub fn prepare_udf(f: &Rc<FunctionDescription>, actual_argument_values: &Vec<Val
         
             return Ok(Value::Empty)
         } else {
-            return Err(format!("Variable length argument lists are not supported for user function {}", f.name.as_string()))
+            return Err(ppl_error!(Argument, "Variable length argument lists are not supported for user function {}", f.name.as_string()))
         }
     }
     panic!("internal error");
 }
 
-pub fn execute_udf(_f: &Rc<FunctionDescription>, _workspace: &WorkSpace) -> Result<(),String> {
+pub fn execute_udf(_f: &Rc<FunctionDescription>, _workspace: &WorkSpace) -> Result<(),PplError> {
     /* let invocation = workspace.current_invocation().unwrap();
     let fib = invocation.get_fib().unwrap();
 
@@ -122,28 +124,61 @@ pub fn execute_udf(_f: &Rc<FunctionDescription>, _workspace: &WorkSpace) -> Resu
     Ok(())
 }
 
-pub fn stop(name: &Value, lines: &[Value], workspace: &WorkSpace) -> Result<(),String> {
+pub fn stop(name: &Value, lines: &[Value], workspace: &WorkSpace) -> Result<(),PplError> {
     statement_internal(name, lines, |s| { s.set_stop(true); }, workspace)
 }
 
-pub fn trace(name: &Value, lines: &[Value], workspace: &WorkSpace) -> Result<(),String> {
+pub fn stop_after(name: &Value, line: &Value, hits: &Value, workspace: &WorkSpace) -> Result<(),PplError> {
+    let statement = get_statement(name, line, workspace)?;
+    statement.set_stop(true);
+    statement.set_stop_after(hits.as_usize()? as u32);
+    Ok(())
+}
+
+pub fn stop_if(name: &Value, line: &Value, condition: &Value, workspace: &WorkSpace) -> Result<(),PplError> {
+    let statement = get_statement(name, line, workspace)?;
+    let condition = condition.as_string();
+    let condition = Parser::new(condition.as_str(), workspace).parse(condition.as_str())?;
+    statement.set_stop(true);
+    statement.set_stop_condition(condition);
+    Ok(())
+}
+
+pub fn trace(name: &Value, lines: &[Value], workspace: &WorkSpace) -> Result<(),PplError> {
     statement_internal(name, lines, |s| { s.set_trace(true); }, workspace)
 }
 
-pub fn unstop(name: &Value, lines: &[Value], workspace: &WorkSpace) -> Result<(),String> {
+pub fn unstop(name: &Value, lines: &[Value], workspace: &WorkSpace) -> Result<(),PplError> {
     statement_internal(name, lines, |s| { s.set_stop(false); }, workspace)
 }
 
-pub fn untrace(name: &Value, lines: &[Value], workspace: &WorkSpace) -> Result<(),String> {
+pub fn untrace(name: &Value, lines: &[Value], workspace: &WorkSpace) -> Result<(),PplError> {
     statement_internal(name, lines, |s| { s.set_trace(false); }, workspace)
 }
 
-fn statement_internal(name: &Value, lines: &[Value], setting: fn(statement: Rc<Statement>), workspace: &WorkSpace) -> Result<(),String> {
+fn get_statement(name: &Value, line: &Value, workspace: &WorkSpace) -> Result<Rc<Statement>,PplError> {
+    match workspace.try_get_function(name.as_string().as_str()) {
+        Some(f) => {
+            match &f.implementation_class {
+                FunctionImplementation::System(_) => Err(ppl_error!(Runtime, "System functions cannot be stopped")),
+                FunctionImplementation::User(body) => {
+                    match body.executable.get_statement_by_line_number(line.as_line_number()?) {
+                        Some(statement) => Ok(statement),
+                        None => Err(ppl_error!(Runtime, "Invalid line number {}", line)),
+                    }
+                },
+            }
+        },
+        None => Err(ppl_error!(Name, "Function {} not found", name))
+    }
+}
+
+fn statement_internal(name: &Value, lines: &[Value], setting: fn(statement: Rc<Statement>), workspace: &WorkSpace) -> Result<(),PplError> {
     let opt_f = workspace.try_get_function(name.as_string().as_str());
     match opt_f {
         Some(f) => {
             match &f.implementation_class {
-                FunctionImplementation::System(_) => Err(format!("System functions cannot be stopped")),
+                FunctionImplementation::System(_) => Err(ppl_error!(Runtime, "System functions cannot be stopped")),
                 FunctionImplementation::User(body) => {
                     if lines.len() == 0 {
                         body.executable.process_on_each_statement(setting);
@@ -151,7 +186,7 @@ fn statement_internal(name: &Value, lines: &[Value], setting: fn(statement: Rc<S
                         for line in lines {
                             match body.executable.get_statement_by_line_number(line.as_line_number()?) {
                                 Some(statement) => setting(statement),
-                                None => return Err(format!("Invalid line number {}", line)),
+                                None => return Err(ppl_error!(Runtime, "Invalid line number {}", line)),
                             }
                         }
                     }
@@ -159,6 +194,6 @@ fn statement_internal(name: &Value, lines: &[Value], setting: fn(statement: Rc<S
                 }
             }
         },
-        None => Err(format!("Function {} not found", name))
+        None => Err(ppl_error!(Name, "Function {} not found", name))
     }
 }
@@ -13,9 +13,11 @@
 //  the order of statements.
 
 
+pub mod coverage;
 pub mod debug;
 pub mod executable;
 pub mod invocation;
+pub mod profile;
 pub mod stack_usage;
 pub mod statement;
 
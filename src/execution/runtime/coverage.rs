@@ -0,0 +1,154 @@
+//  This module records which statements of user functions have executed, and which way each conditional branch
+//  (the cbranch an if or while compiles to) has gone.  cbranch branches when its operand is true, and if and
+//  while hand it their condition negated, so a branch taken is the condition false.
+
+use std::{collections::HashMap, fmt::Write, rc::Rc};
+
+use crate::{lexical::LineNumber, parser::tree::Node, symbols::metadata::{FunctionDescription, FunctionImplementation}, utility::convert_escape_sequences};
+
+use super::{executable::Executable, statement::Statement};
+
+
+#[derive(Default)]
+pub struct Coverage {
+    lines: HashMap<(String, LineNumber), u64>,
+    branches: HashMap<(String, usize), (u64, u64)>,     //  Keyed by node index; the times the condition was true, then false
+    calls: HashMap<String, u64>,
+    running: bool,
+}
+
+impl Coverage {
+    pub fn new() -> Coverage {
+        Coverage { running: true, ..Default::default() }
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.running
+    }
+
+    //  Stopped coverage keeps what it's recorded for reporting
+
+    pub fn stop(&mut self) {
+        self.running = false;
+    }
+
+    pub fn record_call(&mut self, function: &str) {
+        if self.running {
+            *self.calls.entry(String::from(function)).or_default() += 1;
+        }
+    }
+
+    pub fn record_statement(&mut self, function: &str, statement: &Statement) {
+        if self.running {
+            *self.lines.entry((String::from(function), statement.as_line_number())).or_default() += 1;
+        }
+    }
+
+    pub fn record_branch(&mut self, function: &str, node_index: usize, branched: bool) {
+        if self.running {
+            let outcomes = self.branches.entry((String::from(function), node_index)).or_default();
+            if branched {
+                outcomes.1 += 1;
+            } else {
+                outcomes.0 += 1;
+            }
+        }
+    }
+
+    //  A listing of each function, with the number of times each line executed, or ##### if it never did, and
+    //  the outcomes of any condition on the line
+
+    pub fn listing(&self, functions: &[Rc<FunctionDescription>]) -> String {
+        let mut listing = String::new();
+        for (name, executable) in user_functions(functions) {
+            let statements = statements(executable);
+            let executed = statements.iter().filter(|statement| self.line_count(name, statement).is_some()).count();
+            let outcomes: Vec<(u64, u64)> = statements.iter().flat_map(|statement| self.statement_branches(name, executable, statement)).collect();
+            let outcomes_seen = outcomes.iter().map(|(t, f)| (*t > 0) as usize + (*f > 0) as usize).sum::<usize>();
+            let _ = writeln!(listing, "@{}\t{} of {} lines, {} of {} branches", name, executed, statements.len(), outcomes_seen, outcomes.len() * 2);
+
+            for statement in &statements {
+                let count = match self.line_count(name, statement) {
+                    Some(count) => count.to_string(),
+                    None => String::from("#####"),
+                };
+                let _ = write!(listing, "{:>9}  [{}] {}", count, statement.as_line_number(), first_line(statement));
+                for (true_count, false_count) in self.statement_branches(name, executable, statement) {
+                    let _ = write!(listing, "\t[true {}, false {}]", true_count, false_count);
+                }
+                listing += "\n";
+            }
+            listing += "\n";
+        }
+        listing
+    }
+
+    //  The same in lcov's tracefile format, with a record per function.  Each condition is a block with two
+    //  branches, true and false.
+
+    pub fn lcov(&self, functions: &[Rc<FunctionDescription>]) -> String {
+        let mut lcov = String::new();
+        for (name, executable) in user_functions(functions) {
+            let statements = statements(executable);
+            let start = statements.first().map(|statement| statement.as_line_number()).unwrap_or(0);
+            let calls = self.calls.get(name).copied().unwrap_or(0);
+            let _ = writeln!(lcov, "TN:\nSF:{}\nFN:{},{}\nFNDA:{},{}\nFNF:1\nFNH:{}", name, start, name, calls, name, (calls > 0) as u32);
+
+            let (mut branches_found, mut branches_hit) = (0, 0);
+            for statement in &statements {
+                for (block, (true_count, false_count)) in self.statement_branches(name, executable, statement).into_iter().enumerate() {
+                    for (branch, count) in [true_count, false_count].into_iter().enumerate() {
+                        let taken = if self.line_count(name, statement).is_some() { count.to_string() } else { String::from("-") };
+                        let _ = writeln!(lcov, "BRDA:{},{},{},{}", statement.as_line_number(), block, branch, taken);
+                        branches_found += 1;
+                        branches_hit += (count > 0) as u32;
+                    }
+                }
+            }
+            let _ = writeln!(lcov, "BRF:{}\nBRH:{}", branches_found, branches_hit);
+
+            for statement in &statements {
+                let _ = writeln!(lcov, "DA:{},{}", statement.as_line_number(), self.line_count(name, statement).unwrap_or(0));
+            }
+            let lines_hit = statements.iter().filter(|statement| self.line_count(name, statement).is_some()).count();
+            let _ = writeln!(lcov, "LF:{}\nLH:{}\nend_of_record", statements.len(), lines_hit);
+        }
+        lcov
+    }
+
+    fn line_count(&self, function: &str, statement: &Statement) -> Option<u64> {
+        self.lines.get(&(String::from(function), statement.as_line_number())).copied()
+    }
+
+    fn statement_branches(&self, function: &str, executable: &Executable, statement: &Statement) -> Vec<(u64, u64)> {
+        statement.iter()
+            .filter(|index| is_conditional_branch(executable, *index))
+            .map(|index| self.branches.get(&(String::from(function), index)).copied().unwrap_or_default())
+            .collect()
+    }
+}
+
+pub fn is_conditional_branch(executable: &Executable, node_index: usize) -> bool {
+    matches!(executable.get_node(node_index).as_ref(), Node::Operation(op) if op.get_name() == "cbranch")
+}
+
+fn user_functions(functions: &[Rc<FunctionDescription>]) -> Vec<(&str, &Executable)> {
+    functions.iter().filter_map(|f| match &f.implementation_class {
+        FunctionImplementation::User(body) => Some((f.name.as_str(), body.executable.as_ref())),
+        FunctionImplementation::System(_) => None,
+    }).collect()
+}
+
+//  The line a statement starts on; an if or while's source runs on through the statements it controls
+
+fn first_line(statement: &Statement) -> String {
+    convert_escape_sequences(statement.as_source().lines().next().unwrap_or_default())
+}
+
+//  In line order, which isn't the order they're held in: an if or while is added after the statements it controls
+
+fn statements(executable: &Executable) -> Vec<Rc<Statement>> {
+    let mut statements: Vec<Rc<Statement>> = (1..=executable.get_statement_count()).filter_map(|index| executable.get_statement(index)).collect();
+    statements.sort_by_key(|statement| statement.as_line_number());
+    statements
+}
@@ -1,8 +1,11 @@
 //  This module holds support routines for debugging execution
 
-use std::rc::Rc;
+use std::{fs::File, io::Write, rc::Rc, time::Instant};
 
-use crate::{workspace::{WorkSpace, debug::DebugOption}, symbols::metadata::FunctionDescription, execution::value::Value, utility::convert_escape_sequences};
+use strum_macros::{Display, EnumString};
+
+use crate::{workspace::{WorkSpace, debug::DebugOption}, symbols::metadata::FunctionDescription, execution::value::Value, utility::{as_text, convert_escape_sequences}};
+use crate::error::PplError;
 
 
 pub fn display_function(f: &Rc<FunctionDescription>, actual_argument_values: &Vec<Value>, workspace: &WorkSpace) {
@@ -16,4 +19,119 @@ pub fn display_function(f: &Rc<FunctionDescription>, actual_argument_values: &Ve
         }
         println!(")");
     }
-}
\ No newline at end of file
+}
+
+//  The trace log records each call of a user function, with its arguments, and each return, with its value.
+//  Every entry has the seconds since logging started and the call depth (1 for a function called from
+//  immediate mode).  As text, entries are indented by depth:
+//
+//      0.000012   1 outer(5)
+//      0.000031   2   inner(6)
+//      0.000040   2   inner = 13
+//      0.000052   1 outer = 19
+//
+//  As JSON, there's one object per line:
+//
+//      {"time":0.000012,"depth":1,"event":"call","function":"outer","arguments":["5"]}
+//      {"time":0.000052,"depth":1,"event":"return","function":"outer","value":"19"}
+
+#[derive(Clone, Copy, Debug, Display, EnumString, PartialEq)]
+#[strum(ascii_case_insensitive)]
+pub enum TraceFormat {
+    Text,
+    Json,
+}
+
+enum TraceDestination {
+    Buffer(Vec<u8>),
+    File(File),
+}
+
+pub struct TraceLog {
+    format: TraceFormat,
+    destination: TraceDestination,
+    started: Instant,
+}
+
+impl TraceLog {
+    pub fn to_buffer(format: TraceFormat) -> TraceLog {
+        TraceLog { format, destination: TraceDestination::Buffer(Vec::new()), started: Instant::now() }
+    }
+
+    pub fn to_file(filename: &str, format: TraceFormat) -> Result<TraceLog,PplError> {
+        Ok(TraceLog { format, destination: TraceDestination::File(File::create(filename)?), started: Instant::now() })
+    }
+
+    //  What's been logged to a buffer so far; a file's log is in the file
+
+    pub fn contents(&self) -> String {
+        match &self.destination {
+            TraceDestination::Buffer(buffer) => String::from_utf8_lossy(buffer).into_owned(),
+            TraceDestination::File(_) => String::new(),
+        }
+    }
+
+    pub fn log_call(&mut self, depth: usize, function: &str, arguments: &[Value]) -> Result<(),PplError> {
+        let arguments: Vec<String> = arguments.iter().map(as_text).collect();
+        let entry = match self.format {
+            TraceFormat::Text => format!("{:.6} {:>3} {}{}({})", self.elapsed(), depth, indentation(depth), function, arguments.join(", ")),
+            TraceFormat::Json => format!(r#"{{"time":{:.6},"depth":{},"event":"call","function":{},"arguments":[{}]}}"#,
+                self.elapsed(), depth, as_json(function), arguments.iter().map(|a| as_json(a)).collect::<Vec<String>>().join(",")),
+        };
+        self.write(entry)
+    }
+
+    pub fn log_return(&mut self, depth: usize, function: &str, value: &Value) -> Result<(),PplError> {
+        let value = as_text(value);
+        let entry = match self.format {
+            TraceFormat::Text => format!("{:.6} {:>3} {}{} = {}", self.elapsed(), depth, indentation(depth), function, value),
+            TraceFormat::Json => format!(r#"{{"time":{:.6},"depth":{},"event":"return","function":{},"value":{}}}"#,
+                self.elapsed(), depth, as_json(function), as_json(&value)),
+        };
+        self.write(entry)
+    }
+
+    fn elapsed(&self) -> f64 {
+        self.started.elapsed().as_secs_f64()
+    }
+
+    fn write(&mut self, entry: String) -> Result<(),PplError> {
+        match &mut self.destination {
+            TraceDestination::Buffer(buffer) => writeln!(buffer, "{}", entry)?,
+            TraceDestination::File(file) => writeln!(file, "{}", entry)?,
+        }
+        Ok(())
+    }
+}
+
+fn as_json(text: &str) -> String {
+    let mut result = String::from("\"");
+    for c in text.chars() {
+        match c {
+            '"' => result += "\\\"",
+            '\\' => result += "\\\\",
+            c if (c as u32) < 0x20 => result += format!("\\u{:04x}", c as u32).as_str(),
+            c => result.push(c),
+        }
+    }
+    result.push('"');
+    result
+}
+
+fn indentation(depth: usize) -> String {
+    "  ".repeat(depth.saturating_sub(1))
+}
+
+pub fn log_call(f: &Rc<FunctionDescription>, actual_argument_values: &[Value], workspace: &WorkSpace) -> Result<(),PplError> {
+    if let Some(log) = workspace.trace_log.borrow_mut().as_mut() {
+        log.log_call(workspace.get_call_depth(), f.name.as_str(), actual_argument_values)?;
+    }
+    Ok(())
+}
+
+pub fn log_return(f: &Rc<FunctionDescription>, value: &Value, workspace: &WorkSpace) -> Result<(),PplError> {
+    if let Some(log) = workspace.trace_log.borrow_mut().as_mut() {
+        log.log_return(workspace.get_call_depth(), f.name.as_str(), value)?;
+    }
+    Ok(())
+}
@@ -11,6 +11,7 @@ use super::statement::Statement;
 pub struct Executable {
     source: String,                             //  The source string that was parsed
     statements: Vec<Rc<Statement>>,             //  The parsed statements
+    statement_by_node: Vec<Option<usize>>,      //  The origin-0 index of the statement each node belongs to
     node_list: Vec<Rc<Node>>,                       //  The parse tree
     function_return_line_number: Option<LineNumber>,  //  The origin-0 index of the function return node
 }
@@ -25,6 +26,12 @@ impl Executable {
 
     pub fn add_statement(&mut self, line_number: LineNumber, starting_offset: usize, ending_offset: usize, node_indices: &Set<usize>) -> usize {
         let statement = Statement::new(line_number, &self.source[starting_offset..ending_offset], node_indices);
+        for node_index in node_indices.iter() {
+            if node_index >= self.statement_by_node.len() {
+                self.statement_by_node.resize(node_index + 1, None);
+            }
+            self.statement_by_node[node_index].get_or_insert(self.statements.len());
+        }
         self.statements.push(Rc::new(statement));
         self.statements.len() - 1
     }
@@ -50,22 +57,10 @@ impl Executable {
     }
 
     pub fn get_statement_from_node_index(&self, node_index: usize) -> Option<Rc<Statement>> {
-        let mut low:i32 = 0;
-        let mut high:i32 = self.statements.len() as i32;
 
-        while low <= high {
-            let target = ((low + high) / 2) as usize;
-            if self.statements[target].as_node_indices().contains(node_index) {
-                return Some(self.statements[target].clone());
-            }
-            if node_index < self.statements[target].as_first_node_index() {
-                high = target as i32 - 1;
-            } else {
-                low = target as i32 + 1;
-            }
-        }
+        //  An if or while is added after the statements it controls, so the statements aren't in node order
 
-        None
+        self.statement_by_node.get(node_index).copied().flatten().map(|statement_index| self.statements[statement_index].clone())
     }
 
     pub fn get_node(&self, index: usize) -> Rc<Node> {
@@ -93,21 +88,10 @@ impl Executable {
     }
 
     pub fn get_statement_index_by_line_number(&self, line_number: LineNumber) -> Option<usize> {
-        let mut low = 1;
-        let mut high = self.statements.len();
-
-        while low <= high {
-            let target = (low + high) / 2;
-            if self.statements[target-1].as_line_number() == line_number {
-                return Some(target);
-            } else if self.statements[target-1].as_line_number() > line_number {
-                high = target - 1;
-            } else {
-                low = target + 1;
-            }
-        }
 
-        None
+        //  An if or while is added after the statements it controls, so the statements aren't in line order
+
+        self.statements.iter().position(|statement| statement.as_line_number() == line_number).map(|index| index + 1)
     }
 
     pub fn is_first_executable_node(&self, statement: &Rc<Statement>, node_index: usize) -> bool {
@@ -128,7 +112,7 @@ impl Executable {
     }
 
     pub fn new(source: &str) -> Executable {
-        Executable { source: String::from(source), statements: Vec::new(), node_list: Vec::new(), function_return_line_number: None }
+        Executable { source: String::from(source), statements: Vec::new(), statement_by_node: Vec::new(), node_list: Vec::new(), function_return_line_number: None }
     }
 
     pub fn process_on_each_statement(&self, processor: fn(statement: Rc<Statement>)) {
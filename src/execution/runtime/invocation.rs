@@ -10,6 +10,7 @@ use crate::{execution::{functions::FunctionInvocationBlock, value::Value},
     utility::convert_escape_sequences};
 
 use super::{executable::Executable, statement::Statement};
+use crate::error::PplError;
 
 
 
@@ -186,7 +187,7 @@ impl Invocation {
         *self.next_node_index.borrow_mut() = next_node_index;
     }
 
-    pub fn set_next_node_by_line_number(&self, line_number: LineNumber) -> Result<Value,String> {
+    pub fn set_next_node_by_line_number(&self, line_number: LineNumber) -> Result<Value,PplError> {
 
         if let Some(_) = &self.fib {
 
@@ -205,7 +206,7 @@ impl Invocation {
             panic!("internal error");
 
         } else {
-            Err(format!("--> can only be used inside functions or to resume a stopped function"))
+            Err(ppl_error!(Runtime, "--> can only be used inside functions or to resume a stopped function"))
         }
     }
 
@@ -0,0 +1,89 @@
+//  This module holds the profiler, which counts how often each statement of a user function executes and how
+//  long it takes.  Time is measured per node, so a statement's time is its own: the statements of the functions
+//  it calls are charged to those functions.  A function's time is the sum of its statements' times.
+
+use std::{collections::HashMap, fmt::Write, time::Duration};
+
+use crate::{error::Frame, lexical::LineNumber, utility::convert_escape_sequences};
+
+use super::statement::Statement;
+
+
+#[derive(Default)]
+struct LineProfile {
+    source: String,
+    count: u64,
+    time: Duration,
+}
+
+#[derive(Default)]
+struct FunctionProfile {
+    calls: u64,
+    time: Duration,
+}
+
+#[derive(Default)]
+pub struct Profile {
+    lines: HashMap<(String, LineNumber), LineProfile>,
+    functions: HashMap<String, FunctionProfile>,
+    running: bool,
+}
+
+impl Profile {
+    pub fn new() -> Profile {
+        Profile { running: true, ..Default::default() }
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.running
+    }
+
+    //  A stopped profile keeps what it's recorded for reporting
+
+    pub fn stop(&mut self) {
+        self.running = false;
+    }
+
+    pub fn record_call(&mut self, function: &str) {
+        if !self.running {
+            return;
+        }
+        self.functions.entry(String::from(function)).or_default().calls += 1;
+    }
+
+    //  Charges the time taken by one node to its statement.  The statement's count goes up when its first
+    //  node runs, which is a label's node for a labelled statement, since that's where --> goes.
+
+    pub fn record_node(&mut self, function: &str, statement: &Statement, is_first_node: bool, time: Duration) {
+        if !self.running {
+            return;
+        }
+        let line = self.lines.entry((String::from(function), statement.as_line_number()))
+            .or_insert_with(|| LineProfile { source: statement.as_source(), ..Default::default() });
+        line.time += time;
+        if is_first_node {
+            line.count += 1;
+        }
+        self.functions.entry(String::from(function)).or_default().time += time;
+    }
+
+    //  The functions, then the hottest lines, each by descending time.  The limit, if any, is on the lines.
+
+    pub fn report(&self, limit: Option<usize>) -> String {
+        let mut functions: Vec<(&String, &FunctionProfile)> = self.functions.iter().collect();
+        functions.sort_by(|a, b| b.1.time.cmp(&a.1.time).then(a.0.cmp(b.0)));
+        let mut lines: Vec<(&(String, LineNumber), &LineProfile)> = self.lines.iter().collect();
+        lines.sort_by(|a, b| b.1.time.cmp(&a.1.time).then(b.1.count.cmp(&a.1.count)).then(a.0.cmp(b.0)));
+
+        let mut report = String::from("      Time     Calls  Function\n");
+        for (name, function) in functions {
+            let _ = writeln!(report, "{:10.6}{:10}  {}", function.time.as_secs_f64(), function.calls, name);
+        }
+        report += "\n      Time     Count  Line\n";
+        for ((name, line_number), line) in lines.into_iter().take(limit.unwrap_or(usize::MAX)) {
+            let frame = Frame { function_name: Some(name.clone()), line_number: *line_number, source: convert_escape_sequences(line.source.lines().next().unwrap_or_default()) };
+            let _ = writeln!(report, "{:10.6}{:10}  {}", line.time.as_secs_f64(), line.count, frame);
+        }
+        report
+    }
+}
@@ -1,4 +1,6 @@
-//  This module holds code to capture the current stack pointer. Credit to 
+//  This module holds code to capture the current stack pointer.  Rather than read the register, which takes
+//  assembler for each architecture, it takes the address of a local, which is on the stack wherever the stack is.
+//  That's as good as the stack pointer for measuring how far the stack has grown.  Credit for tick!() to
 //  "Lambda Fairy" of StackOverflow, dated Aug 15, 2016
 
 use std::cell::Cell;
@@ -10,13 +12,9 @@ thread_local!(static STACK_END: Cell<usize> = Cell::new(usize::MAX));
 #[macro_export]
 macro_rules! stack_ptr {
     () => ({
-        // Grab a copy of the stack pointer
-        let x: usize;
-        unsafe {
-            asm!("mov {},rsp",
-                 out(reg) x,);
-        }
-        x
+        // Grab the address of something on the stack
+        let x = 0u8;
+        std::hint::black_box(std::ptr::addr_of!(x)) as usize
     })
 }
 
@@ -1,10 +1,12 @@
 //  This module holds the definitions of the Statement and StatementBlockIterator structures.
 
 use core::fmt;
-use std::cell::RefCell;
+use std::{cell::RefCell, rc::Rc};
 
 use crate::{lexical::LineNumber, utility::{convert_escape_sequences, Set, SetIterator}};
 
+use super::executable::Executable;
+
 
 
 
@@ -14,6 +16,9 @@ pub struct Statement {
     node_indices: Set<usize>,               //  The origin-0 index numbers of the nodes corresponding to this statement
     trace: RefCell<bool>,            //  TRUE if the execution of the statement should be traced
     stop: RefCell<bool>,             //  TRUE if a breakpoint is set on the statement
+    stop_condition: RefCell<Option<Rc<Executable>>>,    //  PPL expression that must be true for the breakpoint to stop, parsed when set
+    stop_after: RefCell<u32>,        //  The breakpoint stops from this hit onward
+    hits: RefCell<u32>,              //  The number of times the breakpoint has been reached, with its condition true
 }
 
 
@@ -40,6 +45,19 @@ impl Statement {
         self.source.clone()
     }
 
+    pub fn count_hit(&self) -> u32 {
+        *self.hits.borrow_mut() += 1;
+        *self.hits.borrow()
+    }
+
+    pub fn get_stop_after(&self) -> u32 {
+        *self.stop_after.borrow()
+    }
+
+    pub fn get_stop_condition(&self) -> Option<Rc<Executable>> {
+        self.stop_condition.borrow().clone()
+    }
+
     pub fn is_stop_set(&self) -> bool {
         *self.stop.borrow()
     }
@@ -58,11 +76,27 @@ impl Statement {
             line_number: line_number,  
             node_indices: node_indices.clone(),
             trace: RefCell::new(false), 
-            stop: RefCell::new(false), }
+            stop: RefCell::new(false),
+            stop_condition: RefCell::new(None),
+            stop_after: RefCell::new(0),
+            hits: RefCell::new(0), }
     }
 
+    //  Setting or clearing a breakpoint makes it unconditional again
+
     pub fn set_stop(&self, value: bool) {
         *self.stop.borrow_mut() = value;
+        *self.stop_condition.borrow_mut() = None;
+        *self.stop_after.borrow_mut() = 0;
+        *self.hits.borrow_mut() = 0;
+    }
+
+    pub fn set_stop_after(&self, hits: u32) {
+        *self.stop_after.borrow_mut() = hits;
+    }
+
+    pub fn set_stop_condition(&self, condition: Rc<Executable>) {
+        *self.stop_condition.borrow_mut() = Some(condition);
     }
 
     pub fn set_trace(&self, value: bool) {
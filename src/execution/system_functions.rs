@@ -6,18 +6,25 @@ use crate::{symbols::{
     name::Name, datatype::is_assignable_to}, 
     workspace::{WorkSpace, GeneralSymbol}, lexical::LineNumber};
 use crate::execution::value::Value;
-use super::{evaluate_internal, value::{sequence::SequenceInstance, Cell, recursion_detector}};
+use super::{definition::{is_function_definition_end, is_function_definition_start}, editor::FunctionEditor, evaluate_internal, value::{sequence::SequenceInstance, Cell, recursion_detector}};
+use crate::error::PplError;
 
 mod arithmetic;
 mod comparison;
+mod complex;
 mod debug;
+mod math;
 mod metadata;
+mod testing;
 
 pub fn init(workspace: &WorkSpace) {
     arithmetic::init(workspace);
     comparison::init(workspace);
+    complex::init(workspace);
     debug::init(workspace);
+    math::init(workspace);
     metadata::init(workspace);
+    testing::init(workspace);
 
     workspace.add_system_function(
         "_", 
@@ -86,6 +93,16 @@ pub fn init(workspace: &WorkSpace) {
             implementation_class: FunctionImplementation::System(FunctionClass::NullValuedDiadic(cgoto)),
             help_text: String::from("If the condition is true, transfer control to the designated statement") });
 
+    workspace.add_system_function(
+        "args", 
+        FunctionDescription { 
+            name: Name::from_str("args"), 
+            arguments: FunctionArgumentList::Fixed(Vec::new()),
+            local_variables: None,
+            return_value: Some(MetaDataTypeName::from_str("tuple")), 
+            implementation_class: FunctionImplementation::System(FunctionClass::Nullary(args)),
+            help_text: String::from("Returns the script's command line arguments as a sequence of strings") });
+
     workspace.add_system_function(
         "assign", 
         FunctionDescription { 
@@ -197,7 +214,7 @@ pub fn init(workspace: &WorkSpace) {
                 local_variables: None,
                 return_value: Some(MetaDataTypeName::from_str("string")), 
                 implementation_class: FunctionImplementation::System(FunctionClass::Diadic(feature)),
-                help_text: String::from(r#"{"SET" | "CLEAR"} feature options {"StringEscapes"}"#) });
+                help_text: String::from(r#"{"SET" | "CLEAR"} feature options {"LineNames" | "PromoteOnOverflow" | "StringEscapes"}"#) });
                                         
     workspace.add_system_function(
         "format", 
@@ -230,6 +247,90 @@ pub fn init(workspace: &WorkSpace) {
             implementation_class: FunctionImplementation::System(FunctionClass::Varying(iformat)),
             help_text: String::from("Convert number(s) to string using a format specification") });
                                                                 
+    workspace.add_system_function(
+        "limit.depth", 
+        FunctionDescription { 
+            name: Name::from_str("limit.depth"), 
+            arguments: FunctionArgumentList::Fixed(vec![]),
+            local_variables: None,
+            return_value: Some(MetaDataTypeName::from_str("int")), 
+            implementation_class: FunctionImplementation::System(FunctionClass::Nullary(show_limit_depth)),
+            help_text: String::from("Display the number of user functions that can be executing at once, or 0 if there's no limit") });
+
+    workspace.add_system_function(
+        "limit.depth", 
+        FunctionDescription { 
+            name: Name::from_str("limit.depth"), 
+            arguments: FunctionArgumentList::Fixed(vec![
+                FormalArgument { name: Name::from_str("n"), mechanism: ArgumentMechanism::ByValue,  datatype: MetaDataTypeName::from_str("int") }]),
+            local_variables: None,
+            return_value: None, 
+            implementation_class: FunctionImplementation::System(FunctionClass::NullValuedMonadic(limit_depth)),
+            help_text: String::from("Limit the number of user functions that can be executing at once; 0 removes the limit.  Going over it is an error.") });
+
+    workspace.add_system_function(
+        "limit.nodes", 
+        FunctionDescription { 
+            name: Name::from_str("limit.nodes"), 
+            arguments: FunctionArgumentList::Fixed(vec![]),
+            local_variables: None,
+            return_value: Some(MetaDataTypeName::from_str("int")), 
+            implementation_class: FunctionImplementation::System(FunctionClass::Nullary(show_limit_nodes)),
+            help_text: String::from("Display the number of nodes one command can execute, counting those of every function it calls, or 0 if there's no limit") });
+
+    workspace.add_system_function(
+        "limit.nodes", 
+        FunctionDescription { 
+            name: Name::from_str("limit.nodes"), 
+            arguments: FunctionArgumentList::Fixed(vec![
+                FormalArgument { name: Name::from_str("n"), mechanism: ArgumentMechanism::ByValue,  datatype: MetaDataTypeName::from_str("int") }]),
+            local_variables: None,
+            return_value: None, 
+            implementation_class: FunctionImplementation::System(FunctionClass::NullValuedMonadic(limit_nodes)),
+            help_text: String::from("Limit the number of nodes one command can execute, counting those of every function it calls; 0 removes the limit.  Going over it is an error.") });
+
+    workspace.add_system_function(
+        "limit.stack", 
+        FunctionDescription { 
+            name: Name::from_str("limit.stack"), 
+            arguments: FunctionArgumentList::Fixed(vec![]),
+            local_variables: None,
+            return_value: Some(MetaDataTypeName::from_str("int")), 
+            implementation_class: FunctionImplementation::System(FunctionClass::Nullary(show_limit_stack)),
+            help_text: String::from("Display the kilobytes of stack an evaluation can use, including the evaluations nested in it, or 0 if there's no limit") });
+
+    workspace.add_system_function(
+        "limit.stack", 
+        FunctionDescription { 
+            name: Name::from_str("limit.stack"), 
+            arguments: FunctionArgumentList::Fixed(vec![
+                FormalArgument { name: Name::from_str("n"), mechanism: ArgumentMechanism::ByValue,  datatype: MetaDataTypeName::from_str("int") }]),
+            local_variables: None,
+            return_value: None, 
+            implementation_class: FunctionImplementation::System(FunctionClass::NullValuedMonadic(limit_stack)),
+            help_text: String::from("Limit the kilobytes of stack an evaluation can use, including the evaluations nested in it; 0 removes the limit.  Without a limit, running out of stack ends the process.") });
+
+    workspace.add_system_function(
+        "limit.values", 
+        FunctionDescription { 
+            name: Name::from_str("limit.values"), 
+            arguments: FunctionArgumentList::Fixed(vec![]),
+            local_variables: None,
+            return_value: Some(MetaDataTypeName::from_str("int")), 
+            implementation_class: FunctionImplementation::System(FunctionClass::Nullary(show_limit_values)),
+            help_text: String::from("Display the number of values the value stack can hold, or 0 if there's no limit") });
+
+    workspace.add_system_function(
+        "limit.values", 
+        FunctionDescription { 
+            name: Name::from_str("limit.values"), 
+            arguments: FunctionArgumentList::Fixed(vec![
+                FormalArgument { name: Name::from_str("n"), mechanism: ArgumentMechanism::ByValue,  datatype: MetaDataTypeName::from_str("int") }]),
+            local_variables: None,
+            return_value: None, 
+            implementation_class: FunctionImplementation::System(FunctionClass::NullValuedMonadic(limit_values)),
+            help_text: String::from("Limit the number of values the value stack can hold; 0 removes the limit.  Going over it is an error.") });
+
     workspace.add_system_function(
         "noncopy", 
         FunctionDescription { 
@@ -255,7 +356,12 @@ pub fn init(workspace: &WorkSpace) {
 
 
 
-fn assign(receiver: &Value, value: &Value, workspace: &WorkSpace) -> Result<Value, String> {
+fn args(workspace: &WorkSpace) -> Result<Value,PplError> {
+    let arguments = workspace.get_arguments().iter().map(|a| SequenceInstance::construct_string_sequence(a)).collect();
+    Ok(Value::Sequence(SequenceInstance::from_string(&String::from("tuple"), 1, arguments)))
+}
+
+fn assign(receiver: &Value, value: &Value, workspace: &WorkSpace) -> Result<Value,PplError> {
     match receiver {
         Value::Symbol(symbol) => {
             match symbol.as_symbol() {
@@ -264,7 +370,14 @@ fn assign(receiver: &Value, value: &Value, workspace: &WorkSpace) -> Result<Valu
                     Cell::validate_value(value)?;
                     workspace.add_variable(u.as_string().as_str(), VariableDescription { cell: Cell::new(value.clone()) });
                 },
-                _ => return Err(format!("Only variables can be the target of assign")),
+
+                //  A variable can take the name of a library function, such as pi, and hides it
+
+                GeneralSymbol::Function(f) if matches!(f.implementation_class, FunctionImplementation::System(_)) && workspace.is_library_function(f.name.as_str()) => {
+                    Cell::validate_value(value)?;
+                    workspace.add_variable(f.name.as_str(), VariableDescription { cell: Cell::new(value.clone()) });
+                },
+                _ => return Err(ppl_error!(Runtime, "Only variables can be the target of assign")),
             }
         },
         Value::ValueByReference(receiver) => {
@@ -279,14 +392,14 @@ fn assign(receiver: &Value, value: &Value, workspace: &WorkSpace) -> Result<Valu
     Ok(value.clone())
 }
 
-fn branch(destination: &Value, workspace: &WorkSpace) -> Result<(),String>{
+fn branch(destination: &Value, workspace: &WorkSpace) -> Result<(),PplError>{
     let node_index = destination.as_usize()?;
     let invocation = workspace.current_invocation().unwrap();
     invocation.set_pending_goto_index(Some(node_index));
     Ok(())
 }
 
-fn cbranch(condition: &Value, destination: &Value, workspace: &WorkSpace) -> Result<(),String> {
+fn cbranch(condition: &Value, destination: &Value, workspace: &WorkSpace) -> Result<(),PplError> {
     if condition.as_bool()? {
         branch (destination, workspace)        
     } else {
@@ -294,7 +407,7 @@ fn cbranch(condition: &Value, destination: &Value, workspace: &WorkSpace) -> Res
     }
 }
 
-fn cgoto(condition: &Value, destination: &Value, workspace: &WorkSpace) -> Result<(),String> {
+fn cgoto(condition: &Value, destination: &Value, workspace: &WorkSpace) -> Result<(),PplError> {
     if condition.as_bool()? {
         goto (destination, workspace)        
     } else {
@@ -303,23 +416,23 @@ fn cgoto(condition: &Value, destination: &Value, workspace: &WorkSpace) -> Resul
     }
 }
 
-fn concat(left: &Value, right: &Value, _workspace: &WorkSpace) -> Result<Value,String> {
+fn concat(left: &Value, right: &Value, _workspace: &WorkSpace) -> Result<Value,PplError> {
     if let Value::Sequence(left_sequence) = left {
         if let Value::Sequence(right_sequence) = right {
             if left_sequence.as_datatype() == right_sequence.as_datatype() {
                 SequenceInstance::concat(left_sequence, right_sequence)
             } else {
-                Err(format!("Both sequences must be of the same type"))
+                Err(ppl_error!(Type, "Both sequences must be of the same type"))
             }
         } else {
-            Err(format!("{} is not a variadic sequence", right))
+            Err(ppl_error!(Type, "{} is not a variadic sequence", right))
         }
     } else {
-        Err(format!("{} is not a variadic sequence", left))
+        Err(ppl_error!(Type, "{} is not a variadic sequence", left))
     }
 }
 
-fn debug_print(operand: &Value, _workspace: &WorkSpace) -> Result<(),String> {
+fn debug_print(operand: &Value, _workspace: &WorkSpace) -> Result<(),PplError> {
     recursion_detector::Cycle::start();
     dbg!(operand);
     recursion_detector::Cycle::start();
@@ -327,13 +440,35 @@ fn debug_print(operand: &Value, _workspace: &WorkSpace) -> Result<(),String> {
     Ok(())
 }
 
+fn edit_function(name: &str, workspace: &WorkSpace) -> Result<(),PplError> {
+    //  Run the line editor on an existing function until the user enters a lone $
+
+    let mut editor = FunctionEditor::open(name, workspace)?;
+    let stdin = io::stdin();
+    let mut stdout = io::stdout();
+
+    loop {
+        print!("{}", editor.prompt());
+        stdout.flush()?;
+        let mut current_line = String::new();
+        if stdin.read_line(&mut current_line)? == 0 || current_line.trim() == "$" {
+            return editor.close(workspace);
+        }
+        match editor.apply(&current_line, workspace) {
+            Ok(message) if message.is_empty() => {},
+            Ok(message) => println!("{}", message),
+            Err(e) => println!("{}", e),
+        }
+    }
+}
+
 
-fn exec(s: &Value, workspace: &WorkSpace) -> Result<Value,String>{
+fn exec(s: &Value, workspace: &WorkSpace) -> Result<Value,PplError>{
     recursion_detector::Cycle::start();
     Ok(SequenceInstance::construct_string_sequence(&evaluate_internal(&s.as_string(), workspace)?))
 }
 
-fn exit(args: &Vec<Value>, _workspace: &WorkSpace) -> Result<Value,String> {
+fn exit(args: &Vec<Value>, _workspace: &WorkSpace) -> Result<Value,PplError> {
     let mut exit_code = 0;
     if args.len() >= 1 {
         exit_code = args[0].as_i32()?;
@@ -342,16 +477,16 @@ fn exit(args: &Vec<Value>, _workspace: &WorkSpace) -> Result<Value,String> {
     //Ok(Value::Empty)
 }
 
-fn feature(operation: &Value, option: &Value, workspace: &WorkSpace) -> Result<Value,String> {
+fn feature(operation: &Value, option: &Value, workspace: &WorkSpace) -> Result<Value,PplError> {
     match operation.as_string().to_ascii_lowercase().as_str() {
         "on" | "set" => workspace.features.borrow_mut().set_str(option.as_string().as_str())?,
         "off" | "clear" => workspace.features.borrow_mut().clear_str(option.as_string().as_str())?,
-        _ => return Err(format!("{} is not a valid feature operation", operation.as_string())),
+        _ => return Err(ppl_error!(Type, "{} is not a valid feature operation", operation.as_string())),
     }
     Ok(SequenceInstance::construct_string_sequence(""))
 }
 
-fn goto(destination: &Value, workspace: &WorkSpace) -> Result<(),String> {
+fn goto(destination: &Value, workspace: &WorkSpace) -> Result<(),PplError> {
     let line_number = destination.as_usize()? as LineNumber;
 
     //  We have a bit of a conundrum here...in that gotos in immediate mode are used to resume stopped
@@ -393,16 +528,43 @@ fn goto(destination: &Value, workspace: &WorkSpace) -> Result<(),String> {
                 }
                 workspace.end_invocation();
             },
-            None => return Err(format!("--> can only be used in immediate mode to resume a stopped function")),
+            None => return Err(ppl_error!(Runtime, "--> can only be used in immediate mode to resume a stopped function")),
         }
     }
 }
 
-fn iformat(args: &Vec<Value>, workspace: &WorkSpace) -> Result<Value,String> {
+fn iformat(args: &Vec<Value>, workspace: &WorkSpace) -> Result<Value,PplError> {
     Ok(SequenceInstance::construct_string_sequence(&Value::format(args, workspace)?))
 }
 
-fn noncopy(receiver: &Value, value: &Value, workspace: &WorkSpace) -> Result<Value, String> {
+fn limit_depth(n: &Value, workspace: &WorkSpace) -> Result<(),PplError> {
+    workspace.limits.borrow_mut().depth = as_limit(n)?;
+    Ok(())
+}
+
+fn limit_nodes(n: &Value, workspace: &WorkSpace) -> Result<(),PplError> {
+    workspace.limits.borrow_mut().nodes = as_limit(n)?.map(|n| n as u64);
+    Ok(())
+}
+
+fn limit_stack(n: &Value, workspace: &WorkSpace) -> Result<(),PplError> {
+    workspace.limits.borrow_mut().stack = as_limit(n)?.map(|n| n * 1024);
+    Ok(())
+}
+
+fn limit_values(n: &Value, workspace: &WorkSpace) -> Result<(),PplError> {
+    workspace.limits.borrow_mut().values = as_limit(n)?;
+    Ok(())
+}
+
+fn as_limit(n: &Value) -> Result<Option<usize>,PplError> {
+    match n.as_usize()? {
+        0 => Ok(None),
+        n => Ok(Some(n)),
+    }
+}
+
+fn noncopy(receiver: &Value, value: &Value, workspace: &WorkSpace) -> Result<Value,PplError> {
 //    dbg!(receiver);
 //    dbg!(value);
     match (receiver, value) {
@@ -414,9 +576,9 @@ fn noncopy(receiver: &Value, value: &Value, workspace: &WorkSpace) -> Result<Val
                 (GeneralSymbol::Unresolved(u), GeneralSymbol::Variable(exp)) => {
                     workspace.add_variable(u.as_string().as_str(), VariableDescription { cell: Cell::new_by_reference(exp.cell.borrow().as_contents()) });
                 },
-                (_,GeneralSymbol::Unresolved(_)) => return Err(format!("{} not found", attachee.as_string())),
+                (_,GeneralSymbol::Unresolved(_)) => return Err(ppl_error!(Name, "{} not found", attachee.as_string())),
                 _ => {
-                    return Err(format!("Only variables can be the target of assign"));
+                    return Err(ppl_error!(Runtime, "Only variables can be the target of assign"));
                 },
             }
         },
@@ -428,7 +590,7 @@ fn noncopy(receiver: &Value, value: &Value, workspace: &WorkSpace) -> Result<Val
                 GeneralSymbol::Unresolved(u) => {
                     workspace.add_variable(u.as_string().as_str(), VariableDescription { cell: Cell::new_by_reference(attachee.cell.borrow().as_contents()) });
                 },
-                _ => {dbg!(symbol.as_symbol()); return Err(format!("Only variables can be the target of assign"));},
+                _ => {dbg!(symbol.as_symbol()); return Err(ppl_error!(Runtime, "Only variables can be the target of assign"));},
             }
         },
         (Value::ValueByReference(receiver), Value::Symbol(attachee)) => {
@@ -437,18 +599,18 @@ fn noncopy(receiver: &Value, value: &Value, workspace: &WorkSpace) -> Result<Val
                     if is_assignable_to(&v.cell.borrow().as_ref_to_value(), &workspace.resolve_datatype(&receiver.datatype.as_string())?, workspace)? {
                         receiver.cell.borrow_mut().set_reference(v.cell.borrow().as_contents().clone());
                     } else {
-                        return Err(format!("Incompatible datatype"));
+                        return Err(ppl_error!(Name, "Incompatible datatype"));
                     }
                 },
-                GeneralSymbol::Unresolved(_) => return Err(format!("{} not found", attachee.as_string())),
-                _ => return Err(format!("Only variables can be the target of assign")),
+                GeneralSymbol::Unresolved(_) => return Err(ppl_error!(Name, "{} not found", attachee.as_string())),
+                _ => return Err(ppl_error!(Runtime, "Only variables can be the target of assign")),
             }
         },
         (Value::ValueByReference(receiver), Value::ValueByReference(attachee)) => {
             if is_assignable_to(&*attachee.cell.borrow().as_ref_to_value(), &workspace.resolve_datatype(&receiver.datatype.as_string())?, workspace)? {
                 receiver.cell.borrow_mut().set_reference(attachee.cell.borrow().as_contents().clone());
             } else {
-                return Err(format!("Incompatible datatype"));
+                return Err(ppl_error!(Type, "Incompatible datatype"));
             }
         },
         (_,_) => {
@@ -460,11 +622,11 @@ fn noncopy(receiver: &Value, value: &Value, workspace: &WorkSpace) -> Result<Val
     Ok(value.clone())
 }
 
-fn pformat(args: &Vec<Value>, workspace: &WorkSpace) -> Result<(),String> {
+fn pformat(args: &Vec<Value>, workspace: &WorkSpace) -> Result<(),PplError> {
     print(&vec![iformat(args, workspace)?], workspace)
 }
 
-fn print(args: &Vec<Value>, workspace: &WorkSpace) -> Result<(),String> {
+fn print(args: &Vec<Value>, workspace: &WorkSpace) -> Result<(),PplError> {
     let mut result = String::new();
 
     recursion_detector::Cycle::start();
@@ -477,27 +639,27 @@ fn print(args: &Vec<Value>, workspace: &WorkSpace) -> Result<(),String> {
 
     match *workspace.get_alternate_print_destinatin() {
         Some(ref mut cursor) => {
-            cursor.write(result.as_bytes()).map_err(|e| format!("{}", e))?;
+            cursor.write_all(result.as_bytes())?;
         },
         None => {
-            write!(stdout(), "{}", result).map_err(|e| format!("{}", e))?;
-            stdout().flush().map_err(|e| format!("{}", e))?;
+            write!(stdout(), "{}", result)?;
+            stdout().flush()?;
         },
     }
 
     Ok(())
 }
 
-fn read_stdin(_workspace: &WorkSpace) -> Result<Value, String> {
+fn read_stdin(_workspace: &WorkSpace) -> Result<Value,PplError> {
     let mut result = String::new();
     let stdin = io::stdin();
     match stdin.read_line(&mut result) {
         Ok(_) => Ok(SequenceInstance::construct_string_sequence(&result)),
-        Err(e) => Err(format!("{}", e))
+        Err(e) => Err(ppl_error!(Io, "{}", e))
     }
 }
 
-fn read_stdin_ppl(_workspace: &WorkSpace) -> Result<Value, String> {
+fn read_stdin_ppl(workspace: &WorkSpace) -> Result<Value,PplError> {
     //  This function implements PPL's interpreter semantics on input: whereby a line starting
     //  with $ (and no equal sign) is a function definition. It reads and appends lines until it
     //  encounters an empty line.
@@ -511,31 +673,50 @@ fn read_stdin_ppl(_workspace: &WorkSpace) -> Result<Value, String> {
     loop {
         if function_definition_active {
             print!("[{}] ", line_number);
-            stdout.flush().map_err(|e| format!("{}", e))?;
+            stdout.flush()?;
         }
         let mut current_line = String::new();
         match stdin.read_line(&mut current_line) {
+            Ok(0) => return Ok(SequenceInstance::construct_string_sequence(&result)),
             Ok(_) => {
                 result += current_line.as_str();
                 if function_definition_active {
-                    if current_line.as_str() == "\r\n" || current_line.trim() == "$" {
+                    if is_function_definition_end(&current_line) {
                         return Ok(SequenceInstance::construct_string_sequence(&result));
                     } else {
                         line_number += 1;
                     }
-                } else if current_line.len() > 1 && &current_line[0..1] == "$" && !current_line.contains('=') {
+                } else if let Some(name) = FunctionEditor::is_edit_request(&current_line, workspace) {
+                    edit_function(&name, workspace)?;
+                    return Ok(SequenceInstance::construct_string_sequence(""));
+                } else if is_function_definition_start(&current_line) {
                     function_definition_active = true;
                 } else {
                     return Ok(SequenceInstance::construct_string_sequence(&result));
                 }
             },
-            Err(e) => return Err(format!("{}", e)),
+            Err(e) => return Err(ppl_error!(Io, "{}", e)),
         }
     }
 }
 
-
-fn show_features(workspace: &WorkSpace) -> Result<Value,String> {
+fn show_features(workspace: &WorkSpace) -> Result<Value,PplError> {
     Ok(SequenceInstance::construct_string_sequence(&workspace.features.borrow().show()))
 }
 
+
+fn show_limit_depth(workspace: &WorkSpace) -> Result<Value,PplError> {
+    Ok(Value::Int(workspace.limits.borrow().depth.unwrap_or(0) as i32))
+}
+
+fn show_limit_nodes(workspace: &WorkSpace) -> Result<Value,PplError> {
+    Ok(Value::Int(workspace.limits.borrow().nodes.unwrap_or(0) as i32))
+}
+
+fn show_limit_stack(workspace: &WorkSpace) -> Result<Value,PplError> {
+    Ok(Value::Int((workspace.limits.borrow().stack.unwrap_or(0) / 1024) as i32))
+}
+
+fn show_limit_values(workspace: &WorkSpace) -> Result<Value,PplError> {
+    Ok(Value::Int(workspace.limits.borrow().values.unwrap_or(0) as i32))
+}
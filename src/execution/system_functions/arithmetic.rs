@@ -1,6 +1,12 @@
 //  This module holds the PPL system function implementations for arithmetic
 
 use crate::{workspace::WorkSpace, symbols::{metadata::{FunctionDescription, FormalArgument, ArgumentMechanism, MetaDataTypeName, FunctionImplementation, FunctionClass, FunctionArgumentList}, name::Name, datatype::{RootDataType, strongest_datatype}}, execution::value::Value};
+use crate::{error::PplError, workspace::optional_features::Feature};
+
+#[cfg(feature = "bignum")]
+use num_bigint::BigInt;
+#[cfg(feature = "bignum")]
+use num_traits::ToPrimitive;
 
 
 pub fn init(workspace: &WorkSpace) {
@@ -173,96 +179,224 @@ pub fn init(workspace: &WorkSpace) {
 
 
 
-fn add(left: &Value, right: &Value, workspace: &WorkSpace) -> Result<Value,String> {
+fn add(left: &Value, right: &Value, workspace: &WorkSpace) -> Result<Value,PplError> {
     let value_list = [RootDataType::from_value(left, workspace)?, RootDataType::from_value(right, workspace)?];
     let result_type = strongest_datatype(&value_list, workspace)?;
     match  result_type {
-        RootDataType::Int => Ok(Value::Int(left.as_i32()? + right.as_i32()?)),
+        RootDataType::Rational => Ok(Value::Rational(left.as_rational()?.checked_add(&right.as_rational()?)?)),
+        RootDataType::Decimal => Ok(Value::Decimal(left.as_decimal()?.checked_add(&right.as_decimal()?)?)),
+        RootDataType::Structure(_) => Ok(Value::from(left.as_complex()?.add(&right.as_complex()?))),
         RootDataType::Real => Ok(Value::Real(left.as_f32()? + right.as_f32()?)),
         RootDataType::Dbl => Ok(Value::Double(left.as_f64()? + right.as_f64()?)),
-        _ => Err(format!("unsupported datatype for arithmetic")),
+        _ => integer_arithmetic(&ADD, &result_type, left, right, workspace),
     }
 }
 
 
-fn divide(left: &Value, right: &Value, workspace: &WorkSpace) -> Result<Value,String> {
+fn divide(left: &Value, right: &Value, workspace: &WorkSpace) -> Result<Value,PplError> {
     let value_list = [RootDataType::from_value(left, workspace)?, RootDataType::from_value(right, workspace)?];
     let result_type = strongest_datatype(&value_list, workspace)?;
+    if !matches!(result_type, RootDataType::Structure(_)) && right.as_f64()? == 0.0 {
+        return Err(ppl_error!(Arithmetic, "Division by zero"));
+    }
     match  result_type {
-        RootDataType::Int => {
-            let r = right.as_i32()?;
-            let l = left.as_i32()?;
-            if r == 0 {
-                if l > 0 { 
-                    Ok(Value::Int(i32::MAX))
-                } else {
-                    Ok(Value::Int(i32::MIN))
-                }
-            } else {
-                Ok(Value::Int(l / r))
-            }
-        },
+        RootDataType::Rational => Ok(Value::Rational(left.as_rational()?.checked_div(&right.as_rational()?)?)),
+        RootDataType::Decimal => Ok(Value::Decimal(left.as_decimal()?.checked_div(&right.as_decimal()?)?)),
+        RootDataType::Structure(_) => Ok(Value::from(left.as_complex()?.checked_div(&right.as_complex()?)?)),
         RootDataType::Real => Ok(Value::Real(left.as_f32()? / right.as_f32()?)),
         RootDataType::Dbl => Ok(Value::Double(left.as_f64()? / right.as_f64()?)),
-        _ => Err(format!("unsupported datatype for arithmetic")),
+        _ => integer_arithmetic(&DIVIDE, &result_type, left, right, workspace),
     }
 }
 
-fn multiply(left: &Value, right: &Value, workspace: &WorkSpace) -> Result<Value,String> {
+fn multiply(left: &Value, right: &Value, workspace: &WorkSpace) -> Result<Value,PplError> {
     let value_list = [RootDataType::from_value(left, workspace)?, RootDataType::from_value(right, workspace)?];
     let result_type = strongest_datatype(&value_list, workspace)?;
     match  result_type {
-        RootDataType::Int => Ok(Value::Int(left.as_i32()? * right.as_i32()?)),
+        RootDataType::Rational => Ok(Value::Rational(left.as_rational()?.checked_mul(&right.as_rational()?)?)),
+        RootDataType::Decimal => Ok(Value::Decimal(left.as_decimal()?.checked_mul(&right.as_decimal()?)?)),
+        RootDataType::Structure(_) => Ok(Value::from(left.as_complex()?.mul(&right.as_complex()?))),
         RootDataType::Real => Ok(Value::Real(left.as_f32()? * right.as_f32()?)),
         RootDataType::Dbl => Ok(Value::Double(left.as_f64()? * right.as_f64()?)),
-        _ => Err(format!("unsupported datatype for arithmetic")),
+        _ => integer_arithmetic(&MULTIPLY, &result_type, left, right, workspace),
     }
 }
 
-fn minus(operand: &Value, workspace: &WorkSpace) -> Result<Value,String> {
+fn minus(operand: &Value, workspace: &WorkSpace) -> Result<Value,PplError> {
     let value_list = [RootDataType::from_value(operand, workspace)?];
     let result_type = strongest_datatype(&value_list, workspace)?;
     match  result_type {
         RootDataType::Bool => Ok(Value::Bool(!operand.as_bool()?)),
-        RootDataType::Int => Ok(Value::Int(-operand.as_i32()?)),
+        RootDataType::Rational => Ok(Value::Rational(operand.as_rational()?.checked_neg()?)),
+        RootDataType::Decimal => Ok(Value::Decimal(operand.as_decimal()?.checked_neg()?)),
+        RootDataType::Structure(_) => Ok(Value::from(operand.as_complex()?.neg())),
         RootDataType::Real => Ok(Value::Real(-operand.as_f32()?)),
         RootDataType::Dbl => Ok(Value::Double(-operand.as_f64()?)),
-        _ => Err(format!("unsupported datatype for arithmetic")),
+        _ => integer_arithmetic(&NEGATE, &result_type, operand, operand, workspace),
     }
 }
 
-fn plus(operand: &Value, workspace: &WorkSpace) -> Result<Value,String> {
+fn plus(operand: &Value, workspace: &WorkSpace) -> Result<Value,PplError> {
     let value_list = [RootDataType::from_value(operand, workspace)?];
     let result_type = strongest_datatype(&value_list, workspace)?;
     match  result_type {
         RootDataType::Int => Ok(Value::Int(operand.as_i32()?)),
+        RootDataType::Int64 => Ok(Value::Int64(operand.as_i64()?)),
+        #[cfg(feature = "bignum")]
+        RootDataType::Bignum => Ok(Value::Bignum(operand.as_bignum()?)),
+        RootDataType::Rational => Ok(Value::Rational(operand.as_rational()?)),
+        RootDataType::Decimal => Ok(Value::Decimal(operand.as_decimal()?)),
+        RootDataType::Structure(_) => Ok(Value::from(operand.as_complex()?)),
         RootDataType::Real => Ok(Value::Real(operand.as_f32()?)),
         RootDataType::Dbl => Ok(Value::Double(operand.as_f64()?)),
-        _ => Err(format!("unsupported datatype for arithmetic")),
+        _ => Err(ppl_error!(Type, "unsupported datatype for arithmetic")),
     }
 }
 
-fn power(left: &Value, right: &Value, workspace: &WorkSpace) -> Result<Value,String> {
+fn power(left: &Value, right: &Value, workspace: &WorkSpace) -> Result<Value,PplError> {
     let value_list = [RootDataType::from_value(left, workspace)?, RootDataType::from_value(right, workspace)?];
     let result_type = strongest_datatype(&value_list, workspace)?;
     match  result_type {
-        RootDataType::Int => Ok(Value::Int(left.as_i32()?.pow(right.as_i32()? as u32))),
+        RootDataType::Rational | RootDataType::Decimal => exact_power(&result_type, left, right),
+        RootDataType::Structure(_) => Ok(Value::from(left.as_complex()?.pow(&right.as_complex()?)?)),
         RootDataType::Real => Ok(Value::Real(left.as_f32()?.powf(right.as_f32()?))),
         RootDataType::Dbl => Ok(Value::Double(left.as_f64()?.powf(right.as_f64()?))),
-        _ => Err(format!("unsupported datatype for arithmetic")),
+        _ => {
+            if left.as_f64()? == 0.0 && right.as_f64()? < 0.0 {
+                return Err(ppl_error!(Arithmetic, "Division by zero"));
+            }
+            integer_arithmetic(&POWER, &result_type, left, right, workspace)
+        },
     }
 }
 
-fn subtract(left: &Value, right: &Value, workspace: &WorkSpace) -> Result<Value,String> {
+fn subtract(left: &Value, right: &Value, workspace: &WorkSpace) -> Result<Value,PplError> {
     let value_list = [RootDataType::from_value(left, workspace)?, RootDataType::from_value(right, workspace)?];
     let result_type = strongest_datatype(&value_list, workspace)?;
     match  result_type {
-        RootDataType::Int => Ok(Value::Int(left.as_i32()? - right.as_i32()?)),
+        RootDataType::Rational => Ok(Value::Rational(left.as_rational()?.checked_sub(&right.as_rational()?)?)),
+        RootDataType::Decimal => Ok(Value::Decimal(left.as_decimal()?.checked_sub(&right.as_decimal()?)?)),
+        RootDataType::Structure(_) => Ok(Value::from(left.as_complex()?.sub(&right.as_complex()?))),
         RootDataType::Real => Ok(Value::Real(left.as_f32()? - right.as_f32()?)),
         RootDataType::Dbl => Ok(Value::Double(left.as_f64()? - right.as_f64()?)),
-        _ => Err(format!("unsupported datatype for arithmetic")),
+        _ => integer_arithmetic(&SUBTRACT, &result_type, left, right, workspace),
+    }
+}
+
+
+//  Integer arithmetic is checked.  A result too large for its datatype is an error, unless the PromoteOnOverflow
+//  feature is set, when the operation is done again in the next wider datatype: int64 for an int, and bignum
+//  (if there is one) for an int64.
+
+struct IntegerOperation {
+    name: &'static str,
+    int: fn(i32, i32) -> Option<i32>,
+    int64: fn(i64, i64) -> Option<i64>,
+    #[cfg(feature = "bignum")]
+    bignum: fn(BigInt, BigInt) -> Result<BigInt,PplError>,
+}
+
+const ADD: IntegerOperation = IntegerOperation {
+    name: "addition",
+    int: i32::checked_add,
+    int64: i64::checked_add,
+    #[cfg(feature = "bignum")]
+    bignum: |left, right| Ok(left + right),
+};
+
+const DIVIDE: IntegerOperation = IntegerOperation {
+    name: "division",
+    int: i32::checked_div,
+    int64: i64::checked_div,
+    #[cfg(feature = "bignum")]
+    bignum: |left, right| Ok(left / right),
+};
+
+const MULTIPLY: IntegerOperation = IntegerOperation {
+    name: "multiplication",
+    int: i32::checked_mul,
+    int64: i64::checked_mul,
+    #[cfg(feature = "bignum")]
+    bignum: |left, right| Ok(left * right),
+};
+
+const NEGATE: IntegerOperation = IntegerOperation {
+    name: "negation",
+    int: |operand, _| operand.checked_neg(),
+    int64: |operand, _| operand.checked_neg(),
+    #[cfg(feature = "bignum")]
+    bignum: |operand, _| Ok(-operand),
+};
+
+//  A negative power of an integer is the integer part of its reciprocal, so only 1 and -1 have nonzero ones
+
+const POWER: IntegerOperation = IntegerOperation {
+    name: "power",
+    int: |base, exponent| match u32::try_from(exponent) {
+        Ok(exponent) => base.checked_pow(exponent),
+        Err(_) => Some(if base.abs() == 1 { base.pow((exponent % 2).unsigned_abs()) } else { 0 }),
+    },
+    int64: |base, exponent| match u32::try_from(exponent) {
+        Ok(exponent) => base.checked_pow(exponent),
+        Err(_) if exponent < 0 => Some(if base.abs() == 1 { base.pow((exponent % 2).unsigned_abs() as u32) } else { 0 }),
+        Err(_) => None,
+    },
+    #[cfg(feature = "bignum")]
+    bignum: |base, exponent| match exponent.to_u32() {
+        Some(exponent) => Ok(base.pow(exponent)),
+        None if exponent.sign() == num_bigint::Sign::Minus => {
+            Ok(if base.magnitude() == &num_bigint::BigUint::from(1u32) { base.pow((exponent % 2u32).magnitude().to_u32().unwrap()) } else { BigInt::from(0) })
+        },
+        None => Err(ppl_error!(Arithmetic, "{} is too large an exponent", exponent)),
+    },
+};
+
+const SUBTRACT: IntegerOperation = IntegerOperation {
+    name: "subtraction",
+    int: i32::checked_sub,
+    int64: i64::checked_sub,
+    #[cfg(feature = "bignum")]
+    bignum: |left, right| Ok(left - right),
+};
+
+fn integer_arithmetic(operation: &IntegerOperation, datatype: &RootDataType, left: &Value, right: &Value, workspace: &WorkSpace) -> Result<Value,PplError> {
+    let mut datatype = datatype.clone();
+    loop {
+        let result = match datatype {
+            RootDataType::Int => (operation.int)(left.as_i32()?, right.as_i32()?).map(Value::Int),
+            RootDataType::Int64 => (operation.int64)(left.as_i64()?, right.as_i64()?).map(Value::Int64),
+            #[cfg(feature = "bignum")]
+            RootDataType::Bignum => return (operation.bignum)(left.as_bignum()?, right.as_bignum()?).map(Value::Bignum),
+            _ => return Err(ppl_error!(Type, "unsupported datatype for arithmetic")),
+        };
+        match result {
+            Some(value) => return Ok(value),
+            None => datatype = wider_integer_datatype(&datatype, operation, workspace)?,
+        }
     }
 }
 
+fn wider_integer_datatype(datatype: &RootDataType, operation: &IntegerOperation, workspace: &WorkSpace) -> Result<RootDataType,PplError> {
+    if workspace.features.borrow().is_set(&Feature::PromoteOnOverflow) {
+        match datatype {
+            RootDataType::Int => return Ok(RootDataType::Int64),
+            #[cfg(feature = "bignum")]
+            RootDataType::Int64 => return Ok(RootDataType::Bignum),
+            _ => {},
+        }
+    }
+    Err(ppl_error!(Arithmetic, "Integer overflow in {}", operation.name))
+}
 
+//  Rationals and decimals are raised exactly to integer powers.  Any other power of them is taken as a dbl.
 
+fn exact_power(datatype: &RootDataType, left: &Value, right: &Value) -> Result<Value,PplError> {
+    let exponent = right.as_rational()?;
+    if !exponent.is_integer() {
+        return Ok(Value::Double(left.as_f64()?.powf(right.as_f64()?)));
+    }
+    match datatype {
+        RootDataType::Rational => Ok(Value::Rational(left.as_rational()?.pow(exponent.numerator())?)),
+        _ => Ok(Value::Decimal(left.as_decimal()?.pow(exponent.numerator())?)),
+    }
+}
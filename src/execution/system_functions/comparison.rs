@@ -1,6 +1,7 @@
 //  This module holds the PPL system functions for comparing user objects
 
 use crate::{workspace::WorkSpace, symbols::{metadata::{FormalArgument, FunctionArgumentList, ArgumentMechanism, MetaDataTypeName, FunctionClass, FunctionImplementation, FunctionDescription}, datatype::{RootDataType, strongest_datatype, self}, name::Name}, execution::value::Value};
+use crate::error::PplError;
 
 
 pub fn init(workspace: &WorkSpace) {
@@ -223,18 +224,18 @@ pub fn init(workspace: &WorkSpace) {
 
 
 
-fn and(left: &Value, right: &Value, _workspace: &WorkSpace) -> Result<Value,String> {
+fn and(left: &Value, right: &Value, _workspace: &WorkSpace) -> Result<Value,PplError> {
     Ok(Value::Bool(left.as_bool()? && right.as_bool()?))
 }
 
-fn eq(left: &Value, right: &Value, workspace: &WorkSpace) -> Result<Value,String> {
+pub fn eq(left: &Value, right: &Value, workspace: &WorkSpace) -> Result<Value,PplError> {
     match eq_internal(left, right, workspace) {
         Ok(result) => Ok(result),
         Err(_) => Ok(Value::Bool(false)),
     }
 }
 
-fn eq_internal(left: &Value, right: &Value, workspace: &WorkSpace) -> Result<Value,String> {
+fn eq_internal(left: &Value, right: &Value, workspace: &WorkSpace) -> Result<Value,PplError> {
     match (left, right) {
         (Value::Sequence(left_sequence), Value::Sequence(right_sequence)) => return Ok(Value::Bool(left_sequence.compare(right_sequence, workspace)? == 0)),
         (Value::Structure(left_structure), Value::Structure(right_structure)) => return Ok(Value::Bool(left_structure.compare(right_structure, workspace)? == 0)),
@@ -247,12 +248,18 @@ fn eq_internal(left: &Value, right: &Value, workspace: &WorkSpace) -> Result<Val
         RootDataType::Char => Ok(Value::Bool(left.as_char()? == right.as_char()?)),
         RootDataType::Dbl => Ok(Value::Bool(left.as_f64()? == right.as_f64()?)),
         RootDataType::Int => Ok(Value::Bool(left.as_i32()? == right.as_i32()?)),
+        RootDataType::Int64 => Ok(Value::Bool(left.as_i64()? == right.as_i64()?)),
+        #[cfg(feature = "bignum")]
+        RootDataType::Bignum => Ok(Value::Bool(left.as_bignum()? == right.as_bignum()?)),
+        RootDataType::Rational => Ok(Value::Bool(left.as_rational()? == right.as_rational()?)),
+        RootDataType::Decimal => Ok(Value::Bool(left.as_decimal()? == right.as_decimal()?)),
+        RootDataType::Structure(_) => Ok(Value::Bool(left.as_complex()? == right.as_complex()?)),
         RootDataType::Real => Ok(Value::Bool(left.as_f32()? == right.as_f32()?)),
-        _ => Err(format!("unsupported datatype for comparison")),
+        _ => Err(ppl_error!(Type, "unsupported datatype for comparison")),
     }
 }
 
-fn gr(left: &Value, right: &Value, workspace: &WorkSpace) -> Result<Value,String> {
+pub fn gr(left: &Value, right: &Value, workspace: &WorkSpace) -> Result<Value,PplError> {
     match (left, right) {
         (Value::Sequence(left_sequence), Value::Sequence(right_sequence)) => {
             return Ok(Value::Bool(left_sequence.compare(right_sequence, workspace)? > 0));
@@ -268,25 +275,30 @@ fn gr(left: &Value, right: &Value, workspace: &WorkSpace) -> Result<Value,String
         RootDataType::Char => Ok(Value::Bool(left.as_char()? > right.as_char()?)),
         RootDataType::Dbl => Ok(Value::Bool(left.as_f64()? > right.as_f64()?)),
         RootDataType::Int => Ok(Value::Bool(left.as_i32()? > right.as_i32()?)),
+        RootDataType::Int64 => Ok(Value::Bool(left.as_i64()? > right.as_i64()?)),
+        #[cfg(feature = "bignum")]
+        RootDataType::Bignum => Ok(Value::Bool(left.as_bignum()? > right.as_bignum()?)),
+        RootDataType::Rational => Ok(Value::Bool(left.as_rational()? > right.as_rational()?)),
+        RootDataType::Decimal => Ok(Value::Bool(left.as_decimal()? > right.as_decimal()?)),
         RootDataType::Real => Ok(Value::Bool(left.as_f32()? > right.as_f32()?)),
-        _ => Err(format!("unsupported datatype for comparison")),
+        _ => Err(ppl_error!(Type, "unsupported datatype for comparison")),
     }
 }
 
-fn greq(left: &Value, right: &Value, workspace: &WorkSpace) -> Result<Value,String> {
+fn greq(left: &Value, right: &Value, workspace: &WorkSpace) -> Result<Value,PplError> {
     Ok(Value::Bool(!less(left, right, workspace)?.as_bool()?))
 }
 
-fn is_an_instance_of(v: &Value, data_type: &Value, workspace: &WorkSpace) -> Result<Value,String> {
+fn is_an_instance_of(v: &Value, data_type: &Value, workspace: &WorkSpace) -> Result<Value,PplError> {
     let data_type_name = data_type.as_string();
     let target_datatype_reference = workspace.try_get_datatype(&data_type_name);
     if target_datatype_reference.is_some() {
         return Ok(Value::Bool(datatype::is_an_instance_of(v, target_datatype_reference.unwrap().root_data_type(), workspace)?));
     }
-    Err(format!("Datatype {} not found", data_type_name))
+    Err(ppl_error!(Name, "Datatype {} not found", data_type_name))
 }
 
-fn less(left: &Value, right: &Value, workspace: &WorkSpace) -> Result<Value,String> {
+pub fn less(left: &Value, right: &Value, workspace: &WorkSpace) -> Result<Value,PplError> {
     match (left, right) {
         (Value::Sequence(left_sequence), Value::Sequence(right_sequence)) => {
             return Ok(Value::Bool(left_sequence.compare(right_sequence, workspace)? < 0));
@@ -302,20 +314,25 @@ fn less(left: &Value, right: &Value, workspace: &WorkSpace) -> Result<Value,Stri
         RootDataType::Char => Ok(Value::Bool(left.as_char()? < right.as_char()?)),
         RootDataType::Dbl => Ok(Value::Bool(left.as_f64()? < right.as_f64()?)),
         RootDataType::Int => Ok(Value::Bool(left.as_i32()? < right.as_i32()?)),
+        RootDataType::Int64 => Ok(Value::Bool(left.as_i64()? < right.as_i64()?)),
+        #[cfg(feature = "bignum")]
+        RootDataType::Bignum => Ok(Value::Bool(left.as_bignum()? < right.as_bignum()?)),
+        RootDataType::Rational => Ok(Value::Bool(left.as_rational()? < right.as_rational()?)),
+        RootDataType::Decimal => Ok(Value::Bool(left.as_decimal()? < right.as_decimal()?)),
         RootDataType::Real => Ok(Value::Bool(left.as_f32()? < right.as_f32()?)),
-        _ => Err(format!("unsupported datatype for comparison")),
+        _ => Err(ppl_error!(Type, "unsupported datatype for comparison")),
     }
 }
 
-fn lesseq(left: &Value, right: &Value, workspace: &WorkSpace) -> Result<Value,String> {
+fn lesseq(left: &Value, right: &Value, workspace: &WorkSpace) -> Result<Value,PplError> {
     Ok(Value::Bool(!gr(left, right, workspace)?.as_bool()?))
 }
 
-fn noteq(left: &Value, right: &Value, workspace: &WorkSpace) -> Result<Value,String> {
+fn noteq(left: &Value, right: &Value, workspace: &WorkSpace) -> Result<Value,PplError> {
     Ok(Value::Bool(!eq(left, right, workspace)?.as_bool()?))
 }
 
-fn or(left: &Value, right: &Value, _workspace: &WorkSpace) -> Result<Value,String> {
+fn or(left: &Value, right: &Value, _workspace: &WorkSpace) -> Result<Value,PplError> {
     Ok(Value::Bool(left.as_bool()? || right.as_bool()?))
 }
 
@@ -0,0 +1,85 @@
+//  This module holds the PPL system functions for complex numbers.  abs, exp and sqrt are homonyms of the math
+//  library's functions, chosen for a complex argument; arg and conj also take a plain number, as a complex value
+//  with no imaginary part.
+
+use crate::{workspace::WorkSpace, symbols::{metadata::{FunctionDescription, FormalArgument, ArgumentMechanism, MetaDataTypeName, FunctionImplementation, FunctionClass, FunctionArgumentList}, name::Name}, execution::value::Value};
+use crate::error::PplError;
+
+
+pub fn init(workspace: &WorkSpace) {
+    workspace.add_library_function(
+        "abs",
+        FunctionDescription {
+            name: Name::from_str("abs"),
+            arguments: FunctionArgumentList::Fixed(vec![
+                FormalArgument { name: Name::from_str("z"), mechanism: ArgumentMechanism::ByValue,  datatype: MetaDataTypeName::from_str("complex") }]),
+            local_variables: None,
+            return_value: Some(MetaDataTypeName::from_str("real")),
+            implementation_class: FunctionImplementation::System(FunctionClass::Monadic(abs)),
+            help_text: String::from("Returns the modulus of a complex number") });
+
+    workspace.add_system_function(
+        "arg",
+        FunctionDescription {
+            name: Name::from_str("arg"),
+            arguments: FunctionArgumentList::Fixed(vec![
+                FormalArgument { name: Name::from_str("z"), mechanism: ArgumentMechanism::ByValue,  datatype: MetaDataTypeName::from_str("general") }]),
+            local_variables: None,
+            return_value: Some(MetaDataTypeName::from_str("real")),
+            implementation_class: FunctionImplementation::System(FunctionClass::Monadic(arg)),
+            help_text: String::from("Returns the argument of a complex number, the angle in radians from the positive real axis") });
+
+    workspace.add_system_function(
+        "conj",
+        FunctionDescription {
+            name: Name::from_str("conj"),
+            arguments: FunctionArgumentList::Fixed(vec![
+                FormalArgument { name: Name::from_str("z"), mechanism: ArgumentMechanism::ByValue,  datatype: MetaDataTypeName::from_str("general") }]),
+            local_variables: None,
+            return_value: Some(MetaDataTypeName::from_str("complex")),
+            implementation_class: FunctionImplementation::System(FunctionClass::Monadic(conj)),
+            help_text: String::from("Returns the complex conjugate") });
+
+    workspace.add_library_function(
+        "exp",
+        FunctionDescription {
+            name: Name::from_str("exp"),
+            arguments: FunctionArgumentList::Fixed(vec![
+                FormalArgument { name: Name::from_str("z"), mechanism: ArgumentMechanism::ByValue,  datatype: MetaDataTypeName::from_str("complex") }]),
+            local_variables: None,
+            return_value: Some(MetaDataTypeName::from_str("complex")),
+            implementation_class: FunctionImplementation::System(FunctionClass::Monadic(exp)),
+            help_text: String::from("Returns e raised to a complex power") });
+
+    workspace.add_library_function(
+        "sqrt",
+        FunctionDescription {
+            name: Name::from_str("sqrt"),
+            arguments: FunctionArgumentList::Fixed(vec![
+                FormalArgument { name: Name::from_str("z"), mechanism: ArgumentMechanism::ByValue,  datatype: MetaDataTypeName::from_str("complex") }]),
+            local_variables: None,
+            return_value: Some(MetaDataTypeName::from_str("complex")),
+            implementation_class: FunctionImplementation::System(FunctionClass::Monadic(sqrt)),
+            help_text: String::from("Returns the principal square root of a complex number, the one with a non-negative real part") });
+}
+
+
+fn abs(z: &Value, _workspace: &WorkSpace) -> Result<Value,PplError> {
+    Ok(Value::Real(z.as_complex()?.abs() as f32))
+}
+
+fn arg(z: &Value, _workspace: &WorkSpace) -> Result<Value,PplError> {
+    Ok(Value::Real(z.as_complex()?.arg() as f32))
+}
+
+fn conj(z: &Value, _workspace: &WorkSpace) -> Result<Value,PplError> {
+    Ok(Value::from(z.as_complex()?.conj()))
+}
+
+fn exp(z: &Value, _workspace: &WorkSpace) -> Result<Value,PplError> {
+    Ok(Value::from(z.as_complex()?.exp()))
+}
+
+fn sqrt(z: &Value, _workspace: &WorkSpace) -> Result<Value,PplError> {
+    Ok(Value::from(z.as_complex()?.sqrt()))
+}
@@ -1,4 +1,7 @@
-use crate::{workspace::WorkSpace, symbols::{metadata::{FunctionDescription, FunctionArgumentList, FunctionImplementation, FunctionClass, FormalArgument, ArgumentMechanism, MetaDataTypeName}, name::Name}, execution::value::{Value, sequence::SequenceInstance}};
+use crate::{workspace::{WorkSpace, GeneralSymbol, debug::{Step, Watchpoint}}, execution::runtime::invocation::ExecutionState, symbols::{metadata::{FunctionDescription, FunctionArgumentList, FunctionImplementation, FunctionClass, FormalArgument, ArgumentMechanism, MetaDataTypeName}, name::Name}, execution::value::{Value, sequence::SequenceInstance}};
+use crate::error::{Frame, PplError};
+use crate::execution::runtime::{coverage::Coverage, debug::{TraceFormat, TraceLog}, profile::Profile};
+use std::str::FromStr;
 
 
 
@@ -15,6 +18,37 @@ pub fn init(workspace: &WorkSpace) {
             implementation_class: FunctionImplementation::System(FunctionClass::NullValuedNullary(breakpoint)),
             help_text: String::from("Interrupts the currently executing function") });
                         
+    workspace.add_system_function(
+        "coverage", 
+        FunctionDescription { 
+            name: Name::from_str("coverage"), 
+            arguments: FunctionArgumentList::Fixed(vec![]),
+            local_variables: None,
+            return_value: None, 
+            implementation_class: FunctionImplementation::System(FunctionClass::NullValuedNullary(coverage)),
+            help_text: String::from("Starts recording which lines of every user function execute, and which way their conditions go") });
+
+    workspace.add_system_function(
+        "coverage.lcov", 
+        FunctionDescription { 
+            name: Name::from_str("coverage.lcov"), 
+            arguments: FunctionArgumentList::Fixed(vec![
+                FormalArgument { name: Name::from_str("filename"), mechanism: ArgumentMechanism::ByValue,  datatype: MetaDataTypeName::from_str("string") }]),
+            local_variables: None,
+            return_value: None, 
+            implementation_class: FunctionImplementation::System(FunctionClass::NullValuedMonadic(coverage_lcov)),
+            help_text: String::from("Writes the coverage recorded so far to a file in lcov's tracefile format, with a record for each user function") });
+
+    workspace.add_system_function(
+        "coverage.report", 
+        FunctionDescription { 
+            name: Name::from_str("coverage.report"), 
+            arguments: FunctionArgumentList::Fixed(vec![]),
+            local_variables: None,
+            return_value: Some(MetaDataTypeName::from_str("string")), 
+            implementation_class: FunctionImplementation::System(FunctionClass::Nullary(coverage_report)),
+            help_text: String::from("Lists every user function with the number of times each line executed, marking lines that never did with #####") });
+
     workspace.add_system_function(
         "debug", 
         FunctionDescription { 
@@ -37,6 +71,68 @@ pub fn init(workspace: &WorkSpace) {
             implementation_class: FunctionImplementation::System(FunctionClass::NullValuedDiadic(debug)),
             help_text: String::from(r#"{"SET" | "CLEAR"} debug options {"LEX" | "PARSE"}"#) });
                                 
+    workspace.add_system_function(
+        "down", 
+        FunctionDescription { 
+            name: Name::from_str("down"), 
+            arguments: FunctionArgumentList::Fixed(vec![]),
+            local_variables: None,
+            return_value: Some(MetaDataTypeName::from_str("string")), 
+            implementation_class: FunctionImplementation::System(FunctionClass::Nullary(down)),
+            help_text: String::from("Selects the frame called by the selected frame of a stopped function") });
+
+    workspace.add_system_function(
+        "frame", 
+        FunctionDescription { 
+            name: Name::from_str("frame"), 
+            arguments: FunctionArgumentList::Fixed(vec![]),
+            local_variables: None,
+            return_value: Some(MetaDataTypeName::from_str("string")), 
+            implementation_class: FunctionImplementation::System(FunctionClass::Nullary(show_frame)),
+            help_text: String::from("Shows the frame whose variables immediate mode sees while a function is stopped") });
+
+    workspace.add_system_function(
+        "frame", 
+        FunctionDescription { 
+            name: Name::from_str("frame"), 
+            arguments: FunctionArgumentList::Fixed(vec![
+                FormalArgument { name: Name::from_str("n"), mechanism: ArgumentMechanism::ByValue,  datatype: MetaDataTypeName::from_str("int") }]), 
+            local_variables: None,
+            return_value: Some(MetaDataTypeName::from_str("string")), 
+            implementation_class: FunctionImplementation::System(FunctionClass::Monadic(frame)),
+            help_text: String::from("Selects frame n of a stopped function, where 0 is the stopped function, 1 its caller, and so on") });
+
+    workspace.add_system_function(
+        "profile", 
+        FunctionDescription { 
+            name: Name::from_str("profile"), 
+            arguments: FunctionArgumentList::Fixed(vec![]),
+            local_variables: None,
+            return_value: None, 
+            implementation_class: FunctionImplementation::System(FunctionClass::NullValuedNullary(profile)),
+            help_text: String::from("Starts counting the executions of each line of every user function, and the time each takes") });
+
+    workspace.add_system_function(
+        "profile.report", 
+        FunctionDescription { 
+            name: Name::from_str("profile.report"), 
+            arguments: FunctionArgumentList::Fixed(vec![]),
+            local_variables: None,
+            return_value: Some(MetaDataTypeName::from_str("string")), 
+            implementation_class: FunctionImplementation::System(FunctionClass::Nullary(profile_report)),
+            help_text: String::from("Lists the functions profiled, then every line profiled, each with the most time first") });
+
+    workspace.add_system_function(
+        "profile.report", 
+        FunctionDescription { 
+            name: Name::from_str("profile.report"), 
+            arguments: FunctionArgumentList::Fixed(vec![
+                FormalArgument { name: Name::from_str("n"), mechanism: ArgumentMechanism::ByValue,  datatype: MetaDataTypeName::from_str("int") }]),
+            local_variables: None,
+            return_value: Some(MetaDataTypeName::from_str("string")), 
+            implementation_class: FunctionImplementation::System(FunctionClass::Monadic(profile_report_top)),
+            help_text: String::from("Lists the functions profiled, then the n lines that took the most time") });
+
     workspace.add_system_function(
         "stack.usage", 
         FunctionDescription { 
@@ -47,6 +143,36 @@ pub fn init(workspace: &WorkSpace) {
             implementation_class: FunctionImplementation::System(FunctionClass::Nullary(stack_usage)),
             help_text: String::from(r#"Returns the amount of stack space consumed by the function, in kilobytes"#) });
                                                     
+    workspace.add_system_function(
+        "step", 
+        FunctionDescription { 
+            name: Name::from_str("step"), 
+            arguments: FunctionArgumentList::Fixed(vec![]),
+            local_variables: None,
+            return_value: None, 
+            implementation_class: FunctionImplementation::System(FunctionClass::NullValuedNullary(step)),
+            help_text: String::from("Continues a stopped function for one statement, stopping in any function that statement calls") });
+
+    workspace.add_system_function(
+        "step.out", 
+        FunctionDescription { 
+            name: Name::from_str("step.out"), 
+            arguments: FunctionArgumentList::Fixed(vec![]),
+            local_variables: None,
+            return_value: None, 
+            implementation_class: FunctionImplementation::System(FunctionClass::NullValuedNullary(step_out)),
+            help_text: String::from("Continues a stopped function until it returns, stopping in its caller") });
+
+    workspace.add_system_function(
+        "step.over", 
+        FunctionDescription { 
+            name: Name::from_str("step.over"), 
+            arguments: FunctionArgumentList::Fixed(vec![]),
+            local_variables: None,
+            return_value: None, 
+            implementation_class: FunctionImplementation::System(FunctionClass::NullValuedNullary(step_over)),
+            help_text: String::from("Continues a stopped function for one statement, running any functions that statement calls") });
+
     workspace.add_system_function(
         "stop", 
         FunctionDescription { 
@@ -57,6 +183,26 @@ pub fn init(workspace: &WorkSpace) {
             implementation_class: FunctionImplementation::System(FunctionClass::NullValuedVarying(stop)),
             help_text: String::from("Sets breakpoints at the specified lines in the designated function.") });
                                 
+    workspace.add_system_function(
+        "stop.after", 
+        FunctionDescription { 
+            name: Name::from_str("stop.after"), 
+            arguments: FunctionArgumentList::Varying(ArgumentMechanism::ByReference), 
+            local_variables: None,
+            return_value: None, 
+            implementation_class: FunctionImplementation::System(FunctionClass::NullValuedVarying(stop_after)),
+            help_text: String::from("stop.after(function, line, n) sets a breakpoint that stops the nth time the line is reached, and every time after") });
+
+    workspace.add_system_function(
+        "stop.if", 
+        FunctionDescription { 
+            name: Name::from_str("stop.if"), 
+            arguments: FunctionArgumentList::Varying(ArgumentMechanism::ByReference), 
+            local_variables: None,
+            return_value: None, 
+            implementation_class: FunctionImplementation::System(FunctionClass::NullValuedVarying(stop_if)),
+            help_text: String::from(r#"stop.if(function, line, "condition") sets a breakpoint that stops when the condition, evaluated in the function, is true"#) });
+
     workspace.add_system_function(
         "trace", 
         FunctionDescription { 
@@ -66,7 +212,49 @@ pub fn init(workspace: &WorkSpace) {
             return_value: None, 
             implementation_class: FunctionImplementation::System(FunctionClass::NullValuedVarying(trace)),
             help_text: String::from("Trace execution of the specified function.") });
+
+    workspace.add_system_function(
+        "trace.log", 
+        FunctionDescription { 
+            name: Name::from_str("trace.log"), 
+            arguments: FunctionArgumentList::Fixed(vec![]),
+            local_variables: None,
+            return_value: Some(MetaDataTypeName::from_str("string")), 
+            implementation_class: FunctionImplementation::System(FunctionClass::Nullary(show_trace_log)),
+            help_text: String::from("Returns the calls and returns logged so far, when the trace log is kept in memory") });
+
+    workspace.add_system_function(
+        "trace.log", 
+        FunctionDescription { 
+            name: Name::from_str("trace.log"), 
+            arguments: FunctionArgumentList::Fixed(vec![
+                FormalArgument { name: Name::from_str("destination"), mechanism: ArgumentMechanism::ByValue,  datatype: MetaDataTypeName::from_str("string") },
+                FormalArgument { name: Name::from_str("format"), mechanism: ArgumentMechanism::ByValue,  datatype: MetaDataTypeName::from_str("string") }]), 
+            local_variables: None,
+            return_value: None, 
+            implementation_class: FunctionImplementation::System(FunctionClass::NullValuedDiadic(trace_log)),
+            help_text: String::from(r#"trace.log(destination, format) logs every function call, with its arguments, and every return, with its value.  The destination is a file name, or "" to keep the log in memory; the format is "text" or "json""#) });
                                 
+    workspace.add_system_function(
+        "uncoverage", 
+        FunctionDescription { 
+            name: Name::from_str("uncoverage"), 
+            arguments: FunctionArgumentList::Fixed(vec![]),
+            local_variables: None,
+            return_value: None, 
+            implementation_class: FunctionImplementation::System(FunctionClass::NullValuedNullary(uncoverage)),
+            help_text: String::from("Stops recording coverage, keeping what was recorded for coverage.report() and coverage.lcov()") });
+
+    workspace.add_system_function(
+        "unprofile", 
+        FunctionDescription { 
+            name: Name::from_str("unprofile"), 
+            arguments: FunctionArgumentList::Fixed(vec![]),
+            local_variables: None,
+            return_value: None, 
+            implementation_class: FunctionImplementation::System(FunctionClass::NullValuedNullary(unprofile)),
+            help_text: String::from("Stops profiling, keeping the counts and times for profile.report()") });
+
     workspace.add_system_function(
         "unstop", 
         FunctionDescription { 
@@ -86,57 +274,312 @@ pub fn init(workspace: &WorkSpace) {
             return_value: None, 
             implementation_class: FunctionImplementation::System(FunctionClass::NullValuedVarying(untrace)),
             help_text: String::from("Cease tracing execution of the specified function.") });
+
+    workspace.add_system_function(
+        "untrace.log", 
+        FunctionDescription { 
+            name: Name::from_str("untrace.log"), 
+            arguments: FunctionArgumentList::Fixed(vec![]),
+            local_variables: None,
+            return_value: None, 
+            implementation_class: FunctionImplementation::System(FunctionClass::NullValuedNullary(untrace_log)),
+            help_text: String::from("Stops logging function calls and returns, closing the log") });
+
+    workspace.add_system_function(
+        "unwatch", 
+        FunctionDescription { 
+            name: Name::from_str("unwatch"), 
+            arguments: FunctionArgumentList::Varying(ArgumentMechanism::ByReference), 
+            local_variables: None,
+            return_value: None, 
+            implementation_class: FunctionImplementation::System(FunctionClass::NullValuedVarying(unwatch)),
+            help_text: String::from("Removes the watchpoints on the specified variables, or all watchpoints") });
+
+    workspace.add_system_function(
+        "up", 
+        FunctionDescription { 
+            name: Name::from_str("up"), 
+            arguments: FunctionArgumentList::Fixed(vec![]),
+            local_variables: None,
+            return_value: Some(MetaDataTypeName::from_str("string")), 
+            implementation_class: FunctionImplementation::System(FunctionClass::Nullary(up)),
+            help_text: String::from("Selects the caller of the selected frame of a stopped function") });
+
+    workspace.add_system_function(
+        "watch", 
+        FunctionDescription { 
+            name: Name::from_str("watch"), 
+            arguments: FunctionArgumentList::Varying(ArgumentMechanism::ByReference), 
+            local_variables: None,
+            return_value: None, 
+            implementation_class: FunctionImplementation::System(FunctionClass::NullValuedVarying(watch)),
+            help_text: String::from("Stops execution when any of the specified variables, or their members, is assigned") });
+
+    workspace.add_system_function(
+        "where", 
+        FunctionDescription { 
+            name: Name::from_str("where"), 
+            arguments: FunctionArgumentList::Fixed(vec![]),
+            local_variables: None,
+            return_value: Some(MetaDataTypeName::from_str("string")), 
+            implementation_class: FunctionImplementation::System(FunctionClass::Nullary(show_where)),
+            help_text: String::from("Shows the functions that are executing, innermost first, with the line each has reached") });
 }
 
 
-fn breakpoint(_workspace: &WorkSpace) -> Result<(),String> {
-    Err(format!("Breakpoint"))
+fn breakpoint(_workspace: &WorkSpace) -> Result<(),PplError> {
+    Err(ppl_error!(Stop, "Breakpoint"))
 }
 
-fn debug(operation: &Value, option: &Value, workspace: &WorkSpace) -> Result<(),String> {
+fn coverage(workspace: &WorkSpace) -> Result<(),PplError> {
+    *workspace.coverage.borrow_mut() = Some(Coverage::new());
+    Ok(())
+}
+
+fn coverage_lcov(filename: &Value, workspace: &WorkSpace) -> Result<(),PplError> {
+    match workspace.coverage.borrow().as_ref() {
+        Some(coverage) => Ok(std::fs::write(filename.as_string(), coverage.lcov(&workspace.get_user_functions()))?),
+        None => Err(ppl_error!(Runtime, "Coverage has not been recorded")),
+    }
+}
+
+fn coverage_report(workspace: &WorkSpace) -> Result<Value,PplError> {
+    match workspace.coverage.borrow().as_ref() {
+        Some(coverage) => Ok(SequenceInstance::construct_string_sequence(coverage.listing(&workspace.get_user_functions()).as_str())),
+        None => Err(ppl_error!(Runtime, "Coverage has not been recorded")),
+    }
+}
+
+fn debug(operation: &Value, option: &Value, workspace: &WorkSpace) -> Result<(),PplError> {
     match operation.as_string().to_ascii_lowercase().as_str() {
         "on" | "set" => workspace.debug_options.borrow_mut().set_str(option.as_string().as_str())?,
         "off" | "clear" => workspace.debug_options.borrow_mut().clear_str(option.as_string().as_str())?,
-        _ => return Err(format!("{} is not a valid debug operation", operation.as_string())),
+        _ => return Err(ppl_error!(Type, "{} is not a valid debug operation", operation.as_string())),
     }
     Ok(())
 }
 
-fn show_debug(workspace: &WorkSpace) -> Result<Value,String> {
+fn down(workspace: &WorkSpace) -> Result<Value,PplError> {
+    match workspace.get_selected_frame() {
+        0 => Err(ppl_error!(Index, "The innermost frame is already selected")),
+        selected => select_frame(selected - 1, workspace),
+    }
+}
+
+fn frame(n: &Value, workspace: &WorkSpace) -> Result<Value,PplError> {
+    let n = n.as_usize()?;
+    select_frame(n, workspace)
+}
+
+//  The frames of stopped functions that can be selected, innermost first
+
+fn function_frames(workspace: &WorkSpace) -> Result<Vec<Frame>,PplError> {
+    if !workspace.is_function_stopped() {
+        return Err(ppl_error!(Runtime, "No function is stopped"));
+    }
+    Ok(workspace.traceback().into_iter().filter(|frame| frame.function_name.is_some()).collect())
+}
+
+fn select_frame(n: usize, workspace: &WorkSpace) -> Result<Value,PplError> {
+    let frames = function_frames(workspace)?;
+    match frames.get(n) {
+        Some(frame) => {
+            workspace.select_frame(n);
+            Ok(SequenceInstance::construct_string_sequence(format!("{}: {}", n, frame).as_str()))
+        },
+        None => Err(ppl_error!(Index, "There is no frame {}", n)),
+    }
+}
+
+fn show_debug(workspace: &WorkSpace) -> Result<Value,PplError> {
     let options = workspace.debug_options.borrow().show();
     Ok(SequenceInstance::construct_string_sequence(options.as_str()))
 }
 
-fn stack_usage(workspace: &WorkSpace) -> Result<Value,String> {
+fn profile(workspace: &WorkSpace) -> Result<(),PplError> {
+    *workspace.profile.borrow_mut() = Some(Profile::new());
+    Ok(())
+}
+
+fn profile_report(workspace: &WorkSpace) -> Result<Value,PplError> {
+    report_profile(None, workspace)
+}
+
+fn profile_report_top(n: &Value, workspace: &WorkSpace) -> Result<Value,PplError> {
+    report_profile(Some(n.as_usize()?), workspace)
+}
+
+fn report_profile(limit: Option<usize>, workspace: &WorkSpace) -> Result<Value,PplError> {
+    match workspace.profile.borrow().as_ref() {
+        Some(profile) => Ok(SequenceInstance::construct_string_sequence(profile.report(limit).as_str())),
+        None => Err(ppl_error!(Runtime, "Nothing has been profiled")),
+    }
+}
+
+fn stack_usage(workspace: &WorkSpace) -> Result<Value,PplError> {
     Ok(Value::Int((workspace.get_stack_size() / 1024) as i32))
 }
 
-fn stop(args: &Vec<Value>, workspace: &WorkSpace) -> Result<(),String> {
+fn step(workspace: &WorkSpace) -> Result<(),PplError> {
+    resume_stepping(|_| Step::Into, workspace)
+}
+
+fn step_out(workspace: &WorkSpace) -> Result<(),PplError> {
+    resume_stepping(Step::Out, workspace)
+}
+
+fn step_over(workspace: &WorkSpace) -> Result<(),PplError> {
+    resume_stepping(Step::Over, workspace)
+}
+
+//  Like -->, this discards the immediate mode command and resumes the stopped function, but at the statement
+//  where it stopped
+
+fn resume_stepping(step: fn(usize) -> Step, workspace: &WorkSpace) -> Result<(),PplError> {
+    loop {
+        match workspace.current_invocation() {
+            Some(invocation) if invocation.get_fib().is_some() => {
+                if invocation.get_execution_state() != ExecutionState::Stopped {
+                    return Err(ppl_error!(Runtime, "Only a stopped function can be stepped"));
+                }
+                invocation.repeat();
+                invocation.set_execution_state(ExecutionState::Resumed);
+                *workspace.step.borrow_mut() = Some(step(workspace.get_invocation_depth()));
+                return Ok(());
+            },
+            Some(_) => workspace.end_invocation(),
+            None => return Err(ppl_error!(Runtime, "Only a stopped function can be stepped")),
+        }
+    }
+}
+
+fn stop(args: &Vec<Value>, workspace: &WorkSpace) -> Result<(),PplError> {
     if args.len() == 0 {
-        return Err(format!("Stop requires a function name"));
+        return Err(ppl_error!(Argument, "Stop requires a function name"));
     }
     crate::execution::functions::stop(&args[0], &args[1..], workspace)
 }
 
-fn trace(args: &Vec<Value>, workspace: &WorkSpace) -> Result<(),String> {
+fn stop_after(args: &Vec<Value>, workspace: &WorkSpace) -> Result<(),PplError> {
+    if args.len() != 3 {
+        return Err(ppl_error!(Argument, "stop.after requires a function name, a line number and a count"));
+    }
+    crate::execution::functions::stop_after(&args[0], &args[1], &args[2], workspace)
+}
+
+fn stop_if(args: &Vec<Value>, workspace: &WorkSpace) -> Result<(),PplError> {
+    if args.len() != 3 {
+        return Err(ppl_error!(Argument, "stop.if requires a function name, a line number and a condition"));
+    }
+    crate::execution::functions::stop_if(&args[0], &args[1], &args[2], workspace)
+}
+
+fn trace(args: &Vec<Value>, workspace: &WorkSpace) -> Result<(),PplError> {
     if args.len() == 0 {
-        return Err(format!("Trace requires a function name"));
+        return Err(ppl_error!(Argument, "Trace requires a function name"));
     }
     crate::execution::functions::trace(&args[0], &args[1..], workspace)
 }
-fn unstop(args: &Vec<Value>, workspace: &WorkSpace) -> Result<(),String> {
+
+fn trace_log(destination: &Value, format: &Value, workspace: &WorkSpace) -> Result<(),PplError> {
+    let format = TraceFormat::from_str(format.as_string().as_str())
+        .map_err(|_| ppl_error!(Argument, "The trace log format must be \"text\" or \"json\", not \"{}\"", format.as_string()))?;
+    let log = match destination.as_string().as_str() {
+        "" => TraceLog::to_buffer(format),
+        filename => TraceLog::to_file(filename, format)?,
+    };
+    *workspace.trace_log.borrow_mut() = Some(log);
+    Ok(())
+}
+
+fn show_trace_log(workspace: &WorkSpace) -> Result<Value,PplError> {
+    match workspace.trace_log.borrow().as_ref() {
+        Some(log) => Ok(SequenceInstance::construct_string_sequence(log.contents().as_str())),
+        None => Err(ppl_error!(Runtime, "The trace log is not on")),
+    }
+}
+
+fn uncoverage(workspace: &WorkSpace) -> Result<(),PplError> {
+    if let Some(coverage) = workspace.coverage.borrow_mut().as_mut() {
+        coverage.stop();
+    }
+    Ok(())
+}
+
+fn unprofile(workspace: &WorkSpace) -> Result<(),PplError> {
+    if let Some(profile) = workspace.profile.borrow_mut().as_mut() {
+        profile.stop();
+    }
+    Ok(())
+}
+
+fn unstop(args: &Vec<Value>, workspace: &WorkSpace) -> Result<(),PplError> {
     if args.len() == 0 {
-        return Err(format!("Unstop requires a function name"));
+        return Err(ppl_error!(Argument, "Unstop requires a function name"));
     }
     crate::execution::functions::unstop(&args[0], &args[1..], workspace)
 }
 
-fn untrace(args: &Vec<Value>, workspace: &WorkSpace) -> Result<(),String> {
+fn untrace(args: &Vec<Value>, workspace: &WorkSpace) -> Result<(),PplError> {
     if args.len() == 0 {
-        return Err(format!("Untrace requires a function name"));
+        return Err(ppl_error!(Argument, "Untrace requires a function name"));
     }
     crate::execution::functions::untrace(&args[0], &args[1..], workspace)
 }
 
+fn untrace_log(workspace: &WorkSpace) -> Result<(),PplError> {
+    *workspace.trace_log.borrow_mut() = None;
+    Ok(())
+}
 
+fn unwatch(args: &Vec<Value>, workspace: &WorkSpace) -> Result<(),PplError> {
+    if args.is_empty() {
+        workspace.watchpoints.borrow_mut().clear();
+        return Ok(());
+    }
+    for arg in args {
+        let name = arg.as_string().to_ascii_lowercase();
+        workspace.watchpoints.borrow_mut().retain(|watchpoint| watchpoint.name != name);
+    }
+    Ok(())
+}
 
+fn up(workspace: &WorkSpace) -> Result<Value,PplError> {
+    select_frame(workspace.get_selected_frame() + 1, workspace)
+}
+
+fn watch(args: &Vec<Value>, workspace: &WorkSpace) -> Result<(),PplError> {
+    if args.is_empty() {
+        return Err(ppl_error!(Argument, "Watch requires a variable name"));
+    }
+    for arg in args {
+        match arg {
+            Value::Symbol(symbol) => match symbol.as_symbol() {
+                GeneralSymbol::Variable(variable) => workspace.watchpoints.borrow_mut().push(Watchpoint::new(&symbol.as_string(), variable)),
+                _ => return Err(ppl_error!(Type, "{} is not a variable", symbol.as_string())),
+            },
+            _ => return Err(ppl_error!(Type, "{} is not a variable", arg)),
+        }
+    }
+    Ok(())
+}
+
+fn show_frame(workspace: &WorkSpace) -> Result<Value,PplError> {
+    let selected = workspace.get_selected_frame();
+    match function_frames(workspace)?.get(selected) {
+        Some(frame) => Ok(SequenceInstance::construct_string_sequence(format!("{}: {}", selected, frame).as_str())),
+        None => Err(ppl_error!(Index, "There is no frame {}", selected)),
+    }
+}
+
+fn show_where(workspace: &WorkSpace) -> Result<Value,PplError> {
+
+    //  Leave out the immediate mode command that's calling where()
+
+    let mut frames = workspace.traceback();
+    if frames.first().is_some_and(|frame| frame.function_name.is_none()) {
+        frames.remove(0);
+    }
+    let trace: Vec<String> = frames.iter().map(|frame| frame.to_string()).collect();
+    Ok(SequenceInstance::construct_string_sequence(trace.join("\n").as_str()))
+}
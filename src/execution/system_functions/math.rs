@@ -0,0 +1,268 @@
+//  This module holds the PPL math library.  Each function is overloaded for int, real and dbl arguments, with a
+//  homonym for each datatype that's chosen by the datatype of the argument, alongside the complex homonyms of abs,
+//  exp and sqrt.  A value of any other numeric datatype goes to the first homonym it converts to, so the dbl ones
+//  are added first.  A program's own function or variable of the same name hides the library's.
+
+use crate::{workspace::WorkSpace, symbols::{metadata::{FunctionDescription, FormalArgument, ArgumentMechanism, MetaDataTypeName, FunctionImplementation, FunctionClass, FunctionArgumentList}, name::Name}, execution::value::Value};
+use crate::error::PplError;
+use super::comparison;
+
+
+const FLOAT_DATATYPES: [&str; 3] = ["dbl", "real", "real"];
+const SAME_DATATYPES: [&str; 3] = ["dbl", "real", "int"];
+
+pub fn init(workspace: &WorkSpace) {
+
+    //  Functions of a real variable, which are real for an int argument
+
+    for (name, implementation, help_text) in [
+        ("acos", acos as fn(&Value, &WorkSpace) -> Result<Value,PplError>, "Returns the arc cosine, in radians, of a value from -1 to 1"),
+        ("asin", asin, "Returns the arc sine, in radians, of a value from -1 to 1"),
+        ("atan", atan, "Returns the arc tangent, in radians"),
+        ("cos", cos, "Returns the cosine of an angle in radians"),
+        ("exp", exp, "Returns e raised to a power"),
+        ("ln", ln, "Returns the natural logarithm of a positive value"),
+        ("log10", log10, "Returns the base 10 logarithm of a positive value"),
+        ("sin", sin, "Returns the sine of an angle in radians"),
+        ("sqrt", sqrt, "Returns the square root of a value that isn't negative"),
+        ("tan", tan, "Returns the tangent of an angle in radians"),
+    ] {
+        for (argument_datatype, return_datatype) in SAME_DATATYPES.iter().zip(FLOAT_DATATYPES) {
+            add_overload(workspace, name, &[argument_datatype], return_datatype, FunctionClass::Monadic(implementation), help_text);
+        }
+    }
+
+    //  Functions whose result has the datatype of the argument
+
+    for (name, implementation, help_text) in [
+        ("abs", abs as fn(&Value, &WorkSpace) -> Result<Value,PplError>, "Returns the absolute value"),
+        ("ceil", ceil, "Rounds up, to the nearest whole number at or above the value"),
+        ("floor", floor, "Rounds down, to the nearest whole number at or below the value"),
+        ("round", round, "Rounds to the nearest whole number, with halves rounded away from zero"),
+        ("trunc", trunc, "Rounds toward zero, dropping any fraction"),
+    ] {
+        for datatype in SAME_DATATYPES {
+            add_overload(workspace, name, &[datatype], datatype, FunctionClass::Monadic(implementation), help_text);
+        }
+    }
+
+    for datatype in SAME_DATATYPES {
+        add_overload(workspace, "sign", &[datatype], "int", FunctionClass::Monadic(sign), "Returns -1, 0 or 1 as the value is negative, zero or positive");
+    }
+
+    //  Functions of two values of the same datatype
+
+    for (name, implementation, help_text) in [
+        ("max", max as fn(&Value, &Value, &WorkSpace) -> Result<Value,PplError>, "Returns the larger of two values"),
+        ("min", min, "Returns the smaller of two values"),
+        ("mod", modulo, "Returns the remainder of dividing x by y, which has the sign of y"),
+        ("rem", rem, "Returns the remainder of dividing x by y, which has the sign of x"),
+    ] {
+        for datatype in SAME_DATATYPES {
+            add_overload(workspace, name, &[datatype, datatype], datatype, FunctionClass::Diadic(implementation), help_text);
+        }
+    }
+
+    //  max and min also compare anything else that > and < do, such as strings
+
+    add_overload(workspace, "max", &["general", "general"], "general", FunctionClass::Diadic(larger), "Returns the larger of two values");
+    add_overload(workspace, "min", &["general", "general"], "general", FunctionClass::Diadic(smaller), "Returns the smaller of two values");
+
+    for datatype in ["int64", "int"] {
+        add_overload(workspace, "gcd", &[datatype, datatype], datatype, FunctionClass::Diadic(gcd), "Returns the greatest common divisor of two integers");
+    }
+
+    workspace.add_library_function(
+        "pi",
+        FunctionDescription {
+            name: Name::from_str("pi"),
+            arguments: FunctionArgumentList::Fixed(Vec::new()),
+            local_variables: None,
+            return_value: Some(MetaDataTypeName::from_str("dbl")),
+            implementation_class: FunctionImplementation::System(FunctionClass::Nullary(pi)),
+            help_text: String::from("Returns the ratio of a circle's circumference to its diameter") });
+}
+
+fn add_overload(workspace: &WorkSpace, name: &str, argument_datatypes: &[&str], return_datatype: &str, implementation: FunctionClass, help_text: &str) {
+    workspace.add_library_function(
+        name,
+        FunctionDescription {
+            name: Name::from_str(name),
+            arguments: FunctionArgumentList::Fixed(argument_datatypes.iter().zip(["x", "y"]).map(|(datatype, argument)|
+                FormalArgument { name: Name::from_str(argument), mechanism: ArgumentMechanism::ByValue,  datatype: MetaDataTypeName::from_str(datatype) }).collect()),
+            local_variables: None,
+            return_value: Some(MetaDataTypeName::from_str(return_datatype)),
+            implementation_class: FunctionImplementation::System(implementation),
+            help_text: String::from(help_text) });
+}
+
+
+fn abs(x: &Value, _workspace: &WorkSpace) -> Result<Value,PplError> {
+    match x {
+        Value::Int(v) => v.checked_abs().map(Value::Int).ok_or_else(|| ppl_error!(Arithmetic, "Integer overflow in abs")),
+        _ => same_datatype(x, f32::abs, f64::abs),
+    }
+}
+
+fn acos(x: &Value, _workspace: &WorkSpace) -> Result<Value,PplError> {
+    float_function("acos", x, f32::acos, f64::acos)
+}
+
+fn asin(x: &Value, _workspace: &WorkSpace) -> Result<Value,PplError> {
+    float_function("asin", x, f32::asin, f64::asin)
+}
+
+fn atan(x: &Value, _workspace: &WorkSpace) -> Result<Value,PplError> {
+    float_function("atan", x, f32::atan, f64::atan)
+}
+
+fn ceil(x: &Value, _workspace: &WorkSpace) -> Result<Value,PplError> {
+    same_datatype(x, f32::ceil, f64::ceil)
+}
+
+fn cos(x: &Value, _workspace: &WorkSpace) -> Result<Value,PplError> {
+    float_function("cos", x, f32::cos, f64::cos)
+}
+
+fn exp(x: &Value, _workspace: &WorkSpace) -> Result<Value,PplError> {
+    float_function("exp", x, f32::exp, f64::exp)
+}
+
+fn floor(x: &Value, _workspace: &WorkSpace) -> Result<Value,PplError> {
+    same_datatype(x, f32::floor, f64::floor)
+}
+
+fn gcd(x: &Value, y: &Value, _workspace: &WorkSpace) -> Result<Value,PplError> {
+    let (mut a, mut b) = (x.as_i64()?.unsigned_abs(), y.as_i64()?.unsigned_abs());
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    let overflow = || ppl_error!(Arithmetic, "Integer overflow in gcd");
+    match x {
+        Value::Int(_) => i32::try_from(a).map(Value::Int).map_err(|_| overflow()),
+        _ => i64::try_from(a).map(Value::Int64).map_err(|_| overflow()),
+    }
+}
+
+fn larger(x: &Value, y: &Value, workspace: &WorkSpace) -> Result<Value,PplError> {
+    Ok(if comparison::less(x, y, workspace)?.as_bool()? { y.clone() } else { x.clone() })
+}
+
+fn ln(x: &Value, _workspace: &WorkSpace) -> Result<Value,PplError> {
+    float_function("ln", x, f32::ln, f64::ln)
+}
+
+fn log10(x: &Value, _workspace: &WorkSpace) -> Result<Value,PplError> {
+    float_function("log10", x, f32::log10, f64::log10)
+}
+
+fn max(x: &Value, y: &Value, _workspace: &WorkSpace) -> Result<Value,PplError> {
+    match (x, y) {
+        (Value::Int(a), Value::Int(b)) => Ok(Value::Int(*a.max(b))),
+        (Value::Real(a), Value::Real(b)) => Ok(Value::Real(a.max(*b))),
+        _ => Ok(Value::Double(x.as_f64()?.max(y.as_f64()?))),
+    }
+}
+
+fn min(x: &Value, y: &Value, _workspace: &WorkSpace) -> Result<Value,PplError> {
+    match (x, y) {
+        (Value::Int(a), Value::Int(b)) => Ok(Value::Int(*a.min(b))),
+        (Value::Real(a), Value::Real(b)) => Ok(Value::Real(a.min(*b))),
+        _ => Ok(Value::Double(x.as_f64()?.min(y.as_f64()?))),
+    }
+}
+
+//  The remainder of a division rounded down, rather than toward zero, so that it has the sign of the divisor
+
+fn modulo(x: &Value, y: &Value, _workspace: &WorkSpace) -> Result<Value,PplError> {
+    let remainder = truncated_remainder("mod", x, y)?;
+    let (remainder_value, divisor) = (remainder.as_f64()?, y.as_f64()?);
+    if remainder_value != 0.0 && (remainder_value < 0.0) != (divisor < 0.0) {
+        match remainder {
+            Value::Int(r) => Ok(Value::Int(r + y.as_i32()?)),
+            Value::Real(r) => Ok(Value::Real(r + y.as_f32()?)),
+            _ => Ok(Value::Double(remainder_value + divisor)),
+        }
+    } else {
+        Ok(remainder)
+    }
+}
+
+fn pi(_workspace: &WorkSpace) -> Result<Value,PplError> {
+    Ok(Value::Double(std::f64::consts::PI))
+}
+
+fn rem(x: &Value, y: &Value, _workspace: &WorkSpace) -> Result<Value,PplError> {
+    truncated_remainder("rem", x, y)
+}
+
+fn round(x: &Value, _workspace: &WorkSpace) -> Result<Value,PplError> {
+    same_datatype(x, f32::round, f64::round)
+}
+
+fn smaller(x: &Value, y: &Value, workspace: &WorkSpace) -> Result<Value,PplError> {
+    Ok(if comparison::gr(x, y, workspace)?.as_bool()? { y.clone() } else { x.clone() })
+}
+
+fn sign(x: &Value, _workspace: &WorkSpace) -> Result<Value,PplError> {
+    let v = x.as_f64()?;
+    Ok(Value::Int(if v > 0.0 { 1 } else if v < 0.0 { -1 } else { 0 }))
+}
+
+fn sin(x: &Value, _workspace: &WorkSpace) -> Result<Value,PplError> {
+    float_function("sin", x, f32::sin, f64::sin)
+}
+
+fn sqrt(x: &Value, _workspace: &WorkSpace) -> Result<Value,PplError> {
+    float_function("sqrt", x, f32::sqrt, f64::sqrt)
+}
+
+fn tan(x: &Value, _workspace: &WorkSpace) -> Result<Value,PplError> {
+    float_function("tan", x, f32::tan, f64::tan)
+}
+
+fn trunc(x: &Value, _workspace: &WorkSpace) -> Result<Value,PplError> {
+    same_datatype(x, f32::trunc, f64::trunc)
+}
+
+
+//  A dbl gives a dbl, and anything else a real.  A finite argument with no finite result, such as the square root
+//  of a negative number or the logarithm of zero, is an error rather than a NaN or an infinity.
+
+fn float_function(name: &str, x: &Value, real_function: fn(f32) -> f32, dbl_function: fn(f64) -> f64) -> Result<Value,PplError> {
+    let (argument, result) = match x {
+        Value::Double(v) => (*v, Value::Double(dbl_function(*v))),
+        _ => {
+            let v = x.as_f32()?;
+            (v as f64, Value::Real(real_function(v)))
+        },
+    };
+    if argument.is_finite() && !result.as_f64()?.is_finite() {
+        return Err(ppl_error!(Arithmetic, "{}({}) has no finite value", name, x));
+    }
+    Ok(result)
+}
+
+//  The remainder of a division rounded toward zero.  Of the int divisions, only the smallest int by -1 overflows,
+//  and its remainder is 0, which is what wrapping_rem gives.
+
+fn truncated_remainder(name: &str, x: &Value, y: &Value) -> Result<Value,PplError> {
+    if y.as_f64()? == 0.0 {
+        return Err(ppl_error!(Arithmetic, "Division by zero in {}", name));
+    }
+    match (x, y) {
+        (Value::Int(a), Value::Int(b)) => Ok(Value::Int(a.wrapping_rem(*b))),
+        (Value::Real(a), Value::Real(b)) => Ok(Value::Real(a % b)),
+        _ => Ok(Value::Double(x.as_f64()? % y.as_f64()?)),
+    }
+}
+
+//  An int is already whole, so it's returned as it is
+
+fn same_datatype(x: &Value, real_function: fn(f32) -> f32, dbl_function: fn(f64) -> f64) -> Result<Value,PplError> {
+    match x {
+        Value::Int(v) => Ok(Value::Int(*v)),
+        Value::Double(v) => Ok(Value::Double(dbl_function(*v))),
+        _ => Ok(Value::Real(real_function(x.as_f32()?))),
+    }
+}
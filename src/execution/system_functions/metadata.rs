@@ -1,11 +1,13 @@
 //  This module holds PPL system functions that manipulate metadata
 
-use std::io::{Write, Read};
+use std::{fs::OpenOptions, io::{Write, Read}, path::{Path, PathBuf}, str::FromStr, time::{SystemTime, UNIX_EPOCH}};
 
-use crate::{workspace::{WorkSpace, GeneralSymbol}, 
+use crate::{workspace::{WorkSpace, GeneralSymbol, io::CollisionPolicy}, 
     symbols::{metadata::{FunctionDescription, FunctionArgumentList, FormalArgument, ArgumentMechanism, Metadata, MetaDataTypeName, FunctionImplementation, FunctionClass}, 
     name::Name, datatype::RootDataType}, 
-    execution::value::{Value, sequence::SequenceInstance}};
+    execution::value::{Value, sequence::SequenceInstance},
+    parser::Parser};
+use crate::error::PplError;
 
 
 
@@ -44,6 +46,48 @@ pub fn init(workspace: &WorkSpace) {
             implementation_class: FunctionImplementation::System(FunctionClass::NullValuedDiadic(binary)),
             help_text: String::from("Associates the operator with a diadic function") });
 
+    workspace.add_system_function(
+        "contents", 
+        FunctionDescription { 
+            name: Name::from_str("contents"), 
+            arguments: FunctionArgumentList::Fixed(vec![
+                FormalArgument { name: Name::from_str("filename"), mechanism: ArgumentMechanism::ByValue,  datatype: MetaDataTypeName::from_str("string") }]), 
+            local_variables: None,
+            return_value: Some(MetaDataTypeName::from_str("string")), 
+            implementation_class: FunctionImplementation::System(FunctionClass::Monadic(contents)),
+            help_text: String::from("Lists the datatypes, functions, operators and variables in a workspace file, without loading it") });
+
+    workspace.add_system_function(
+        "copy", 
+        FunctionDescription { 
+            name: Name::from_str("copy"), 
+            arguments: FunctionArgumentList::Varying(ArgumentMechanism::ByValue), 
+            local_variables: None,
+            return_value: Some(MetaDataTypeName::from_str("string")), 
+            implementation_class: FunctionImplementation::System(FunctionClass::Varying(copy)),
+            help_text: String::from(r#"copy(filename, names...) copies the named items (or all of them) from a workspace file, following copy.policy when a name is already in use"#) });
+
+    workspace.add_system_function(
+        "copy.policy", 
+        FunctionDescription { 
+            name: Name::from_str("copy.policy"), 
+            arguments: FunctionArgumentList::Fixed(vec![]), 
+            local_variables: None,
+            return_value: Some(MetaDataTypeName::from_str("string")), 
+            implementation_class: FunctionImplementation::System(FunctionClass::Nullary(show_copy_policy)),
+            help_text: String::from(r#"Display what copy does with names that are already in use"#) });
+
+    workspace.add_system_function(
+        "copy.policy", 
+        FunctionDescription { 
+            name: Name::from_str("copy.policy"), 
+            arguments: FunctionArgumentList::Fixed(vec![
+                FormalArgument { name: Name::from_str("policy"), mechanism: ArgumentMechanism::ByValue,  datatype: MetaDataTypeName::from_str("string") }]), 
+            local_variables: None,
+            return_value: None, 
+            implementation_class: FunctionImplementation::System(FunctionClass::NullValuedMonadic(copy_policy)),
+            help_text: String::from(r#"{"FAIL" | "OVERWRITE" | "RENAME"} what copy does with names that are already in use"#) });
+
     workspace.add_system_function(
         "display", 
         FunctionDescription { 
@@ -63,7 +107,28 @@ pub fn init(workspace: &WorkSpace) {
             local_variables: None,
             return_value: Some(MetaDataTypeName::from_str("general")), 
             implementation_class: FunctionImplementation::System(FunctionClass::Varying(edit)),
-            help_text: String::from("Edit a definition") });
+            help_text: String::from("Edit a definition with the editor named by edit.editor, VISUAL or EDITOR") });
+
+    workspace.add_system_function(
+        "edit.editor", 
+        FunctionDescription { 
+            name: Name::from_str("edit.editor"), 
+            arguments: FunctionArgumentList::Fixed(vec![]), 
+            local_variables: None,
+            return_value: Some(MetaDataTypeName::from_str("string")), 
+            implementation_class: FunctionImplementation::System(FunctionClass::Nullary(show_editor)),
+            help_text: String::from("Display the command edit runs") });
+
+    workspace.add_system_function(
+        "edit.editor", 
+        FunctionDescription { 
+            name: Name::from_str("edit.editor"), 
+            arguments: FunctionArgumentList::Fixed(vec![
+                FormalArgument { name: Name::from_str("command"), mechanism: ArgumentMechanism::ByValue,  datatype: MetaDataTypeName::from_str("string") }]), 
+            local_variables: None,
+            return_value: None, 
+            implementation_class: FunctionImplementation::System(FunctionClass::NullValuedMonadic(set_editor)),
+            help_text: String::from(r#"The command edit runs, in place of VISUAL or EDITOR; "" goes back to those"#) });
 
     workspace.add_system_function(
         "erase", 
@@ -218,7 +283,7 @@ pub fn init(workspace: &WorkSpace) {
 
 
 
-fn binary(operator: &Value, function: &Value, workspace: &WorkSpace) -> Result<(),String> {
+fn binary(operator: &Value, function: &Value, workspace: &WorkSpace) -> Result<(),PplError> {
     if let Some(f) = workspace.try_get_function(function.as_string().as_str()) {
         if let FunctionArgumentList::Fixed(ref args) = f.arguments {
             if args.len() == 2 {
@@ -226,24 +291,63 @@ fn binary(operator: &Value, function: &Value, workspace: &WorkSpace) -> Result<(
                 return Ok(());
             }
         }
-        return Err(format!("{} is not a binary function", function));
+        return Err(ppl_error!(Name, "{} is not a binary function", function));
     }
-    Err(format!("{} not found", function))
+    Err(ppl_error!(Name, "{} not found", function))
 }
 
-fn display(function: &Value, workspace: &WorkSpace) -> Result<Value,String> {
+fn contents(filename: &Value, _workspace: &WorkSpace) -> Result<Value,PplError> {
+    let manifest = crate::workspace::io::contents(&filename.as_string())?;
+    Ok(SequenceInstance::construct_string_sequence(manifest.to_string().trim_end()))
+}
+
+fn copy(args: &Vec<Value>, workspace: &WorkSpace) -> Result<Value,PplError> {
+    if args.is_empty() {
+        return Err(ppl_error!(Argument, "Copy requires a file name"));
+    }
+    let names: Vec<String> = args[1..].iter().map(|name| name.as_string()).collect();
+    let copied = crate::workspace::io::copy(&args[0].as_string(), &names, workspace)?;
+    Ok(SequenceInstance::construct_string_sequence(&copied.join(", ")))
+}
+
+fn copy_policy(policy: &Value, workspace: &WorkSpace) -> Result<(),PplError> {
+    let policy = CollisionPolicy::from_str(&policy.as_string()).map_err(|_| format!("{} is not a valid copy policy", policy.as_string()))?;
+    *workspace.copy_policy.borrow_mut() = policy;
+    Ok(())
+}
+
+fn create_edit_file(text: &str) -> Result<PathBuf,PplError> {
+    //  Build a name that's unique to this process and moment, and insist that the file be newly
+    //  created so we never scribble on somebody else's temporary file
+
+    let nanos = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.subsec_nanos()).unwrap_or(0);
+    let mut attempt = 0;
+    loop {
+        let path = std::env::temp_dir().join(format!("ppl-edit-{}-{}-{}.ppl", std::process::id(), nanos, attempt));
+        match OpenOptions::new().write(true).create_new(true).open(&path) {
+            Ok(mut f) => {
+                f.write_all(text.as_bytes())?;
+                return Ok(path);
+            },
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists && attempt < 100 => attempt += 1,
+            Err(e) => return Err(e.into()),
+        }
+    }
+}
+
+fn display(function: &Value, workspace: &WorkSpace) -> Result<Value,PplError> {
     match workspace.try_get_any(function.as_string().as_str()) {
-        GeneralSymbol::Unresolved(_) => Err(format!("{} not found", function.as_string())),
+        GeneralSymbol::Unresolved(_) => Err(ppl_error!(Name, "{} not found", function.as_string())),
         desc => Ok(SequenceInstance::construct_string_sequence(&format!("{}", desc.as_definition()))),
     }
 }
 
-fn dump_symbol_table(workspace: &WorkSpace) -> Result<(),String> {
+fn dump_symbol_table(workspace: &WorkSpace) -> Result<(),PplError> {
     workspace.dump_symbol_table();
     Ok(())
 }
 
-fn edit(args: &Vec<Value>, workspace: &WorkSpace) -> Result<Value,String> {
+fn edit(args: &Vec<Value>, workspace: &WorkSpace) -> Result<Value,PplError> {
     let text:String;
     match args.len() {
         0 => text = String::new(),
@@ -253,39 +357,67 @@ fn edit(args: &Vec<Value>, workspace: &WorkSpace) -> Result<Value,String> {
                 GeneralSymbol::Datatype(dt) => text = dt.as_definition(),
                 GeneralSymbol::Function(func) => text = func.as_source(),
                 GeneralSymbol::Unresolved(_) => text = args[0].as_string(),
-                _ => return Err(format!("{} cannot be editted", args[0])),
+                _ => return Err(ppl_error!(Argument, "{} cannot be editted", args[0])),
             }
         },
-        _ => return Err(format!("EDIT takes at most one argument")),
+        _ => return Err(ppl_error!(Argument, "EDIT takes at most one argument")),
     }
 
-    let temp_directory = std::env::var("TEMP");
-    let filename:String;
-    match temp_directory {
-        Ok(dir) => filename = dir + "\\function.tmp",
-        Err(_) => filename = String::from("\\function.tmp"),
+    let editted_text = edit_text(&text, workspace)?;
+    if editted_text.trim().is_empty() {
+        return Err(ppl_error!(Runtime, "Edit cancelled"));
     }
-    std::fs::File::create(filename.as_str())
-        .map_err(|e| e.to_string())?
-        .write_all(&text.as_bytes())
-        .map_err(|e| e.to_string())?;
-    std::process::Command::new("notepad")
-        .arg(filename.as_str())
-        .output()
-        .map_err(|e| e.to_string())?;
-    let mut editted_text = String::new();
-    std::fs::File::open(filename.as_str())
-        .map_err(|e| e.to_string())?
-        .read_to_string(&mut editted_text)
-        .map_err(|e| e.to_string())?;
+    if editted_text.trim_end() == text.trim_end() {
+        return Ok(SequenceInstance::construct_string_sequence("No changes"));
+    }
+
+    //  Make sure the new text parses before we throw away the old definition
+
+    Parser::new(editted_text.as_str(), workspace).parse(editted_text.as_str())?;
 
-    if args.len() == 1 {
+    if args.len() == 1 && !matches!(workspace.try_get_any(&args[0].as_string()), GeneralSymbol::Unresolved(_)) {
         erase(&vec![args[0].clone()], workspace)?;
+        return match super::exec(&SequenceInstance::construct_string_sequence(editted_text.as_str()), workspace) {
+            Ok(result) => Ok(result),
+            Err(e) => {
+                //  Put the original definition back, so a failed edit doesn't lose it
+
+                workspace.remove(&args[0].as_string());
+                super::exec(&SequenceInstance::construct_string_sequence(text.as_str()), workspace)?;
+                Err(e)
+            },
+        };
     }
     super::exec(&SequenceInstance::construct_string_sequence(editted_text.as_str()), workspace)
 }
 
-fn erase(args: &Vec<Value>, workspace: &WorkSpace) -> Result<(),String> {
+fn edit_text(text: &str, workspace: &WorkSpace) -> Result<String,PplError> {
+    let path = create_edit_file(text)?;
+    let result = run_editor(&path, workspace);
+    let _ = std::fs::remove_file(&path);
+    result
+}
+
+fn editor_command(workspace: &WorkSpace) -> Vec<String> {
+    if let Some(command) = workspace.editor.borrow().as_ref() {
+        return command.split_whitespace().map(String::from).collect();
+    }
+    for variable in ["VISUAL", "EDITOR"] {
+        if let Ok(command) = std::env::var(variable) {
+            let words: Vec<String> = command.split_whitespace().map(String::from).collect();
+            if !words.is_empty() {
+                return words;
+            }
+        }
+    }
+    if cfg!(windows) {
+        vec![String::from("notepad")]
+    } else {
+        vec![String::from("vi")]
+    }
+}
+
+fn erase(args: &Vec<Value>, workspace: &WorkSpace) -> Result<(),PplError> {
     if args.len() == 0 {
         workspace.remove_all();
     } else {
@@ -296,69 +428,100 @@ fn erase(args: &Vec<Value>, workspace: &WorkSpace) -> Result<(),String> {
     Ok(())
 }
 
-fn false_value(_workspace: &WorkSpace) -> Result<Value, String> {
+fn false_value(_workspace: &WorkSpace) -> Result<Value,PplError> {
     Ok(Value::Bool(false))
 }
 
-fn get_type(operand: &Value, _workspace: &WorkSpace) -> Result<Value,String> {
+fn get_type(operand: &Value, _workspace: &WorkSpace) -> Result<Value,PplError> {
     Ok(SequenceInstance::construct_string_sequence(&operand.as_datatype()?))
 }
 
-fn help(args: &Vec<Value>, workspace: &WorkSpace) -> Result<Value,String> {
+fn help(args: &Vec<Value>, workspace: &WorkSpace) -> Result<Value,PplError> {
     match args.len() {
         0 => workspace.help_all(),
         _ => workspace.help_one(&args[0].as_string()),
     }
 }
 
-fn length(operand: &Value, _workspace: &WorkSpace) -> Result<Value,String> {
+fn length(operand: &Value, _workspace: &WorkSpace) -> Result<Value,PplError> {
     match operand {
         Value::Empty => Ok(Value::Int(0)),
         Value::Structure(s) => Ok(Value::Int(s.length())),
         Value::Sequence(s) => Ok(Value::Int(s.length())),
-        Value::Selector(_) => Err(format!("Length is undefined for selectors")),
+        Value::Selector(_) => Err(ppl_error!(Runtime, "Length is undefined for selectors")),
         _ => Ok(Value::Int(1)),
     }
 }
 
-fn lower_bound(operand: &Value, _workspace: &WorkSpace) -> Result<Value,String> {
+fn lower_bound(operand: &Value, _workspace: &WorkSpace) -> Result<Value,PplError> {
     match operand {
         Value::Sequence(s) => Ok(Value::Int(s.lower_bound())),
         _ => Ok(Value::Int(1)),
     }
 }
 
-fn make(sequence_type: &Value, count: &Value, value: &Value, workspace: &WorkSpace) -> Result<Value,String> {
+fn make(sequence_type: &Value, count: &Value, value: &Value, workspace: &WorkSpace) -> Result<Value,PplError> {
     match workspace.try_get_datatype(&sequence_type.as_string()) {
         Some(datatype) => {
             if let RootDataType::Sequence(ref seq) = datatype.root_data_type() {
                 SequenceInstance::make(seq, count.as_i32()?, value, workspace)
             } else {
-                Err(format!("{} is not a sequence", sequence_type))
+                Err(ppl_error!(Name, "{} is not a sequence", sequence_type))
             }
         },
-        None => Err(format!("{} not found", sequence_type)),
+        None => Err(ppl_error!(Name, "{} not found", sequence_type)),
     }
 }
 
-fn read(filename: &Value, workspace: &WorkSpace) -> Result<(),String> {
+fn read(filename: &Value, workspace: &WorkSpace) -> Result<(),PplError> {
     crate::workspace::io::read(&filename.as_string(), workspace)
 }
 
-fn relocate(line: &Value, _workspace: &WorkSpace) -> Result<Value,String> {
+fn relocate(line: &Value, _workspace: &WorkSpace) -> Result<Value,PplError> {
     Ok(line.clone())
 }
 
-fn reset(workspace: &WorkSpace) -> Result<(), String> {
+fn reset(workspace: &WorkSpace) -> Result<(),PplError> {
     workspace.reset_function_state();
     Ok(())
 }
 
-fn true_value(_workspace: &WorkSpace) -> Result<Value, String> {
+fn run_editor(path: &Path, workspace: &WorkSpace) -> Result<String,PplError> {
+    let command = editor_command(workspace);
+    let status = std::process::Command::new(&command[0])
+        .args(&command[1..])
+        .arg(path)
+        .status()
+        .map_err(|e| ppl_error!(Io, "Unable to start editor {}: {}", command[0], e))?;
+    if !status.success() {
+        return Err(ppl_error!(Runtime, "Edit cancelled, {} exited with {}", command[0], status));
+    }
+
+    let mut editted_text = String::new();
+    std::fs::File::open(path)?.read_to_string(&mut editted_text)?;
+
+    Ok(editted_text)
+}
+
+fn set_editor(command: &Value, workspace: &WorkSpace) -> Result<(),PplError> {
+    let command = command.as_string();
+    *workspace.editor.borrow_mut() = if command.trim().is_empty() { None } else { Some(command) };
+    Ok(())
+}
+
+fn show_copy_policy(workspace: &WorkSpace) -> Result<Value,PplError> {
+    Ok(SequenceInstance::construct_string_sequence(&workspace.copy_policy.borrow().to_string()))
+}
+
+fn show_editor(workspace: &WorkSpace) -> Result<Value,PplError> {
+    Ok(SequenceInstance::construct_string_sequence(&editor_command(workspace).join(" ")))
+}
+
+fn true_value(_workspace: &WorkSpace) -> Result<Value,PplError> {
     Ok(Value::Bool(true))
 }
 
-fn unary(operator: &Value, function: &Value, workspace: &WorkSpace) -> Result<(),String> {
+fn unary(operator: &Value, function: &Value, workspace: &WorkSpace) -> Result<(),PplError> {
     let symbol_list = workspace.try_get_functions(function.as_string().as_str());
     for symbol in symbol_list {
         match symbol {
@@ -369,19 +532,19 @@ fn unary(operator: &Value, function: &Value, workspace: &WorkSpace) -> Result<()
                         return Ok(());
                     }
                 }
-                return Err(format!("{} is not a unary function", f));
+                return Err(ppl_error!(Name, "{} is not a unary function", f));
             },
-            _ => return Err(format!("{} is not a unary function", function)),
+            _ => return Err(ppl_error!(Name, "{} is not a unary function", function)),
         }
     }
-    Err(format!("{} not found", function))
+    Err(ppl_error!(Name, "{} not found", function))
 }
 
-fn version(_workspace: &WorkSpace) -> Result<Value, String> {
+fn version(_workspace: &WorkSpace) -> Result<Value,PplError> {
     Ok(SequenceInstance::construct_string_sequence(&String::from("PPL T0.0")))
 }
 
-fn write(filename: &Value, workspace: &WorkSpace) -> Result<(),String> {
+fn write(filename: &Value, workspace: &WorkSpace) -> Result<(),PplError> {
     crate::workspace::io::write(&filename.as_string(), workspace)
 }
 
@@ -0,0 +1,187 @@
+//  This module holds the PPL system functions for testing PPL: assertions, and a runner for the functions named
+//  test.* in a workspace
+
+use std::rc::Rc;
+
+use crate::{workspace::WorkSpace, symbols::{metadata::{FunctionDescription, FormalArgument, ArgumentMechanism, MetaDataTypeName, FunctionImplementation, FunctionClass, FunctionArgumentList}, name::Name}, execution::value::{Value, sequence::SequenceInstance}};
+use crate::{error::PplError, parser::Parser, sequencer::start_execution, utility::as_text};
+
+use super::comparison;
+
+
+pub fn init(workspace: &WorkSpace) {
+    workspace.add_system_function(
+        "assert", 
+        FunctionDescription { 
+            name: Name::from_str("assert"), 
+            arguments: FunctionArgumentList::Fixed(vec![
+                FormalArgument { name: Name::from_str("condition"), mechanism: ArgumentMechanism::ByValue,  datatype: MetaDataTypeName::from_str("bool") }]), 
+            local_variables: None,
+            return_value: None, 
+            implementation_class: FunctionImplementation::System(FunctionClass::NullValuedMonadic(assert)),
+            help_text: String::from("Fails with an assertion error if the condition is false") });
+
+    workspace.add_system_function(
+        "assert", 
+        FunctionDescription { 
+            name: Name::from_str("assert"), 
+            arguments: FunctionArgumentList::Fixed(vec![
+                FormalArgument { name: Name::from_str("condition"), mechanism: ArgumentMechanism::ByValue,  datatype: MetaDataTypeName::from_str("bool") },
+                FormalArgument { name: Name::from_str("message"), mechanism: ArgumentMechanism::ByValue,  datatype: MetaDataTypeName::from_str("string") }]), 
+            local_variables: None,
+            return_value: None, 
+            implementation_class: FunctionImplementation::System(FunctionClass::NullValuedDiadic(assert_with_message)),
+            help_text: String::from("Fails with an assertion error, reporting the message, if the condition is false") });
+
+    workspace.add_system_function(
+        "assert.equal", 
+        FunctionDescription { 
+            name: Name::from_str("assert.equal"), 
+            arguments: FunctionArgumentList::Fixed(vec![
+                FormalArgument { name: Name::from_str("actual"), mechanism: ArgumentMechanism::ByValue,  datatype: MetaDataTypeName::from_str("general") },
+                FormalArgument { name: Name::from_str("expected"), mechanism: ArgumentMechanism::ByValue,  datatype: MetaDataTypeName::from_str("general") }]), 
+            local_variables: None,
+            return_value: None, 
+            implementation_class: FunctionImplementation::System(FunctionClass::NullValuedDiadic(assert_equal)),
+            help_text: String::from("Fails with an assertion error, showing both values, unless the actual value = the expected value") });
+
+    workspace.add_system_function(
+        "expect.error", 
+        FunctionDescription { 
+            name: Name::from_str("expect.error"), 
+            arguments: FunctionArgumentList::Fixed(vec![
+                FormalArgument { name: Name::from_str("expression"), mechanism: ArgumentMechanism::ByValue,  datatype: MetaDataTypeName::from_str("string") }]), 
+            local_variables: None,
+            return_value: Some(MetaDataTypeName::from_str("string")), 
+            implementation_class: FunctionImplementation::System(FunctionClass::Monadic(expect_error)),
+            help_text: String::from(r#"expect.error("expression") evaluates the expression, returning its error message, and fails with an assertion error if there isn't one. An interrupt, or going over a limit set by limit.depth, limit.nodes or limit.stack, isn't caught"#) });
+
+    workspace.add_system_function(
+        "test.run", 
+        FunctionDescription { 
+            name: Name::from_str("test.run"), 
+            arguments: FunctionArgumentList::Fixed(vec![]),
+            local_variables: None,
+            return_value: Some(MetaDataTypeName::from_str("string")), 
+            implementation_class: FunctionImplementation::System(FunctionClass::Nullary(run_workspace_tests)),
+            help_text: String::from("Runs every function named test.* that has no arguments, reporting which pass and which fail, and why") });
+
+    workspace.add_system_function(
+        "test.run", 
+        FunctionDescription { 
+            name: Name::from_str("test.run"), 
+            arguments: FunctionArgumentList::Fixed(vec![
+                FormalArgument { name: Name::from_str("filename"), mechanism: ArgumentMechanism::ByValue,  datatype: MetaDataTypeName::from_str("string") }]), 
+            local_variables: None,
+            return_value: Some(MetaDataTypeName::from_str("string")), 
+            implementation_class: FunctionImplementation::System(FunctionClass::Monadic(run_file_tests)),
+            help_text: String::from("Reads a saved workspace into a workspace of its own, and runs its test.* functions there") });
+}
+
+
+fn assert(condition: &Value, workspace: &WorkSpace) -> Result<(),PplError> {
+    assert_with_message(condition, &SequenceInstance::construct_string_sequence("Assertion failed"), workspace)
+}
+
+fn assert_with_message(condition: &Value, message: &Value, _workspace: &WorkSpace) -> Result<(),PplError> {
+    match condition.as_bool()? {
+        true => Ok(()),
+        false => Err(ppl_error!(Assertion, "{}", message.as_string())),
+    }
+}
+
+fn assert_equal(actual: &Value, expected: &Value, workspace: &WorkSpace) -> Result<(),PplError> {
+    match comparison::eq(actual, expected, workspace)?.as_bool()? {
+        true => Ok(()),
+        false => Err(ppl_error!(Assertion, "Expected {}, got {}", as_text(expected), as_text(actual))),
+    }
+}
+
+//  Running out of something isn't the error a test expects, and catching it would only let the evaluation
+//  carry on running out
+
+fn expect_error(expression: &Value, workspace: &WorkSpace) -> Result<Value,PplError> {
+    let source = expression.as_string();
+    match evaluate_nested(source.as_str(), workspace) {
+        Ok(value) => Err(ppl_error!(Assertion, "Expected an error from {}, got {}", source, as_text(&value))),
+        Err(e @ (PplError::Interrupt(_) | PplError::Limit(_))) => Err(e),
+        Err(e) => Ok(SequenceInstance::construct_string_sequence(e.message())),
+    }
+}
+
+fn run_workspace_tests(workspace: &WorkSpace) -> Result<Value,PplError> {
+    run_tests(workspace)
+}
+
+fn run_file_tests(filename: &Value, _workspace: &WorkSpace) -> Result<Value,PplError> {
+    let scratch = WorkSpace::new();
+    crate::workspace::io::read(&filename.as_string(), &scratch)?;
+    run_tests(&scratch)
+}
+
+//  Each test runs on its own: whatever its failure leaves on the stacks is cleared away, and the workspace's
+//  variables are put back as they were, so one test can't make another pass or fail.  The report is the value
+//  if every test passes, and the message of an assertion error if any fails.
+
+fn run_tests(workspace: &WorkSpace) -> Result<Value,PplError> {
+    let tests: Vec<Rc<FunctionDescription>> = workspace.get_user_functions().into_iter()
+        .filter(|f| f.name.as_str().to_ascii_lowercase().starts_with("test."))
+        .filter(|f| matches!(&f.arguments, FunctionArgumentList::Fixed(arguments) if arguments.is_empty()))
+        .collect();
+
+    let mut report = String::new();
+    let mut failures = 0;
+    for test in &tests {
+        let variables: Vec<(String, Value)> = workspace.get_global_variables().into_iter()
+            .map(|(name, v)| (name, v.cell.borrow().as_ref_to_value().clone()))
+            .collect();
+
+        let result = evaluate_nested(test.name.as_str(), workspace);
+
+        for (name, _) in workspace.get_global_variables() {
+            if !variables.iter().any(|(original, _)| *original == name) {
+                workspace.remove_global_variable(&name);
+            }
+        }
+        for (name, value) in &variables {
+            if let Some(v) = workspace.try_get_variable(name) {
+                v.cell.borrow().set_value(value)?;
+            }
+        }
+
+        match result {
+            Ok(_) => report += format!("PASS {}\n", test.name).as_str(),
+            Err(e @ PplError::Interrupt(_)) => return Err(e),
+            Err(e) => {
+                failures += 1;
+                report += format!("FAIL {}\n    {}\n", test.name, e.message()).as_str();
+
+                //  The traceback goes only as far out as the test itself
+
+                let frames = e.traceback();
+                let test_frame = frames.iter().rposition(|frame| frame.function_name.as_deref() == Some(test.name.as_str()));
+                for frame in &frames[..test_frame.map_or(frames.len(), |index| index + 1)] {
+                    report += format!("    {}\n", frame).as_str();
+                }
+            },
+        }
+    }
+    report += format!("{} tests, {} passed, {} failed", tests.len(), tests.len() - failures, failures).as_str();
+
+    match failures {
+        0 => Ok(SequenceInstance::construct_string_sequence(report.as_str())),
+        _ => Err(ppl_error!(Assertion, "{}", report)),
+    }
+}
+
+//  Evaluates PPL from within a running statement, leaving the stacks as they were if it fails
+
+fn evaluate_nested(source: &str, workspace: &WorkSpace) -> Result<Value,PplError> {
+    let depth = workspace.get_invocation_depth();
+    let result = Parser::new(source, workspace).parse(source)
+        .and_then(|executable| start_execution(&executable, workspace));
+    if result.is_err() {
+        workspace.unwind_invocations(depth);
+    }
+    Ok(result?.unwrap_or(Value::Empty))
+}
@@ -2,7 +2,10 @@
 
 use std::fmt;
 use std::cell::{Ref, RefCell};
-use std::rc::Rc;
+use std::rc::{Rc, Weak};
+
+#[cfg(feature = "bignum")]
+use num_bigint::BigInt;
 
 use crate::parser::tree::ReferenceNode;
 use crate::symbols::help::Help;
@@ -10,13 +13,19 @@ use crate::symbols::metadata::MetaDataTypeName;
 use crate::workspace::GeneralSymbol;
 use crate::{workspace::WorkSpace, symbols::{metadata::MetaDataType, name::Name, datatype::RootDataType}};
 
+use self::decimal::Decimal;
+use self::rational::Rational;
 use self::recursion_detector::Cycle;
 use self::sequence::SequenceInstance;
 use self::structure::{StructureInstance, SelectorInstance};
+use crate::error::PplError;
 
+pub mod complex;
 pub mod conversion;
 pub mod debug;
+pub mod decimal;
 pub mod format;
+pub mod rational;
 pub mod recursion_detector;
 pub mod sequence;
 pub mod structure;
@@ -47,6 +56,11 @@ pub enum Value {
     Empty,
     Bool(bool),
     Int(i32),
+    Int64(i64),
+    #[cfg(feature = "bignum")]
+    Bignum(BigInt),
+    Rational(Rational),
+    Decimal(Decimal),
     Real(f32),
     Double(f64),
     Char(char),
@@ -73,11 +87,16 @@ impl Value {
     //     }
     // }
 
-    pub fn as_datatype(&self) -> Result<String,String> {
+    pub fn as_datatype(&self) -> Result<String,PplError> {
         match self {
             Value::Empty => Ok(String::from("Null")),
             Value::Bool(_) => Ok(String::from("Bool")),
             Value::Int(_) => Ok(String::from("Int")),
+            Value::Int64(_) => Ok(String::from("Int64")),
+            #[cfg(feature = "bignum")]
+            Value::Bignum(_) => Ok(String::from("Bignum")),
+            Value::Rational(_) => Ok(String::from("Rational")),
+            Value::Decimal(_) => Ok(String::from("Decimal")),
             Value::Real(_) => Ok(String::from("Real")),
             Value::Double(_) => Ok(String::from("Dbl")),
             Value::Char(_) => Ok(String::from("Char")),
@@ -86,11 +105,11 @@ impl Value {
             Value::Symbol(s) => 
                 match s.as_symbol() {
                     GeneralSymbol::Variable(v) => v.cell.borrow().as_ref_to_value().as_datatype(),
-                    _ => Err(format!("Not a user datatype")),
+                    _ => Err(ppl_error!(Type, "Not a user datatype")),
                 },
             Value::ValueByReference(c) => c.cell.borrow().as_ref_to_value().as_datatype(),
             Value::LogicalLink(l) => l.as_ref_to_value().as_datatype(),
-            _ => Err(format!("Not a user datatype")),
+            _ => Err(ppl_error!(Type, "Not a user datatype")),
         }
     }
 
@@ -104,7 +123,7 @@ impl Value {
     //     }
     // }
 
-    pub fn from_reference(r: &ReferenceNode, workspace: &WorkSpace) -> Result<Value,String> {
+    pub fn from_reference(r: &ReferenceNode, workspace: &WorkSpace) -> Result<Value,PplError> {
         Ok(Value::Symbol( SymbolicReference { referred_name: r.as_name(), resolution: workspace.try_get_any(r.as_string().as_str()) }))
     }
 
@@ -138,6 +157,11 @@ impl fmt::Display for Value {
             },
             Value::Empty => Ok(()),
             Value::Int(v) => write!(fmt, "{}", v),
+            Value::Int64(v) => write!(fmt, "{}", v),
+            #[cfg(feature = "bignum")]
+            Value::Bignum(v) => write!(fmt, "{}", v),
+            Value::Rational(v) => write!(fmt, "{}", v),
+            Value::Decimal(v) => write!(fmt, "{}", v),
             Value::Symbol(v) => write!(fmt, "{}", v),
             Value::Sequence(v) => {
                 if  v.as_recursion_pass().has_not_been_processed() {
@@ -187,7 +211,7 @@ impl Help for Value {
         self.as_string()
     }
 
-    fn show_help(&self, name: &str, workspace: &WorkSpace) -> Result<Value,String> {
+    fn show_help(&self, name: &str, workspace: &WorkSpace) -> Result<Value,PplError> {
         RootDataType::from_value(self, workspace).unwrap().show_help(name, workspace)
     }
 }
@@ -196,6 +220,7 @@ impl Help for Value {
 pub struct ValueEnvelope {
     recursion_pass: Cycle,
     value: RefCell<Value>,
+    watchers: RefCell<Vec<Weak<std::cell::Cell<u64>>>>,
 }
 
 impl Help for ValueEnvelope {
@@ -207,7 +232,7 @@ impl Help for ValueEnvelope {
         self.as_ref_to_value().as_string()    
     }
 
-    fn show_help(&self, name: &str, workspace: &WorkSpace) -> Result<Value,String> {
+    fn show_help(&self, name: &str, workspace: &WorkSpace) -> Result<Value,PplError> {
         self.value.borrow().show_help(name, workspace)
     }
 }
@@ -228,11 +253,31 @@ impl ValueEnvelope {
     }
 
     pub fn new (value: Value) -> Rc<ValueEnvelope> {
-        Rc::new(ValueEnvelope { recursion_pass: Cycle::new(), value: RefCell::new(value) })
+        Rc::new(ValueEnvelope { recursion_pass: Cycle::new(), value: RefCell::new(value), watchers: RefCell::new(Vec::new()) })
     }
 
     pub fn set_value(&self, value: &Value) {
         *self.value.borrow_mut() = value.clone();
+
+        //  Each write counts, even one that leaves the value as it was
+
+        self.watchers.borrow_mut().retain(|watcher| match watcher.upgrade() {
+            Some(writes) => {
+                writes.set(writes.get() + 1);
+                true
+            },
+            None => false,
+        });
+    }
+
+    //  A watchpoint hears about writes through a counter it hands to each cell it covers
+
+    pub fn watch(&self, writes: &Rc<std::cell::Cell<u64>>) {
+        let mut watchers = self.watchers.borrow_mut();
+        watchers.retain(|watcher| watcher.strong_count() > 0);
+        if !watchers.iter().any(|watcher| std::ptr::eq(watcher.as_ptr(), Rc::as_ptr(writes))) {
+            watchers.push(Rc::downgrade(writes));
+        }
     }
 }
 
@@ -250,7 +295,7 @@ impl Help for Cell {
         self.as_ref_to_value().as_string()    
     }
 
-    fn show_help(&self, name: &str, workspace: &WorkSpace) -> Result<Value,String> {
+    fn show_help(&self, name: &str, workspace: &WorkSpace) -> Result<Value,PplError> {
         self.contents.show_help(name, workspace)
     }
 }
@@ -280,7 +325,7 @@ impl Cell {
     }
 
     pub fn new_by_reference(v: Rc<ValueEnvelope>) -> Rc<RefCell<Cell>> {
-        Rc::new(RefCell::new(Cell { contents: Rc::new(ValueEnvelope { recursion_pass: Cycle::new(), value: RefCell::new(Value::LogicalLink(v.clone())) })}))
+        Rc::new(RefCell::new(Cell { contents: ValueEnvelope::new(Value::LogicalLink(v.clone())) }))
     }
 
     pub fn as_ref_to_value(&self) -> Ref<'_,Value> {
@@ -291,17 +336,17 @@ impl Cell {
         self.set_value(&Value::LogicalLink(r.clone())).expect("set_value failed");
     }
 
-    pub fn set_value(&self, value: &Value) -> Result<(),String> {
+    pub fn set_value(&self, value: &Value) -> Result<(),PplError> {
         self.contents.set_value(Cell::validate_value(value)?);
         Ok(())
     }
 
-    pub fn validate_value(value: &Value) -> Result<&Value,String> {
+    pub fn validate_value(value: &Value) -> Result<&Value,PplError> {
         match value {
-            Value::Selector(sel) => Err(format!("{} is not a value", sel.member)),
+            Value::Selector(sel) => Err(ppl_error!(Name, "{} is not a value", sel.member)),
             Value::Symbol(symbol) => {
                 match symbol.as_symbol() {
-                    GeneralSymbol::Unresolved(_) => Err(format!("{} not found", symbol.as_string())),
+                    GeneralSymbol::Unresolved(_) => Err(ppl_error!(Name, "{} not found", symbol.as_string())),
                     _ => panic!("internal error"),
                 }
             },
@@ -345,26 +390,54 @@ impl fmt::Display for SymbolicReference {
 
 
 
-pub fn construct(datatype: &Rc<MetaDataType>, args: &Vec<Value>, workspace: &WorkSpace) -> Result<Value,String> {
+pub fn construct(datatype: &Rc<MetaDataType>, args: &Vec<Value>, workspace: &WorkSpace) -> Result<Value,PplError> {
     match &datatype.root_data_type() {
-        RootDataType::Int | RootDataType::Real | RootDataType::Dbl | RootDataType::Bool | RootDataType::Char => construct_atomic(datatype, args),
+        RootDataType::Int | RootDataType::Int64 | RootDataType::Real | RootDataType::Dbl | RootDataType::Bool | RootDataType::Char => construct_atomic(datatype, args),
+        #[cfg(feature = "bignum")]
+        RootDataType::Bignum => construct_atomic(datatype, args),
+        RootDataType::Rational | RootDataType::Decimal => construct_exact(datatype, args),
         RootDataType::Structure(_) => StructureInstance::construct(datatype, args, workspace),
         RootDataType::Sequence(_) => SequenceInstance::construct(datatype, args, workspace),
-        _ => Err(format!("{} is not a constructor", datatype.as_string())),
+        _ => Err(ppl_error!(Type, "{} is not a constructor", datatype.as_string())),
     }
 }
 
-fn construct_atomic(datatype: &Rc<MetaDataType>, args: &Vec<Value>) -> Result<Value,String> {
+fn construct_atomic(datatype: &Rc<MetaDataType>, args: &Vec<Value>) -> Result<Value,PplError> {
     if args.len() != 1 {
-        Err(format!("{}() takes 1 argument", datatype.as_string()))
+        Err(ppl_error!(Argument, "{}() takes 1 argument", datatype.as_string()))
     } else {
         match datatype.root_data_type() {
             RootDataType::Int => Ok(Value::Int(args[0].as_i32()?)),
+            RootDataType::Int64 => Ok(Value::Int64(args[0].as_i64()?)),
+            #[cfg(feature = "bignum")]
+            RootDataType::Bignum => Ok(Value::Bignum(args[0].as_bignum()?)),
             RootDataType::Real =>  Ok(Value::Real(args[0].as_f32()?)),
             RootDataType::Dbl =>  Ok(Value::Double(args[0].as_f64()?)),
             RootDataType::Bool => Ok(Value::Bool(args[0].as_bool()?)),
-            RootDataType::Char => Ok(Value::Char(args[0].as_char()?)),
+            RootDataType::Char => Ok(Value::Char(char_from_code_or_value(&args[0])?)),
             _ => panic!("internal error"),
         }
     }
 }
+
+//  char(n) is the character with code n, which is how a saved workspace writes control characters.  Only the
+//  constructor takes a code; an int passed where a char is expected is still an error.
+
+fn char_from_code_or_value(v: &Value) -> Result<char,PplError> {
+    match v.dereference() {
+        Value::Int(code) => u32::try_from(code).ok().and_then(char::from_u32).ok_or_else(|| ppl_error!(Type, "{} is not a valid CHAR", code)),
+        v => v.as_char(),
+    }
+}
+
+//  rational(n, d) is the fraction n/d, and decimal(x, scale) is x rounded to scale decimal places
+
+fn construct_exact(datatype: &Rc<MetaDataType>, args: &[Value]) -> Result<Value,PplError> {
+    match (datatype.root_data_type(), args.len()) {
+        (RootDataType::Rational, 1) => Ok(Value::Rational(args[0].as_rational()?)),
+        (RootDataType::Rational, 2) => Ok(Value::Rational(args[0].as_rational()?.checked_div(&args[1].as_rational()?)?)),
+        (RootDataType::Decimal, 1) => Ok(Value::Decimal(args[0].as_decimal()?)),
+        (RootDataType::Decimal, 2) => Ok(Value::Decimal(args[0].as_decimal()?.rescale(args[1].as_u32()?)?)),
+        _ => Err(ppl_error!(Argument, "{}() takes 1 or 2 arguments", datatype.as_string())),
+    }
+}
@@ -0,0 +1,103 @@
+//  This module implements the arithmetic of the complex datatype
+
+use crate::error::PplError;
+
+
+//  A complex value is the system structure complex=[rp:real, ip:real], so it prints, indexes and selects like any
+//  other structure.  The arithmetic is done in f64 and each result rounded back to the real parts.
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Complex {
+    re: f64,
+    im: f64,
+}
+
+impl Complex {
+    pub fn new(re: f64, im: f64) -> Complex {
+        Complex { re, im }
+    }
+
+    pub fn re(&self) -> f64 {
+        self.re
+    }
+
+    pub fn im(&self) -> f64 {
+        self.im
+    }
+
+    pub fn abs(&self) -> f64 {
+        self.re.hypot(self.im)
+    }
+
+    pub fn arg(&self) -> f64 {
+        self.im.atan2(self.re)
+    }
+
+    pub fn conj(&self) -> Complex {
+        Complex::new(self.re, -self.im)
+    }
+
+    pub fn add(&self, other: &Complex) -> Complex {
+        Complex::new(self.re + other.re, self.im + other.im)
+    }
+
+    pub fn checked_div(&self, other: &Complex) -> Result<Complex,PplError> {
+        let divisor = other.re * other.re + other.im * other.im;
+        if divisor == 0.0 {
+            return Err(ppl_error!(Arithmetic, "Division by zero"));
+        }
+        Ok(Complex::new((self.re * other.re + self.im * other.im) / divisor, (self.im * other.re - self.re * other.im) / divisor))
+    }
+
+    pub fn exp(&self) -> Complex {
+        let magnitude = self.re.exp();
+        Complex::new(magnitude * self.im.cos(), magnitude * self.im.sin())
+    }
+
+    pub fn ln(&self) -> Complex {
+        Complex::new(self.abs().ln(), self.arg())
+    }
+
+    pub fn mul(&self, other: &Complex) -> Complex {
+        Complex::new(self.re * other.re - self.im * other.im, self.re * other.im + self.im * other.re)
+    }
+
+    pub fn neg(&self) -> Complex {
+        Complex::new(-self.re, -self.im)
+    }
+
+    pub fn sub(&self, other: &Complex) -> Complex {
+        Complex::new(self.re - other.re, self.im - other.im)
+    }
+
+    //  An integer power is repeated multiplication, so that i^2 is exactly -1; any other is exp(w ln z)
+
+    pub fn pow(&self, exponent: &Complex) -> Result<Complex,PplError> {
+        if exponent.im == 0.0 && exponent.re.fract() == 0.0 && exponent.re.abs() <= i32::MAX as f64 {
+            let mut result = Complex::new(1.0, 0.0);
+            let mut base = *self;
+            let mut remaining = exponent.re.abs() as u32;
+            while remaining > 0 {
+                if remaining & 1 == 1 {
+                    result = result.mul(&base);
+                }
+                remaining >>= 1;
+                base = base.mul(&base);
+            }
+            return if exponent.re < 0.0 { Complex::new(1.0, 0.0).checked_div(&result) } else { Ok(result) };
+        }
+        if self.re == 0.0 && self.im == 0.0 {
+            return if exponent.re > 0.0 { Ok(*self) } else { Err(ppl_error!(Arithmetic, "Division by zero")) };
+        }
+        Ok(exponent.mul(&self.ln()).exp())
+    }
+
+    //  The principal root, with a real part that is never negative
+
+    pub fn sqrt(&self) -> Complex {
+        let modulus = self.abs();
+        let re = ((modulus + self.re) / 2.0).sqrt();
+        let im = ((modulus - self.re) / 2.0).sqrt();
+        Complex::new(re, if self.im < 0.0 { -im } else { im })
+    }
+}
@@ -1,23 +1,70 @@
-//  This module holds the datatype conversion methods for Value
+//  This module holds the datatype conversion methods for Value, and the conversions between Value and Rust
+//  types used by programs that embed PPL
+
+use std::{collections::HashMap, hash::Hash};
+
+#[cfg(feature = "bignum")]
+use num_bigint::BigInt;
+#[cfg(feature = "bignum")]
+use num_traits::{FromPrimitive, ToPrimitive, Zero};
 
 use crate::{workspace::GeneralSymbol, lexical::LineNumber};
 
-use super::Value;
+use super::{Value, complex::Complex, decimal::Decimal, rational::Rational, sequence::SequenceInstance, structure::{StructureInstance, StructureInstanceMember}};
+use crate::error::PplError;
 
 
 
 impl Value {
-    pub fn as_bool(&self) -> Result<bool,String> {
+    #[cfg(feature = "bignum")]
+    pub fn as_bignum(&self) -> Result<BigInt,PplError> {
+        match self {
+            Value::Bool(v) => Ok(BigInt::from(if *v { 1 } else { 0 })),
+            Value::Int(v) => Ok(BigInt::from(*v)),
+            Value::Int64(v) => Ok(BigInt::from(*v)),
+            Value::Bignum(v) => Ok(v.clone()),
+            Value::Rational(v) => Ok(BigInt::from(v.trunc())),
+            Value::Decimal(v) => Ok(BigInt::from(v.trunc())),
+            Value::Real(v) => BigInt::from_f32(v.trunc()).ok_or_else(|| ppl_error!(Arithmetic, "{} has no BIGNUM value", v)),
+            Value::Double(v) => BigInt::from_f64(v.trunc()).ok_or_else(|| ppl_error!(Arithmetic, "{} has no BIGNUM value", v)),
+            Value::Char(c) => {
+                if c.is_ascii_digit() {
+                    Ok(BigInt::from(c.to_digit(10).unwrap()))
+                } else {
+                    Err(ppl_error!(Type, "'{} is not a digit", c)) 
+                }
+            },
+            Value::Symbol(symbol) => {
+                match symbol.as_symbol() {
+                    GeneralSymbol::Variable(v) => {
+                        v.cell.borrow().as_contents().value.borrow().as_bignum()
+                    },
+                    _ => Err(ppl_error!(Type, "Cannot convert {} to BIGNUM", symbol))
+                }
+            },
+            Value::ValueByReference(v) => {
+                v.cell.borrow().as_ref_to_value().as_bignum()
+            },
+            _ => Err(ppl_error!(Type, "Unable to cast to BIGNUM")),
+        }
+    }
+
+    pub fn as_bool(&self) -> Result<bool,PplError> {
         match self {
             Value::Bool(v) => Ok(*v),
             Value::Int(v) => Ok(if *v == 0 { false } else { true }),
+            Value::Int64(v) => Ok(*v != 0),
+            #[cfg(feature = "bignum")]
+            Value::Bignum(v) => Ok(!v.is_zero()),
+            Value::Rational(v) => Ok(v.numerator() != 0),
+            Value::Decimal(v) => Ok(v.units() != 0),
             Value::Real(v) => Ok(if *v == 0.0 { false } else { true }),
             Value::Double(v) => Ok(if *v == 0.0 { false } else { true }),
             Value::Char(c) => {
                 match c.to_ascii_lowercase() {
                     'f' => Ok(false),
                     't' => Ok(true),
-                    _ => Err(format!("{} is not a valid BOOL", c)),
+                    _ => Err(ppl_error!(Type, "{} is not a valid BOOL", c)),
                 }
             },
             Value::Symbol(symbol) => {
@@ -25,17 +72,17 @@ impl Value {
                     GeneralSymbol::Variable(v) => {
                         v.cell.borrow().as_contents().value.borrow().as_bool()
                     },
-                    _ => Err(format!("Cannot convert {} to bool", symbol))
+                    _ => Err(ppl_error!(Type, "Cannot convert {} to bool", symbol))
                 }
             },
             Value::ValueByReference(v) => {
                 v.cell.borrow().as_ref_to_value().as_bool()
             },
-            _ => Err(format!("Invalid BOOL")),
+            _ => Err(ppl_error!(Type, "Invalid BOOL")),
         }
     }
 
-    pub fn as_char(&self) -> Result<char,String> {
+    pub fn as_char(&self) -> Result<char,PplError> {
         match self {
             Value::Char(c) => Ok(*c),
             Value::Symbol(symbol) => {
@@ -43,27 +90,79 @@ impl Value {
                     GeneralSymbol::Variable(v) => {
                         v.cell.borrow().as_contents().value.borrow().as_char()
                     },
-                    _ => Err(format!("Cannot convert {} to CHAR", symbol))
+                    _ => Err(ppl_error!(Type, "Cannot convert {} to CHAR", symbol))
                 }
             },
             Value::ValueByReference(v) => {
                 v.cell.borrow().as_ref_to_value().as_char()
             },
-            _ => Err(format!("Invalid CHAR")),
+            _ => Err(ppl_error!(Type, "Invalid CHAR")),
         }
     }
 
-    pub fn as_f32(&self) -> Result<f32,String> {
+    //  Any number is a complex value with no imaginary part
+
+    pub fn as_complex(&self) -> Result<Complex,PplError> {
+        match self.dereference() {
+            Value::Structure(structure) if structure.as_string() == "complex" =>
+                Ok(Complex::new(structure.member("rp")?.as_f64()?, structure.member("ip")?.as_f64()?)),
+            Value::Structure(structure) => Err(ppl_error!(Type, "{} is not COMPLEX", structure.as_string())),
+            v => Ok(Complex::new(v.as_f64()?, 0.0)),
+        }
+    }
+
+    //  A string such as "12.50" is read as a decimal, so exact values can be written without going through a float
+
+    pub fn as_decimal(&self) -> Result<Decimal,PplError> {
+        match self {
+            Value::Bool(v) => Ok(Decimal::from_integer(if *v { 1 } else { 0 })),
+            Value::Int(v) => Ok(Decimal::from_integer(*v as i64)),
+            Value::Int64(v) => Ok(Decimal::from_integer(*v)),
+            #[cfg(feature = "bignum")]
+            Value::Bignum(v) => Decimal::new(v.to_i128().ok_or_else(|| ppl_error!(Arithmetic, "{} is out of range for DECIMAL", v))?, 0),
+            Value::Rational(v) => Decimal::from_rational(v),
+            Value::Decimal(v) => Ok(*v),
+            Value::Real(v) => Decimal::from_f32(*v),
+            Value::Double(v) => Decimal::from_f64(*v),
+            Value::Char(c) => {
+                if c.is_ascii_digit() {
+                    Ok(Decimal::from_integer(c.to_digit(10).unwrap() as i64))
+                } else {
+                    Err(ppl_error!(Type, "'{} is not a digit", c)) 
+                }
+            },
+            Value::Sequence(_) => String::try_from(self)?.parse(),
+            Value::Symbol(symbol) => {
+                match symbol.as_symbol() {
+                    GeneralSymbol::Variable(v) => {
+                        v.cell.borrow().as_contents().value.borrow().as_decimal()
+                    },
+                    _ => Err(ppl_error!(Type, "Cannot convert {} to DECIMAL", symbol))
+                }
+            },
+            Value::ValueByReference(v) => {
+                v.cell.borrow().as_ref_to_value().as_decimal()
+            },
+            _ => Err(ppl_error!(Type, "Unable to cast to DECIMAL")),
+        }
+    }
+
+    pub fn as_f32(&self) -> Result<f32,PplError> {
         match self {
             Value::Bool(v) => Ok(if *v { 1.0 } else { 0.0 }),
             Value::Int(v) => Ok(*v as f32),
+            Value::Int64(v) => Ok(*v as f32),
+            #[cfg(feature = "bignum")]
+            Value::Bignum(v) => v.to_f32().ok_or_else(|| ppl_error!(Arithmetic, "{} is out of range for REAL", v)),
+            Value::Rational(v) => Ok(v.to_f64() as f32),
+            Value::Decimal(v) => Ok(v.to_f64() as f32),
             Value::Real(v) => Ok(*v as f32),
             Value::Double(v) => Ok(*v as f32),
             Value::Char(c) => {
                 if c.is_digit(10) {
                     Ok(c.to_digit(10).unwrap() as f32)
                 } else {
-                    Err(format!("'{} is not a digit", c)) 
+                    Err(ppl_error!(Type, "'{} is not a digit", c)) 
                 }
             },
             Value::Symbol(symbol) => {
@@ -71,27 +170,32 @@ impl Value {
                     GeneralSymbol::Variable(v) => {
                         v.cell.borrow().as_ref_to_value().as_f32()
                     },
-                    _ => Err(format!("Cannot convert {} to REAL", symbol))
+                    _ => Err(ppl_error!(Type, "Cannot convert {} to REAL", symbol))
                 }
             },
             Value::ValueByReference(v) => {
                 v.cell.borrow().as_ref_to_value().as_f32()
             },
-            _ => Err(format!("Unable to cast to REAL")),
+            _ => Err(ppl_error!(Type, "Unable to cast to REAL")),
         }
     }
 
-    pub fn as_f64(&self) -> Result<f64,String> {
+    pub fn as_f64(&self) -> Result<f64,PplError> {
         match self {
             Value::Bool(v) => Ok(if *v { 1.0 } else { 0.0 }),
             Value::Int(v) => Ok(*v as f64),
+            Value::Int64(v) => Ok(*v as f64),
+            #[cfg(feature = "bignum")]
+            Value::Bignum(v) => v.to_f64().ok_or_else(|| ppl_error!(Arithmetic, "{} is out of range for DBL", v)),
+            Value::Rational(v) => Ok(v.to_f64()),
+            Value::Decimal(v) => Ok(v.to_f64()),
             Value::Real(v) => Ok(*v as f64),
             Value::Double(v) => Ok(*v as f64),
             Value::Char(c) => {
                 if c.is_digit(10) {
                     Ok(c.to_digit(10).unwrap() as f64)
                 } else {
-                    Err(format!("'{} is not a digit", c)) 
+                    Err(ppl_error!(Type, "'{} is not a digit", c)) 
                 }
             },
             Value::Symbol(symbol) => {
@@ -99,27 +203,32 @@ impl Value {
                     GeneralSymbol::Variable(v) => {
                         v.cell.borrow().as_contents().value.borrow().as_f64()
                     },
-                    _ => Err(format!("Cannot convert {} to DBL", symbol))
+                    _ => Err(ppl_error!(Type, "Cannot convert {} to DBL", symbol))
                 }
             },
             Value::ValueByReference(v) => {
                 v.cell.borrow().as_ref_to_value().as_f64()
             },
-            _ => Err(format!("Unable to cast to DBL")),
+            _ => Err(ppl_error!(Type, "Unable to cast to DBL")),
         }
     }
 
-    pub fn as_i32(&self) -> Result<i32,String> {
+    pub fn as_i32(&self) -> Result<i32,PplError> {
         match self {
             Value::Bool(v) => Ok(if *v { 1 } else { 0 }),
             Value::Int(v) => Ok(*v as i32),
+            Value::Int64(v) => i32::try_from(*v).map_err(|_| ppl_error!(Arithmetic, "{} is out of range for INT", v)),
+            #[cfg(feature = "bignum")]
+            Value::Bignum(v) => v.to_i32().ok_or_else(|| ppl_error!(Arithmetic, "{} is out of range for INT", v)),
+            Value::Rational(v) => i32::try_from(v.trunc()).map_err(|_| ppl_error!(Arithmetic, "{} is out of range for INT", v)),
+            Value::Decimal(v) => i32::try_from(v.trunc()).map_err(|_| ppl_error!(Arithmetic, "{} is out of range for INT", v)),
             Value::Real(v) => Ok(*v as i32),
             Value::Double(v) => Ok(*v as i32),
             Value::Char(c) => {
                 if c.is_digit(10) {
                     Ok(c.to_digit(10).unwrap() as i32)
                 } else {
-                    Err(format!("'{} is not a digit", c)) 
+                    Err(ppl_error!(Type, "'{} is not a digit", c)) 
                 }
             },
             Value::Symbol(symbol) => {
@@ -127,27 +236,32 @@ impl Value {
                     GeneralSymbol::Variable(v) => {
                         v.cell.borrow().as_contents().value.borrow().as_i32()
                     },
-                    _ => Err(format!("Cannot convert {} to INT", symbol))
+                    _ => Err(ppl_error!(Type, "Cannot convert {} to INT", symbol))
                 }
             },
             Value::ValueByReference(v) => {
                 v.cell.borrow().as_ref_to_value().as_i32()
             },
-            _ => Err(format!("Unable to cast to INT")),
+            _ => Err(ppl_error!(Type, "Unable to cast to INT")),
         }
     }
 
-    pub fn as_i64(&self) -> Result<i64,String> {
+    pub fn as_i64(&self) -> Result<i64,PplError> {
         match self {
             Value::Bool(v) => Ok(if *v { 1 } else { 0 }),
             Value::Int(v) => Ok(*v as i64),
+            Value::Int64(v) => Ok(*v),
+            #[cfg(feature = "bignum")]
+            Value::Bignum(v) => v.to_i64().ok_or_else(|| ppl_error!(Arithmetic, "{} is out of range for INT64", v)),
+            Value::Rational(v) => Ok(v.trunc()),
+            Value::Decimal(v) => i64::try_from(v.trunc()).map_err(|_| ppl_error!(Arithmetic, "{} is out of range for INT64", v)),
             Value::Real(v) => Ok(*v as i64),
             Value::Double(v) => Ok(*v as i64),
             Value::Char(c) => {
                 if c.is_digit(10) {
                     Ok(c.to_digit(10).unwrap() as i64)
                 } else {
-                    Err(format!("'{} is not a digit", c)) 
+                    Err(ppl_error!(Type, "'{} is not a digit", c)) 
                 }
             },
             Value::Symbol(symbol) => {
@@ -155,31 +269,72 @@ impl Value {
                     GeneralSymbol::Variable(v) => {
                         v.cell.borrow().as_contents().value.borrow().as_i64()
                     },
-                    _ => Err(format!("Cannot convert {} to INT", symbol))
+                    _ => Err(ppl_error!(Type, "Cannot convert {} to INT64", symbol))
                 }
             },
             Value::ValueByReference(v) => {
                 v.cell.borrow().as_ref_to_value().as_i64()
             },
-            _ => Err(format!("Unable to cast to INT")),
+            _ => Err(ppl_error!(Type, "Unable to cast to INT64")),
+        }
+    }
+
+    //  A string such as "1/3" or "0.25" is read as a rational
+
+    pub fn as_rational(&self) -> Result<Rational,PplError> {
+        match self {
+            Value::Bool(v) => Ok(Rational::from_integer(if *v { 1 } else { 0 })),
+            Value::Int(v) => Ok(Rational::from_integer(*v as i64)),
+            Value::Int64(v) => Ok(Rational::from_integer(*v)),
+            #[cfg(feature = "bignum")]
+            Value::Bignum(v) => Ok(Rational::from_integer(v.to_i64().ok_or_else(|| ppl_error!(Arithmetic, "{} is out of range for RATIONAL", v))?)),
+            Value::Rational(v) => Ok(*v),
+            Value::Decimal(v) => Rational::try_from(v),
+            Value::Real(v) => Rational::try_from(&Decimal::from_f32(*v)?),
+            Value::Double(v) => Rational::from_f64(*v),
+            Value::Char(c) => {
+                if c.is_ascii_digit() {
+                    Ok(Rational::from_integer(c.to_digit(10).unwrap() as i64))
+                } else {
+                    Err(ppl_error!(Type, "'{} is not a digit", c)) 
+                }
+            },
+            Value::Sequence(_) => String::try_from(self)?.parse(),
+            Value::Symbol(symbol) => {
+                match symbol.as_symbol() {
+                    GeneralSymbol::Variable(v) => {
+                        v.cell.borrow().as_contents().value.borrow().as_rational()
+                    },
+                    _ => Err(ppl_error!(Type, "Cannot convert {} to RATIONAL", symbol))
+                }
+            },
+            Value::ValueByReference(v) => {
+                v.cell.borrow().as_ref_to_value().as_rational()
+            },
+            _ => Err(ppl_error!(Type, "Unable to cast to RATIONAL")),
         }
     }
 
-    pub fn as_line_number(&self) -> Result<LineNumber,String> {
+    pub fn as_line_number(&self) -> Result<LineNumber,PplError> {
         self.as_u32()
     }
     
-    pub fn as_u32(&self) -> Result<u32,String> {
+    pub fn as_u32(&self) -> Result<u32,PplError> {
         match self {
             Value::Bool(v) => Ok(if *v { 1 } else { 0 }),
             Value::Int(v) => Ok(*v as u32),
+            Value::Int64(v) => u32::try_from(*v).map_err(|_| ppl_error!(Arithmetic, "{} is out of range", v)),
+            #[cfg(feature = "bignum")]
+            Value::Bignum(v) => v.to_u32().ok_or_else(|| ppl_error!(Arithmetic, "{} is out of range", v)),
+            Value::Rational(v) => u32::try_from(v.trunc()).map_err(|_| ppl_error!(Arithmetic, "{} is out of range", v)),
+            Value::Decimal(v) => u32::try_from(v.trunc()).map_err(|_| ppl_error!(Arithmetic, "{} is out of range", v)),
             Value::Real(v) => Ok(*v as u32),
             Value::Double(v) => Ok(*v as u32),
             Value::Char(c) => {
                 if c.is_digit(10) {
                     Ok(c.to_digit(10).unwrap() as u32)
                 } else {
-                    Err(format!("'{} is not a digit", c)) 
+                    Err(ppl_error!(Type, "'{} is not a digit", c)) 
                 }
             },
             Value::Symbol(symbol) => {
@@ -187,13 +342,13 @@ impl Value {
                     GeneralSymbol::Variable(v) => {
                         v.cell.borrow().as_contents().value.borrow().as_u32()
                     },
-                    _ => Err(format!("Cannot convert {} to INT", symbol))
+                    _ => Err(ppl_error!(Type, "Cannot convert {} to INT", symbol))
                 }
             },
             Value::ValueByReference(v) => {
                 v.cell.borrow().as_ref_to_value().as_u32()
             },
-            _ => Err(format!("Unable to cast to INT")),
+            _ => Err(ppl_error!(Type, "Unable to cast to INT")),
         }
     }
 
@@ -250,17 +405,22 @@ impl Value {
         }
     }
 
-    pub fn as_usize(&self) -> Result<usize,String> {
+    pub fn as_usize(&self) -> Result<usize,PplError> {
         match self {
             Value::Bool(v) => Ok(if *v { 1 } else { 0 }),
             Value::Int(v) => Ok(*v as usize),
+            Value::Int64(v) => usize::try_from(*v).map_err(|_| ppl_error!(Arithmetic, "{} is out of range", v)),
+            #[cfg(feature = "bignum")]
+            Value::Bignum(v) => v.to_usize().ok_or_else(|| ppl_error!(Arithmetic, "{} is out of range", v)),
+            Value::Rational(v) => usize::try_from(v.trunc()).map_err(|_| ppl_error!(Arithmetic, "{} is out of range", v)),
+            Value::Decimal(v) => usize::try_from(v.trunc()).map_err(|_| ppl_error!(Arithmetic, "{} is out of range", v)),
             Value::Real(v) => Ok(*v as usize),
             Value::Double(v) => Ok(*v as usize),
             Value::Char(c) => {
                 if c.is_digit(10) {
                     Ok(c.to_digit(10).unwrap() as usize)
                 } else {
-                    Err(format!("'{} is not a digit", c)) 
+                    Err(ppl_error!(Type, "'{} is not a digit", c)) 
                 }
             },
             Value::Symbol(symbol) => {
@@ -268,15 +428,289 @@ impl Value {
                     GeneralSymbol::Variable(v) => {
                         v.cell.borrow().as_contents().value.borrow().as_usize()
                     },
-                    _ => Err(format!("Cannot convert {} to usize", symbol))
+                    _ => Err(ppl_error!(Type, "Cannot convert {} to usize", symbol))
                 }
             },
             Value::ValueByReference(v) => {
                 v.cell.borrow().as_ref_to_value().as_usize()
             },
-            _ => Err(format!("Unable to cast to INT")),
+            _ => Err(ppl_error!(Type, "Unable to cast to INT")),
+        }
+    }
+
+    //  The value a variable or reference holds, or the value itself
+
+    pub fn dereference(&self) -> Value {
+        match self {
+            Value::Symbol(symbol) => {
+                match symbol.as_symbol() {
+                    GeneralSymbol::Variable(v) => v.cell.borrow().as_contents().value.borrow().dereference(),
+                    _ => self.clone(),
+                }
+            },
+            Value::ValueByReference(v) => v.cell.borrow().as_ref_to_value().dereference(),
+            Value::LogicalLink(envelope) => envelope.as_ref_to_value().dereference(),
+            _ => self.clone(),
+        }
+    }
+
+    pub fn as_sequence(&self) -> Result<SequenceInstance,PplError> {
+        match self.dereference() {
+            Value::Sequence(seq) => Ok(seq),
+            v => Err(ppl_error!(Type, "{} is not a sequence", v)),
+        }
+    }
+
+    pub fn as_structure(&self) -> Result<StructureInstance,PplError> {
+        match self.dereference() {
+            Value::Structure(structure) => Ok(structure),
+            v => Err(ppl_error!(Type, "{} is not a structure", v)),
+        }
+    }
+}
+
+
+//  Rust values become PPL values of the matching datatype.  Vectors become tuples, and maps become tuples of
+//  [key, value] pairs, in key order.
+
+impl From<bool> for Value {
+    fn from(v: bool) -> Value {
+        Value::Bool(v)
+    }
+}
+
+impl From<i32> for Value {
+    fn from(v: i32) -> Value {
+        Value::Int(v)
+    }
+}
+
+impl From<i64> for Value {
+    fn from(v: i64) -> Value {
+        Value::Int64(v)
+    }
+}
+
+#[cfg(feature = "bignum")]
+impl From<BigInt> for Value {
+    fn from(v: BigInt) -> Value {
+        Value::Bignum(v)
+    }
+}
+
+impl From<Rational> for Value {
+    fn from(v: Rational) -> Value {
+        Value::Rational(v)
+    }
+}
+
+impl From<Decimal> for Value {
+    fn from(v: Decimal) -> Value {
+        Value::Decimal(v)
+    }
+}
+
+//  A complex value is a structure, whose parts are rounded to reals
+
+impl From<Complex> for Value {
+    fn from(v: Complex) -> Value {
+        Value::Structure(StructureInstance::from_string(&String::from("complex"), vec![
+            StructureInstanceMember::new("rp", "real", Value::Real(v.re() as f32)),
+            StructureInstanceMember::new("ip", "real", Value::Real(v.im() as f32))]))
+    }
+}
+
+impl From<f32> for Value {
+    fn from(v: f32) -> Value {
+        Value::Real(v)
+    }
+}
+
+impl From<f64> for Value {
+    fn from(v: f64) -> Value {
+        Value::Double(v)
+    }
+}
+
+impl From<char> for Value {
+    fn from(v: char) -> Value {
+        Value::Char(v)
+    }
+}
+
+impl From<&str> for Value {
+    fn from(v: &str) -> Value {
+        SequenceInstance::construct_string_sequence(v)
+    }
+}
+
+impl From<String> for Value {
+    fn from(v: String) -> Value {
+        SequenceInstance::construct_string_sequence(&v)
+    }
+}
+
+impl<T: Into<Value>> From<Vec<T>> for Value {
+    fn from(v: Vec<T>) -> Value {
+        Value::Sequence(SequenceInstance::from_string(&String::from("tuple"), 1, v.into_iter().map(|member| member.into()).collect()))
+    }
+}
+
+impl<K: Into<Value> + Ord, V: Into<Value>> From<HashMap<K,V>> for Value {
+    fn from(v: HashMap<K,V>) -> Value {
+        let mut entries: Vec<(K,V)> = v.into_iter().collect();
+        entries.sort_by(|(left, _), (right, _)| left.cmp(right));
+        Value::from(entries.into_iter().map(|(key, value)| Value::from(vec![key.into(), value.into()])).collect::<Vec<Value>>())
+    }
+}
+
+
+//  And back again.  These are as strict as PPL's own coercions: an int converts to an f64, but not to a String.
+
+impl TryFrom<&Value> for Value {
+    type Error = PplError;
+
+    fn try_from(v: &Value) -> Result<Value,PplError> {
+        Ok(v.dereference())
+    }
+}
+
+impl TryFrom<&Value> for bool {
+    type Error = PplError;
+
+    fn try_from(v: &Value) -> Result<bool,PplError> {
+        v.as_bool()
+    }
+}
+
+impl TryFrom<&Value> for i32 {
+    type Error = PplError;
+
+    fn try_from(v: &Value) -> Result<i32,PplError> {
+        v.as_i32()
+    }
+}
+
+impl TryFrom<&Value> for i64 {
+    type Error = PplError;
+
+    fn try_from(v: &Value) -> Result<i64,PplError> {
+        v.as_i64()
+    }
+}
+
+#[cfg(feature = "bignum")]
+impl TryFrom<&Value> for BigInt {
+    type Error = PplError;
+
+    fn try_from(v: &Value) -> Result<BigInt,PplError> {
+        v.as_bignum()
+    }
+}
+
+impl TryFrom<&Value> for Rational {
+    type Error = PplError;
+
+    fn try_from(v: &Value) -> Result<Rational,PplError> {
+        v.as_rational()
+    }
+}
+
+impl TryFrom<&Value> for Decimal {
+    type Error = PplError;
+
+    fn try_from(v: &Value) -> Result<Decimal,PplError> {
+        v.as_decimal()
+    }
+}
+
+impl TryFrom<&Value> for Complex {
+    type Error = PplError;
+
+    fn try_from(v: &Value) -> Result<Complex,PplError> {
+        v.as_complex()
+    }
+}
+
+impl TryFrom<&Value> for f32 {
+    type Error = PplError;
+
+    fn try_from(v: &Value) -> Result<f32,PplError> {
+        v.as_f32()
+    }
+}
+
+impl TryFrom<&Value> for f64 {
+    type Error = PplError;
+
+    fn try_from(v: &Value) -> Result<f64,PplError> {
+        v.as_f64()
+    }
+}
+
+impl TryFrom<&Value> for char {
+    type Error = PplError;
+
+    fn try_from(v: &Value) -> Result<char,PplError> {
+        v.as_char()
+    }
+}
+
+impl TryFrom<&Value> for String {
+    type Error = PplError;
+
+    fn try_from(v: &Value) -> Result<String,PplError> {
+        match v.dereference() {
+            Value::Char(c) => Ok(String::from(c)),
+            Value::Sequence(seq) => {
+                seq.as_values().iter().map(|cell| match *cell.borrow().as_ref_to_value() {
+                    Value::Char(c) => Ok(c),
+                    _ => Err(ppl_error!(Type, "{} is not a string", seq)),
+                }).collect()
+            },
+            v => Err(ppl_error!(Type, "{} is not a string", v)),
         }
     }
 }
 
+impl<T> TryFrom<&Value> for Vec<T>
+where T: for<'a> TryFrom<&'a Value, Error = PplError>
+{
+    type Error = PplError;
+
+    fn try_from(v: &Value) -> Result<Vec<T>,PplError> {
+        v.as_sequence()?.as_values().iter().map(|cell| T::try_from(&*cell.borrow().as_ref_to_value())).collect()
+    }
+}
+
+//  A map is read from a tuple of [key, value] pairs, or from a structure, whose selectors are the keys
 
+impl<K, V> TryFrom<&Value> for HashMap<K,V>
+where K: for<'a> TryFrom<&'a Value, Error = PplError> + Eq + Hash,
+      V: for<'a> TryFrom<&'a Value, Error = PplError>
+{
+    type Error = PplError;
+
+    fn try_from(v: &Value) -> Result<HashMap<K,V>,PplError> {
+        let mut map = HashMap::new();
+        match v.dereference() {
+            Value::Structure(structure) => {
+                for member in structure.as_values() {
+                    map.insert(K::try_from(&Value::from(member.as_string()))?, V::try_from(&member.as_value())?);
+                }
+            },
+            Value::Sequence(seq) => {
+                for cell in &*seq.as_values() {
+                    let pair = cell.borrow().as_ref_to_value().as_sequence()?;
+                    if pair.length() != 2 {
+                        return Err(ppl_error!(Type, "{} is not a [key, value] pair", pair));
+                    }
+                    let entry = pair.as_values();
+                    map.insert(K::try_from(&*entry[0].borrow().as_ref_to_value())?, V::try_from(&*entry[1].borrow().as_ref_to_value())?);
+                }
+            },
+            v => return Err(ppl_error!(Type, "{} is not a structure or a sequence of pairs", v)),
+        }
+        Ok(map)
+    }
+}
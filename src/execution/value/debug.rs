@@ -16,6 +16,11 @@ impl fmt::Debug for Value {
             Value::Empty => write!(f, "Value::Empty"),
             Value::Bool(v) => write!(f, "Value::Bool({})", v),
             Value::Int(v) => write!(f, "Value::Int({})", v),
+            Value::Int64(v) => write!(f, "Value::Int64({})", v),
+            #[cfg(feature = "bignum")]
+            Value::Bignum(v) => write!(f, "Value::Bignum({})", v),
+            Value::Rational(v) => write!(f, "Value::Rational({})", v),
+            Value::Decimal(v) => write!(f, "Value::Decimal({})", v),
             Value::Real(v) => write!(f, "Value::Real({})", v),
             Value::Double(v) => write!(f, "Value::Double({})", v),
             Value::Char(v) => write!(f, "Value::Char({})", String::from(*v).replace('\n', "\\n").replace('\r', "\\r")),
@@ -0,0 +1,237 @@
+//  This module implements the fixed-scale numbers held by the decimal datatype
+
+use std::{cmp::Ordering, fmt, str::FromStr};
+
+use super::rational::Rational;
+use crate::error::PplError;
+
+
+//  A decimal is a count of units of 10^-scale, so 12.50 is 1250 units at scale 2.  Each decimal keeps the scale
+//  it was made with: a sum or difference has the larger scale of its operands, a product the sum of their scales,
+//  and a quotient the larger of their scales.  Results that have to lose digits are rounded half away from zero,
+//  the way money is rounded.
+
+pub const MAX_SCALE: u32 = 18;
+
+#[derive(Debug, Clone, Copy)]
+pub struct Decimal {
+    units: i128,
+    scale: u32,
+}
+
+impl Decimal {
+    pub fn new(units: i128, scale: u32) -> Result<Decimal,PplError> {
+        if scale > MAX_SCALE {
+            Err(ppl_error!(Arithmetic, "A DECIMAL has at most {} decimal places", MAX_SCALE))
+        } else {
+            Ok(Decimal { units, scale })
+        }
+    }
+
+    pub fn from_integer(value: i64) -> Decimal {
+        Decimal { units: value as i128, scale: 0 }
+    }
+
+    //  A float is taken at its shortest decimal value, so that 0.1 is 0.1 rather than the binary fraction nearest it
+
+    pub fn from_f64(value: f64) -> Result<Decimal,PplError> {
+        if value.is_finite() {
+            format!("{}", value).parse()
+        } else {
+            Err(ppl_error!(Arithmetic, "{} has no exact value", value))
+        }
+    }
+
+    pub fn from_f32(value: f32) -> Result<Decimal,PplError> {
+        if value.is_finite() {
+            format!("{}", value).parse()
+        } else {
+            Err(ppl_error!(Arithmetic, "{} has no exact value", value))
+        }
+    }
+
+    //  A rational is exact when its denominator divides a power of ten, and is rounded to MAX_SCALE places when not
+
+    pub fn from_rational(value: &Rational) -> Result<Decimal,PplError> {
+        let scale = (0..MAX_SCALE).find(|scale| 10i128.pow(*scale) % value.denominator() as i128 == 0).unwrap_or(MAX_SCALE);
+        Decimal::rational_at_scale(value, scale)
+    }
+
+    pub fn rational_at_scale(value: &Rational, scale: u32) -> Result<Decimal,PplError> {
+        let numerator = (value.numerator() as i128).checked_mul(power_of_ten(scale)?).ok_or_else(overflow)?;
+        Decimal::new(divide_rounded(numerator, value.denominator() as i128), scale)
+    }
+
+    pub fn units(&self) -> i128 {
+        self.units
+    }
+
+    pub fn scale(&self) -> u32 {
+        self.scale
+    }
+
+    pub fn is_integer(&self) -> bool {
+        self.units % power_of_ten(self.scale).unwrap() == 0
+    }
+
+    //  The same value with another number of decimal places, rounded if that loses digits
+
+    pub fn rescale(&self, scale: u32) -> Result<Decimal,PplError> {
+        if scale >= self.scale {
+            Decimal::new(self.units.checked_mul(power_of_ten(scale - self.scale)?).ok_or_else(overflow)?, scale)
+        } else {
+            Decimal::new(divide_rounded(self.units, power_of_ten(self.scale - scale)?), scale)
+        }
+    }
+
+    pub fn checked_add(&self, other: &Decimal) -> Result<Decimal,PplError> {
+        let scale = self.scale.max(other.scale);
+        Decimal::new(self.rescale(scale)?.units.checked_add(other.rescale(scale)?.units).ok_or_else(overflow)?, scale)
+    }
+
+    pub fn checked_div(&self, other: &Decimal) -> Result<Decimal,PplError> {
+        if other.units == 0 {
+            return Err(ppl_error!(Arithmetic, "Division by zero"));
+        }
+        let scale = self.scale.max(other.scale);
+        let dividend = self.units.checked_mul(power_of_ten(scale + other.scale - self.scale)?).ok_or_else(overflow)?;
+        Decimal::new(divide_rounded(dividend, other.units), scale)
+    }
+
+    pub fn checked_mul(&self, other: &Decimal) -> Result<Decimal,PplError> {
+        let units = self.units.checked_mul(other.units).ok_or_else(overflow)?;
+        let scale = self.scale + other.scale;
+        if scale > MAX_SCALE {
+            Decimal::new(divide_rounded(units, power_of_ten(scale - MAX_SCALE)?), MAX_SCALE)
+        } else {
+            Decimal::new(units, scale)
+        }
+    }
+
+    pub fn checked_neg(&self) -> Result<Decimal,PplError> {
+        Decimal::new(self.units.checked_neg().ok_or_else(overflow)?, self.scale)
+    }
+
+    pub fn checked_sub(&self, other: &Decimal) -> Result<Decimal,PplError> {
+        self.checked_add(&other.checked_neg()?)
+    }
+
+    //  A negative power is the quotient of 1 and the positive one, at the scale of the base
+
+    pub fn pow(&self, exponent: i64) -> Result<Decimal,PplError> {
+        let mut result = Decimal::from_integer(1);
+        let mut base = *self;
+        let mut remaining = exponent.unsigned_abs();
+        while remaining > 0 {
+            if remaining & 1 == 1 {
+                result = result.checked_mul(&base)?;
+            }
+            remaining >>= 1;
+            if remaining > 0 {
+                base = base.checked_mul(&base)?;
+            }
+        }
+        if exponent < 0 {
+            Decimal::from_integer(1).rescale(self.scale)?.checked_div(&result)
+        } else {
+            Ok(result)
+        }
+    }
+
+    pub fn to_f64(&self) -> f64 {
+        self.to_string().parse().unwrap()
+    }
+
+    //  The integer part, truncated toward zero like a float converted to an int
+
+    pub fn trunc(&self) -> i128 {
+        self.units / power_of_ten(self.scale).unwrap()
+    }
+
+    //  The integer part, and the fraction as a count of 10^-MAX_SCALE units, which orders decimals of any scale
+
+    fn parts(&self) -> (i128, i128) {
+        let divisor = power_of_ten(self.scale).unwrap();
+        (self.units / divisor, (self.units % divisor) * power_of_ten(MAX_SCALE - self.scale).unwrap())
+    }
+}
+
+impl fmt::Display for Decimal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let digits = format!("{:0>width$}", self.units.unsigned_abs(), width = self.scale as usize + 1);
+        let (integer, fraction) = digits.split_at(digits.len() - self.scale as usize);
+        let sign = if self.units < 0 { "-" } else { "" };
+        if fraction.is_empty() {
+            write!(f, "{}{}", sign, integer)
+        } else {
+            write!(f, "{}{}.{}", sign, integer, fraction)
+        }
+    }
+}
+
+impl PartialEq for Decimal {
+    fn eq(&self, other: &Decimal) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Decimal {}
+
+impl Ord for Decimal {
+    fn cmp(&self, other: &Decimal) -> Ordering {
+        self.parts().cmp(&other.parts())
+    }
+}
+
+impl PartialOrd for Decimal {
+    fn partial_cmp(&self, other: &Decimal) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+//  An optional sign, digits, and an optional fraction, as in "-12.50".  The number keeps as many decimal places as
+//  are written, up to MAX_SCALE.
+
+impl FromStr for Decimal {
+    type Err = PplError;
+
+    fn from_str(s: &str) -> Result<Decimal,PplError> {
+        let text = s.trim();
+        let (negative, unsigned) = match text.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, text.strip_prefix('+').unwrap_or(text)),
+        };
+        let (integer, fraction) = unsigned.split_once('.').unwrap_or((unsigned, ""));
+        if integer.len() + fraction.len() == 0 || !integer.chars().chain(fraction.chars()).all(|c| c.is_ascii_digit()) {
+            return Err(ppl_error!(Type, "{} is not a decimal number", s));
+        }
+
+        let scale = (fraction.len() as u32).min(MAX_SCALE);
+        let mut units: i128 = 0;
+        for c in integer.chars().chain(fraction.chars().take(scale as usize)) {
+            units = units.checked_mul(10).and_then(|units| units.checked_add(c.to_digit(10).unwrap() as i128)).ok_or_else(overflow)?;
+        }
+        if fraction.chars().nth(scale as usize).is_some_and(|c| c >= '5') {
+            units = units.checked_add(1).ok_or_else(overflow)?;
+        }
+        Decimal::new(if negative { -units } else { units }, scale)
+    }
+}
+
+fn divide_rounded(dividend: i128, divisor: i128) -> i128 {
+    let quotient = dividend / divisor;
+    let remainder = (dividend % divisor).unsigned_abs();
+    if remainder >= divisor.unsigned_abs() - remainder {
+        quotient + dividend.signum() * divisor.signum()
+    } else {
+        quotient
+    }
+}
+
+fn overflow() -> PplError {
+    ppl_error!(Arithmetic, "Integer overflow in decimal arithmetic")
+}
+
+fn power_of_ten(exponent: u32) -> Result<i128,PplError> {
+    10i128.checked_pow(exponent).ok_or_else(overflow)
+}
@@ -4,7 +4,8 @@ use std::fmt;
 
 use crate::workspace::WorkSpace;
 
-use super::Value;
+use super::{Value, decimal::Decimal};
+use crate::error::PplError;
 
 pub mod format_parser;
 pub mod floating_point;
@@ -87,42 +88,58 @@ impl ParsedF64 {
 
 
 impl super::Value {
-    pub fn format(args: &[Value], workspace: &WorkSpace) -> Result<String,String> {
+    pub fn format(args: &[Value], workspace: &WorkSpace) -> Result<String,PplError> {
         let parser = workspace.get_format_parser();
         let mut last_format = FormatType::Default(FormatControl::new());
         let mut result = String::new();
 
         for arg in args {
             match (arg, &last_format) {
-                (Value::Int(i), FormatType::Default(f)) => result += Value::format_int(*i, f)?.as_str(),
+                (Value::Int(i), FormatType::Default(f)) => result += Value::format_int(*i as i64, f)?.as_str(),
                 (Value::Int(i), FormatType::Double(f)) => result += Value::format_float(*i as f64, false, &f.exponent_symbol.unwrap(), f, workspace)?.as_str(),
-                (Value::Int(i), FormatType::FixedPoint(f)) => result += Value::format_int(*i, f)?.as_str(),
+                (Value::Int(i), FormatType::FixedPoint(f)) => result += Value::format_int(*i as i64, f)?.as_str(),
                 (Value::Int(i), FormatType::Real(f)) => result += Value::format_float(*i as f64, false, &f.exponent_symbol.unwrap(), f, workspace)?.as_str(),
                 (Value::Int(_), FormatType::Free) => result += format!("{}", arg).as_str(),
+                (Value::Int64(i), FormatType::Default(f)) => result += Value::format_int(*i, f)?.as_str(),
+                (Value::Int64(i), FormatType::Double(f)) => result += Value::format_float(*i as f64, false, &f.exponent_symbol.unwrap(), f, workspace)?.as_str(),
+                (Value::Int64(i), FormatType::FixedPoint(f)) => result += Value::format_int(*i, f)?.as_str(),
+                (Value::Int64(i), FormatType::Real(f)) => result += Value::format_float(*i as f64, false, &f.exponent_symbol.unwrap(), f, workspace)?.as_str(),
+                (Value::Int64(_), FormatType::Free) => result += format!("{}", arg).as_str(),
+                #[cfg(feature = "bignum")]
+                (Value::Bignum(i), FormatType::Default(f)) | (Value::Bignum(i), FormatType::FixedPoint(f)) => result += Value::format_bignum(i, f)?.as_str(),
+                #[cfg(feature = "bignum")]
+                (Value::Bignum(_), FormatType::Double(f)) | (Value::Bignum(_), FormatType::Real(f)) => result += Value::format_float(arg.as_f64()?, false, &f.exponent_symbol.unwrap(), f, workspace)?.as_str(),
+                #[cfg(feature = "bignum")]
+                (Value::Bignum(_), FormatType::Free) => result += format!("{}", arg).as_str(),
+                (Value::Rational(r), FormatType::Default(f)) | (Value::Rational(r), FormatType::FixedPoint(f)) => result += Value::format_decimal(&Decimal::rational_at_scale(r, Value::fractional_places(f))?, f)?.as_str(),
+                (Value::Decimal(d), FormatType::Default(f)) | (Value::Decimal(d), FormatType::FixedPoint(f)) => result += Value::format_decimal(&d.rescale(Value::fractional_places(f))?, f)?.as_str(),
+                (Value::Rational(_), FormatType::Double(f)) | (Value::Rational(_), FormatType::Real(f)) |
+                (Value::Decimal(_), FormatType::Double(f)) | (Value::Decimal(_), FormatType::Real(f)) => result += Value::format_float(arg.as_f64()?, false, &f.exponent_symbol.unwrap(), f, workspace)?.as_str(),
+                (Value::Rational(_), FormatType::Free) | (Value::Decimal(_), FormatType::Free) => result += format!("{}", arg).as_str(),
                 (Value::Real(r), FormatType::Default(f)) => result += Value::format_float(*r as f64, false, &'E', f, workspace)?.as_str(),
                 (Value::Real(r), FormatType::Double(f)) => result += Value::format_float(*r as f64, false, &f.exponent_symbol.unwrap(), f, workspace)?.as_str(),
-                (Value::Real(r), FormatType::FixedPoint(f)) => result += Value::format_int(*r as i32, f)?.as_str(),
+                (Value::Real(r), FormatType::FixedPoint(f)) => result += Value::format_int(*r as i64, f)?.as_str(),
                 (Value::Real(r), FormatType::Real(f)) => result += Value::format_float(*r as f64, false, &f.exponent_symbol.unwrap(), f, workspace)?.as_str(),
                 (Value::Real(_), FormatType::Free) => result += format!("{}", arg).as_str(),
                 (Value::Double(d), FormatType::Default(f)) => result += Value::format_float(*d, false, &'D', f, workspace)?.as_str(),
                 (Value::Double(d), FormatType::Double(f)) => result += Value::format_float(*d as f64, false, &f.exponent_symbol.unwrap(), f, workspace)?.as_str(),
-                (Value::Double(d), FormatType::FixedPoint(f)) => result += Value::format_int(*d as i32, f)?.as_str(),
+                (Value::Double(d), FormatType::FixedPoint(f)) => result += Value::format_int(*d as i64, f)?.as_str(),
                 (Value::Double(d), FormatType::Real(f)) => result += Value::format_float(*d as f64, false, &f.exponent_symbol.unwrap(), f, workspace)?.as_str(),
                 (Value::Double(_), FormatType::Free) => result += format!("{}", arg).as_str(),
                 (Value::Sequence(seq), _) => {
                     if seq.as_datatype().as_string().as_str() == "string" {
                         last_format = parser.parse(&*seq.as_values())?;
                     } else {
-                        return Err(format!("Value cannot be formatted"));   
+                        return Err(ppl_error!(Runtime, "Value cannot be formatted"));   
                     }
                 }
-                _ => return Err(format!("Value cannot be formatted")),
+                _ => return Err(ppl_error!(Runtime, "Value cannot be formatted")),
             }
         }
         Ok(result)
     }
 
-    fn format_float(number: f64, is_rounded: bool, exponent_symbol: &char, f: &FormatControl, workspace: &WorkSpace) -> Result<String,String> {
+    fn format_float(number: f64, is_rounded: bool, exponent_symbol: &char, f: &FormatControl, workspace: &WorkSpace) -> Result<String,PplError> {
         let mut result = String::new();
         let mut parse_result = workspace.get_floating_point_parser().parse(number)?.clone();
         let mut exponent = 0;
@@ -134,7 +151,7 @@ impl super::Value {
             if f.integer_zero_suppression_digits > 0 {
                 available_integer_zero_suppression_digits = f.integer_zero_suppression_digits - 1;
             } else {
-                return Err(format!("Format \"{}\" does not support negative numbers", f));
+                return Err(ppl_error!(Runtime, "Format \"{}\" does not support negative numbers", f));
             }
         } else {
             available_integer_zero_suppression_digits = f.integer_zero_suppression_digits;
@@ -163,7 +180,7 @@ impl super::Value {
         }
 
         if integer_digits > (f.integer_non_suppressed_digits + available_integer_zero_suppression_digits) as i32 {
-            return Err(format!("Significant high-order digits lost"));
+            return Err(ppl_error!(Arithmetic, "Significant high-order digits lost"));
         }
 
 
@@ -281,7 +298,7 @@ impl super::Value {
                     result += exponent_string.as_str();
                 },
                 2 => result += exponent_string.as_str(),
-                _ => return Err(format!("Significant high-order digits lost")),
+                _ => return Err(ppl_error!(Arithmetic, "Significant high-order digits lost")),
             }
         }
 
@@ -289,13 +306,46 @@ impl super::Value {
 
     }
 
-    fn format_int(mut number: i32, f: &FormatControl) -> Result<String,String> {
-        let sign = if number < 0 {number = -number; "-"} else {" "};
-        let digits = format!("{}", number);
+    #[cfg(feature = "bignum")]
+    fn format_bignum(number: &num_bigint::BigInt, f: &FormatControl) -> Result<String,PplError> {
+        let sign = if number.sign() == num_bigint::Sign::Minus {"-"} else {" "};
+        let digits = format!("{}", number.magnitude());
         Value::format_internal(sign, digits.as_str(), "", f)
     }
 
-    fn format_internal(sign: &str, int_part: &str, float_part: &str, f: &FormatControl) -> Result<String,String> {
+    //  Rationals and decimals are formatted exactly, after rounding to the number of places the picture shows
+
+    fn format_decimal(number: &Decimal, f: &FormatControl) -> Result<String,PplError> {
+        let sign = if number.units() < 0 {"-"} else {" "};
+        let places = number.scale() as usize;
+        let digits = format!("{:0>width$}", number.units().unsigned_abs(), width = places + 1);
+        let (int_part, float_part) = digits.split_at(digits.len() - places);
+        let mut result = Value::format_internal(sign, int_part.trim_start_matches('0'), "", &FormatControl { decimal_required: false, ..f.clone() })?;
+
+        if f.decimal_required {
+            let trailing_zeroes = (float_part.len() - float_part.trim_end_matches('0').len()).min(f.fractional_zero_suppressed_digits);
+            result += ".";
+            result += &float_part[..float_part.len() - trailing_zeroes];
+            result += &" ".repeat(trailing_zeroes);
+        }
+        Ok(result)
+    }
+
+    fn fractional_places(f: &FormatControl) -> u32 {
+        if f.decimal_required {
+            (f.fractional_non_suppressed_digits + f.fractional_zero_suppressed_digits) as u32
+        } else {
+            0
+        }
+    }
+
+    fn format_int(number: i64, f: &FormatControl) -> Result<String,PplError> {
+        let sign = if number < 0 {"-"} else {" "};
+        let digits = format!("{}", number.unsigned_abs());
+        Value::format_internal(sign, digits.as_str(), "", f)
+    }
+
+    fn format_internal(sign: &str, int_part: &str, float_part: &str, f: &FormatControl) -> Result<String,PplError> {
         let mut int_digits = String::from(int_part);
 
         let available_integer_zero_suppression_digits: usize;
@@ -303,14 +353,14 @@ impl super::Value {
             if f.integer_zero_suppression_digits > 0 {
                 available_integer_zero_suppression_digits = f.integer_zero_suppression_digits - 1;
             } else {
-                return Err(format!("Format \"{}\" does not support negative numbers", f));
+                return Err(ppl_error!(Runtime, "Format \"{}\" does not support negative numbers", f));
             }
         } else {
             available_integer_zero_suppression_digits = f.integer_zero_suppression_digits;
         }
 
         if int_digits.len() > f.integer_non_suppressed_digits + available_integer_zero_suppression_digits {
-            return Err(format!("Significant high-order digits lost"));
+            return Err(ppl_error!(Arithmetic, "Significant high-order digits lost"));
         }
         while int_digits.len() < f.integer_non_suppressed_digits { int_digits = format!("0{}", int_digits); };
 
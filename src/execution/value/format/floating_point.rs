@@ -5,6 +5,7 @@ use std::rc::Rc;
 
 use super::state_machine::{State, Rule};
 use super::ParsedF64;
+use crate::error::PplError;
 
 
 
@@ -201,7 +202,7 @@ impl  Parser {
         }
     }
 
-    pub fn parse(& self, number: f64) -> Result<ParsedF64,String> {
+    pub fn parse(& self, number: f64) -> Result<ParsedF64,PplError> {
         let mut current_state = self.states.last().unwrap().clone();
 
         for c in number.to_string().chars() {
@@ -212,7 +213,7 @@ impl  Parser {
         if current_state.is_stoppable {
             Ok(self.parse_result.borrow().clone())
         } else {
-            Err(format!("Invalid format specification"))
+            Err(ppl_error!(Runtime, "Invalid format specification"))
         }
     }
 }
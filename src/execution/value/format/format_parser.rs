@@ -7,6 +7,7 @@ use crate::execution::value::Cell;
 
 use super::state_machine::{State, Rule};
 use super::{FormatType, FormatControl};
+use crate::error::PplError;
 
 
 
@@ -231,7 +232,7 @@ impl  Parser {
         parser
     }
 
-    pub fn parse(& self, s: &Vec<Rc<RefCell<Cell>>>) -> Result<FormatType,String> {
+    pub fn parse(& self, s: &Vec<Rc<RefCell<Cell>>>) -> Result<FormatType,PplError> {
         *self.format_type.borrow_mut() = FormatType::Default(FormatControl::new());
         *self.format_control.borrow_mut() = FormatControl::new();
         let mut current_state = self.states.last().unwrap().clone();
@@ -249,7 +250,7 @@ impl  Parser {
                 FormatType::Free => Ok(FormatType::Free),
             }
         } else {
-            Err(format!("Invalid format specification"))
+            Err(ppl_error!(Runtime, "Invalid format specification"))
         }
     }
 }
@@ -2,6 +2,7 @@
 
 use std::{cell::RefCell, fmt};
 use std::rc::Rc;
+use crate::error::PplError;
 
 
 
@@ -13,7 +14,7 @@ pub struct State<T> {
 }
 
 impl<T> State<T> {
-    pub fn execute(&self, c: char, parser: &T) -> Result<Rc<State<T>>,String> {
+    pub fn execute(&self, c: char, parser: &T) -> Result<Rc<State<T>>,PplError> {
       //  println!("{}: {}", self.name, c);
         for rule in &*self.rules.borrow() {    
         //    println!("Checking {}", rule);
@@ -25,7 +26,7 @@ impl<T> State<T> {
                 }
             }
         }
-        Err(format!("Invalid format specification"))
+        Err(ppl_error!(Runtime, "Invalid format specification"))
     }
 
     pub fn new(name: &str, is_stoppable: bool, ) -> State<T> {
@@ -44,12 +45,12 @@ impl<T> State<T> {
 #[derive(Debug)]
 pub struct Rule<T> {
     pub matching_characters: Vec<char>,
-    pub action:    fn(&T, char) -> Result<(),String>,
+    pub action:    fn(&T, char) -> Result<(),PplError>,
     pub next_state: Rc<State<T>>,
 }
 
 impl<T> Rule<T> {
-    pub fn new(matching_characters: &[char], action: fn(parser: &T, c: char) -> Result<(),String>, next_state: Rc<State<T>>) -> Rule<T> {
+    pub fn new(matching_characters: &[char], action: fn(parser: &T, c: char) -> Result<(),PplError>, next_state: Rc<State<T>>) -> Rule<T> {
         Rule { matching_characters: matching_characters.to_vec(), action: action, next_state: next_state.clone() }
     }
 }
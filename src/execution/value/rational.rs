@@ -0,0 +1,146 @@
+//  This module implements the exact fractions held by the rational datatype
+
+use std::{cmp::Ordering, fmt, str::FromStr};
+
+use super::decimal::Decimal;
+use crate::error::PplError;
+
+
+//  A rational is kept in lowest terms with a positive denominator, so equal rationals have equal fields.  The
+//  arithmetic is done in i128, where no product of two i64s can overflow, and only the reduced result has to fit.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Rational {
+    numerator: i64,
+    denominator: i64,
+}
+
+impl Rational {
+    pub fn new(numerator: i128, denominator: i128) -> Result<Rational,PplError> {
+        if denominator == 0 {
+            return Err(ppl_error!(Arithmetic, "Division by zero"));
+        }
+        let divisor = gcd(numerator.unsigned_abs(), denominator.unsigned_abs()) as i128;
+        let sign = denominator.signum();
+        match (i64::try_from(sign * (numerator / divisor)), i64::try_from(sign * (denominator / divisor))) {
+            (Ok(numerator), Ok(denominator)) => Ok(Rational { numerator, denominator }),
+            _ => Err(ppl_error!(Arithmetic, "Integer overflow in rational arithmetic")),
+        }
+    }
+
+    pub fn from_integer(value: i64) -> Rational {
+        Rational { numerator: value, denominator: 1 }
+    }
+
+    //  A float is taken at its shortest decimal value, so that 0.1 is 1/10 rather than the binary fraction nearest it
+
+    pub fn from_f64(value: f64) -> Result<Rational,PplError> {
+        Rational::try_from(&Decimal::from_f64(value)?)
+    }
+
+    pub fn numerator(&self) -> i64 {
+        self.numerator
+    }
+
+    pub fn denominator(&self) -> i64 {
+        self.denominator
+    }
+
+    pub fn is_integer(&self) -> bool {
+        self.denominator == 1
+    }
+
+    pub fn checked_add(&self, other: &Rational) -> Result<Rational,PplError> {
+        Rational::new(self.numerator as i128 * other.denominator as i128 + other.numerator as i128 * self.denominator as i128,
+                      self.denominator as i128 * other.denominator as i128)
+    }
+
+    pub fn checked_div(&self, other: &Rational) -> Result<Rational,PplError> {
+        Rational::new(self.numerator as i128 * other.denominator as i128, self.denominator as i128 * other.numerator as i128)
+    }
+
+    pub fn checked_mul(&self, other: &Rational) -> Result<Rational,PplError> {
+        Rational::new(self.numerator as i128 * other.numerator as i128, self.denominator as i128 * other.denominator as i128)
+    }
+
+    pub fn checked_neg(&self) -> Result<Rational,PplError> {
+        Rational::new(-(self.numerator as i128), self.denominator as i128)
+    }
+
+    pub fn checked_sub(&self, other: &Rational) -> Result<Rational,PplError> {
+        self.checked_add(&other.checked_neg()?)
+    }
+
+    pub fn pow(&self, exponent: i64) -> Result<Rational,PplError> {
+        let base = if exponent < 0 { Rational::new(self.denominator as i128, self.numerator as i128)? } else { *self };
+        let overflow = || ppl_error!(Arithmetic, "Integer overflow in rational arithmetic");
+        let exponent = u32::try_from(exponent.unsigned_abs()).map_err(|_| overflow())?;
+        match (base.numerator.checked_pow(exponent), base.denominator.checked_pow(exponent)) {
+            (Some(numerator), Some(denominator)) => Ok(Rational { numerator, denominator }),
+            _ => Err(overflow()),
+        }
+    }
+
+    pub fn to_f64(&self) -> f64 {
+        self.numerator as f64 / self.denominator as f64
+    }
+
+    //  The integer part, truncated toward zero like a float converted to an int
+
+    pub fn trunc(&self) -> i64 {
+        self.numerator / self.denominator
+    }
+}
+
+impl fmt::Display for Rational {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.denominator == 1 {
+            write!(f, "{}", self.numerator)
+        } else {
+            write!(f, "{}/{}", self.numerator, self.denominator)
+        }
+    }
+}
+
+impl Ord for Rational {
+    fn cmp(&self, other: &Rational) -> Ordering {
+        (self.numerator as i128 * other.denominator as i128).cmp(&(other.numerator as i128 * self.denominator as i128))
+    }
+}
+
+impl PartialOrd for Rational {
+    fn partial_cmp(&self, other: &Rational) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+//  "n/d", or a decimal number such as "-12.50"
+
+impl FromStr for Rational {
+    type Err = PplError;
+
+    fn from_str(s: &str) -> Result<Rational,PplError> {
+        match s.split_once('/') {
+            Some((numerator, denominator)) => {
+                let parse = |part: &str| part.trim().parse::<i64>().map_err(|_| ppl_error!(Type, "{} is not a rational number", s));
+                Rational::new(parse(numerator)? as i128, parse(denominator)? as i128)
+            },
+            None => Rational::try_from(&s.parse::<Decimal>()?),
+        }
+    }
+}
+
+impl TryFrom<&Decimal> for Rational {
+    type Error = PplError;
+
+    fn try_from(value: &Decimal) -> Result<Rational,PplError> {
+        Rational::new(value.units(), 10i128.pow(value.scale()))
+    }
+}
+
+fn gcd(mut a: u128, mut b: u128) -> u128 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a.max(1)
+}
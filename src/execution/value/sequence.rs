@@ -10,6 +10,7 @@ use crate::{workspace::WorkSpace,
 
 use super::recursion_detector::Cycle;
 use super::{Cell, Value};
+use crate::error::PplError;
 
 
 pub struct SequenceInstance {
@@ -52,13 +53,13 @@ impl Clone for SequenceInstance {
 }
 
 impl SequenceInstance {
-    pub fn access_cell_by_value(&self, index: i32, workspace: &WorkSpace) -> Result<(),String> {
+    pub fn access_cell_by_value(&self, index: i32, workspace: &WorkSpace) -> Result<(),PplError> {
         let value = self.values.borrow()[self.check_and_normalize_index(index)?].borrow().contents.value.borrow().clone();
         workspace.push_value(&value);
         Ok(())
     }
 
-    pub fn access_cell_by_reference(&self, index: i32, workspace: &WorkSpace) -> Result<(),String> {
+    pub fn access_cell_by_reference(&self, index: i32, workspace: &WorkSpace) -> Result<(),PplError> {
         let normalized_index = self.check_and_normalize_index(index)?;
         let my_rootdatatype = workspace.resolve_datatype(&self.as_datatype().as_string())?;
         if let RootDataType::Sequence(seq) = my_rootdatatype {
@@ -78,16 +79,16 @@ impl SequenceInstance {
         self.values.borrow()
     }
 
-    pub fn check_and_normalize_index(&self, index: i32) -> Result<usize,String> {
+    pub fn check_and_normalize_index(&self, index: i32) -> Result<usize,PplError> {
         let upper_bound = self.lower_bound + (self.values.borrow().len() as i32) - 1;
         if index >= self.lower_bound && index <= upper_bound {
             return Ok((index - self.lower_bound) as usize);
         } else {
-            Err(format!("{} is out of bounds", index))
+            Err(ppl_error!(Index, "{} is out of bounds", index))
         }
     }
 
-    pub fn compare(&self, other: &SequenceInstance, workspace: &WorkSpace) -> Result<i32,String> {
+    pub fn compare(&self, other: &SequenceInstance, workspace: &WorkSpace) -> Result<i32,PplError> {
         let my_values = &*self.values.borrow();
         let other_values = &*other.values.borrow();
         let mut my_iter = my_values.iter();
@@ -112,6 +113,35 @@ impl SequenceInstance {
                                 return Ok(1);
                             }
                         },
+                        RootDataType::Int64 => {
+                            if my_item.borrow().as_contents().value.borrow().as_i64()? < other_item.borrow().as_contents().value.borrow().as_i64()? {
+                                return Ok(-1);
+                            } else if my_item.borrow().as_contents().value.borrow().as_i64()? > other_item.borrow().as_contents().value.borrow().as_i64()? {
+                                return Ok(1);
+                            }
+                        },
+                        #[cfg(feature = "bignum")]
+                        RootDataType::Bignum => {
+                            if my_item.borrow().as_contents().value.borrow().as_bignum()? < other_item.borrow().as_contents().value.borrow().as_bignum()? {
+                                return Ok(-1);
+                            } else if my_item.borrow().as_contents().value.borrow().as_bignum()? > other_item.borrow().as_contents().value.borrow().as_bignum()? {
+                                return Ok(1);
+                            }
+                        },
+                        RootDataType::Rational => {
+                            if my_item.borrow().as_contents().value.borrow().as_rational()? < other_item.borrow().as_contents().value.borrow().as_rational()? {
+                                return Ok(-1);
+                            } else if my_item.borrow().as_contents().value.borrow().as_rational()? > other_item.borrow().as_contents().value.borrow().as_rational()? {
+                                return Ok(1);
+                            }
+                        },
+                        RootDataType::Decimal => {
+                            if my_item.borrow().as_contents().value.borrow().as_decimal()? < other_item.borrow().as_contents().value.borrow().as_decimal()? {
+                                return Ok(-1);
+                            } else if my_item.borrow().as_contents().value.borrow().as_decimal()? > other_item.borrow().as_contents().value.borrow().as_decimal()? {
+                                return Ok(1);
+                            }
+                        },
                         RootDataType::Real => {
                             if my_item.borrow().as_contents().value.borrow().as_f32()? < other_item.borrow().as_contents().value.borrow().as_f32()? {
                                 return Ok(-1);
@@ -142,10 +172,10 @@ impl SequenceInstance {
                                         return Ok(result);
                                     }
                                 } else {
-                                    return Err(format!("Invalid comparison"));
+                                    return Err(ppl_error!(Type, "Invalid comparison"));
                                 }
                             } else {
-                                return Err(format!("Invalid comparison"));
+                                return Err(ppl_error!(Type, "Invalid comparison"));
                             }
                         },
                         RootDataType::Sequence(_) => {
@@ -156,13 +186,13 @@ impl SequenceInstance {
                                         return Ok(result);
                                     }
                                 } else {
-                                    return Err(format!("Invalid comparison"));
+                                    return Err(ppl_error!(Type, "Invalid comparison"));
                                 }
                             } else {
-                                return Err(format!("Invalid comparison"));
+                                return Err(ppl_error!(Type, "Invalid comparison"));
                             }
                         },
-                        _ => return Err(format!("Invalid comparison")),
+                        _ => return Err(ppl_error!(Type, "Invalid comparison")),
                     }
                 },
                 (None, Some(_)) => return Ok(-1),
@@ -172,7 +202,7 @@ impl SequenceInstance {
         }
     }
 
-    pub fn concat(left: &SequenceInstance, right: &SequenceInstance) -> Result<Value,String> {
+    pub fn concat(left: &SequenceInstance, right: &SequenceInstance) -> Result<Value,PplError> {
         let mut catenated_values = Vec::new();
         for v in &*left.values.borrow() {
             catenated_values.push(Cell::new(v.borrow().as_ref_to_value().clone()));
@@ -187,7 +217,7 @@ impl SequenceInstance {
             values: RefCell::new(catenated_values) }))
     }
 
-    pub fn construct(sequence_datatype: &Rc<MetaDataType>, args: &Vec<Value>, workspace: &WorkSpace) -> Result<Value,String> {
+    pub fn construct(sequence_datatype: &Rc<MetaDataType>, args: &Vec<Value>, workspace: &WorkSpace) -> Result<Value,PplError> {
   
         //  Get the sequence's member type and validate the values are compatible with it
     
@@ -196,11 +226,11 @@ impl SequenceInstance {
         let sequence_root_datatype = sequence_datatype.root_data_type();
         if let RootDataType::Sequence(ref seq) = sequence_root_datatype {
             if seq.upper_index_bound.is_some() && args.len() != (seq.upper_index_bound.unwrap() - seq.lower_index_bound + 1) as usize {
-                return Err(format!("{} requires {} elements", sequence_datatype.as_string(), seq.upper_index_bound.unwrap() - seq.lower_index_bound + 1));
+                return Err(ppl_error!(Argument, "{} requires {} elements", sequence_datatype.as_string(), seq.upper_index_bound.unwrap() - seq.lower_index_bound + 1));
             }
             let opt_member = workspace.try_get_datatype(&seq.member_type.as_string().as_str());
             if opt_member.is_none() {
-                return Err(format!("Datatype {} is no longer defined", seq.member_type.as_string()));
+                return Err(ppl_error!(Name, "Datatype {} is no longer defined", seq.member_type.as_string()));
             }
             let member = opt_member.unwrap();
             let member_datatype = member.root_data_type();
@@ -235,12 +265,12 @@ impl SequenceInstance {
             values: RefCell::new(cells) }
     }
 
-/*     pub fn get_reference_to_cell(&self, index: i32) -> Result<Value,String> {
+/*     pub fn get_reference_to_cell(&self, index: i32) -> Result<Value,PplError> {
         let normalized_index = self.check_and_normalize_index(index)?;
         Ok(Value::ValueByReference(self.values.borrow()[normalized_index].clone()))
     }
  */
-    pub fn make(seq: &MetaSequence, count: i32, value: &Value, workspace: &WorkSpace) -> Result<Value,String> {
+    pub fn make(seq: &MetaSequence, count: i32, value: &Value, workspace: &WorkSpace) -> Result<Value,PplError> {
         let member_datatype = workspace.resolve_datatype(&seq.member_type.as_string())?;
         let member_value = member_datatype.coerce(value, workspace)?;
         let mut sequence_values = Vec::new();
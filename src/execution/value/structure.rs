@@ -7,6 +7,7 @@ use std::rc::Rc;
 use crate::{workspace::WorkSpace, symbols::{datatype::{RootDataType, strongest_datatype}, metadata::{MetaDataTypeName, MetaDataType, SelectorDescription}, name::Name}};
 
 use super::{Cell, Value, recursion_detector::Cycle};
+use crate::error::PplError;
 
 
 #[derive(Clone)]
@@ -46,6 +47,13 @@ impl StructureInstance {
     pub fn length(&self) -> i32 {
         self.members.len() as i32
     }
+
+    pub fn member(&self, name: &str) -> Result<Value,PplError> {
+        match self.members.iter().find(|member| member.name.as_str() == name) {
+            Some(member) => Ok(member.as_value()),
+            None => Err(ppl_error!(Index, "{} has no member {}", self.datatype.as_string(), name)),
+        }
+    }
 }
 
 pub struct StructureInstanceMember {
@@ -65,6 +73,14 @@ impl Clone for StructureInstanceMember {
 }
 
 impl StructureInstanceMember {
+    pub fn new(name: &str, datatype: &str, value: Value) -> StructureInstanceMember {
+        StructureInstanceMember { name: Name::from_str(name), datatype: MetaDataTypeName::from_str(datatype), cell: super::Cell::new(value) }
+    }
+
+    pub fn as_contents(&self) -> Rc<super::ValueEnvelope> {
+        self.cell.borrow().as_contents()
+    }
+
     pub fn as_string(&self) -> String {
         self.name.as_string()
     }
@@ -81,7 +97,7 @@ impl StructureInstanceMember {
 
 
 impl StructureInstance {
-    pub fn access_field_by_value(&self, selector: &SelectorInstance, workspace: &WorkSpace) -> Result<(),String> {
+    pub fn access_field_by_value(&self, selector: &SelectorInstance, workspace: &WorkSpace) -> Result<(),PplError> {
         for target in &selector.structures {
             if target.as_string() == self.as_string() {
                 for member in &self.members {
@@ -92,10 +108,10 @@ impl StructureInstance {
                 }
             }
         }
-        Err(format!("{} does not have a field {}", self.as_string(), selector.member.as_string()))
+        Err(ppl_error!(Index, "{} does not have a field {}", self.as_string(), selector.member.as_string()))
     }
 
-    pub fn access_field_by_reference(&self, selector: &SelectorInstance, workspace: &WorkSpace) -> Result<(),String> {
+    pub fn access_field_by_reference(&self, selector: &SelectorInstance, workspace: &WorkSpace) -> Result<(),PplError> {
         for target in &selector.structures {
             if target.as_string() == self.as_string() {
                 for member in &self.members {
@@ -107,7 +123,7 @@ impl StructureInstance {
                 }
             }
         }
-        Err(format!("{} does not have a field {}", self.as_string(), selector.member.as_string()))
+        Err(ppl_error!(Index, "{} does not have a field {}", self.as_string(), selector.member.as_string()))
     }
 
     pub fn as_string(&self) -> String {
@@ -118,7 +134,7 @@ impl StructureInstance {
         &self.members
     }
 
-    pub fn compare(&self, other: &StructureInstance, workspace: &WorkSpace) -> Result<i32,String> {
+    pub fn compare(&self, other: &StructureInstance, workspace: &WorkSpace) -> Result<i32,PplError> {
         let mut my_iter = self.members.iter();
         let mut other_iter = other.members.iter();
 
@@ -141,6 +157,35 @@ impl StructureInstance {
                                 return Ok(1);
                             }
                         },
+                        RootDataType::Int64 => {
+                            if my_item.cell.borrow().as_contents().value.borrow().as_i64()? < other_item.cell.borrow().as_contents().value.borrow().as_i64()? {
+                                return Ok(-1);
+                            } else if my_item.cell.borrow().as_contents().value.borrow().as_i64()? > other_item.cell.borrow().as_contents().value.borrow().as_i64()? {
+                                return Ok(1);
+                            }
+                        },
+                        #[cfg(feature = "bignum")]
+                        RootDataType::Bignum => {
+                            if my_item.cell.borrow().as_contents().value.borrow().as_bignum()? < other_item.cell.borrow().as_contents().value.borrow().as_bignum()? {
+                                return Ok(-1);
+                            } else if my_item.cell.borrow().as_contents().value.borrow().as_bignum()? > other_item.cell.borrow().as_contents().value.borrow().as_bignum()? {
+                                return Ok(1);
+                            }
+                        },
+                        RootDataType::Rational => {
+                            if my_item.cell.borrow().as_contents().value.borrow().as_rational()? < other_item.cell.borrow().as_contents().value.borrow().as_rational()? {
+                                return Ok(-1);
+                            } else if my_item.cell.borrow().as_contents().value.borrow().as_rational()? > other_item.cell.borrow().as_contents().value.borrow().as_rational()? {
+                                return Ok(1);
+                            }
+                        },
+                        RootDataType::Decimal => {
+                            if my_item.cell.borrow().as_contents().value.borrow().as_decimal()? < other_item.cell.borrow().as_contents().value.borrow().as_decimal()? {
+                                return Ok(-1);
+                            } else if my_item.cell.borrow().as_contents().value.borrow().as_decimal()? > other_item.cell.borrow().as_contents().value.borrow().as_decimal()? {
+                                return Ok(1);
+                            }
+                        },
                         RootDataType::Real => {
                             if my_item.cell.borrow().as_contents().value.borrow().as_f32()? < other_item.cell.borrow().as_contents().value.borrow().as_f32()? {
                                 return Ok(-1);
@@ -171,10 +216,10 @@ impl StructureInstance {
                                         return Ok(result);
                                     }
                                 } else {
-                                    return Err(format!("Invalid comparison"));
+                                    return Err(ppl_error!(Type, "Invalid comparison"));
                                 }
                             } else {
-                                return Err(format!("Invalid comparison"));
+                                return Err(ppl_error!(Type, "Invalid comparison"));
                             }
                         },
                         RootDataType::Sequence(_) => {
@@ -185,13 +230,13 @@ impl StructureInstance {
                                         return Ok(result);
                                     }
                                 } else {
-                                    return Err(format!("Invalid comparison"));
+                                    return Err(ppl_error!(Type, "Invalid comparison"));
                                 }
                             } else {
-                                return Err(format!("Invalid comparison"));
+                                return Err(ppl_error!(Type, "Invalid comparison"));
                             }
                         },
-                        _ => return Err(format!("Invalid comparison")),
+                        _ => return Err(ppl_error!(Type, "Invalid comparison")),
                     }
                 },
                 (None, Some(_)) => return Ok(-1),
@@ -201,21 +246,21 @@ impl StructureInstance {
         }
     }
 
-    pub fn construct(structure_datatype: &Rc<MetaDataType>, args: &Vec<Value>, workspace: &WorkSpace) -> Result<Value,String> {
+    pub fn construct(structure_datatype: &Rc<MetaDataType>, args: &Vec<Value>, workspace: &WorkSpace) -> Result<Value,PplError> {
   
         //  Get the structure's member types and validate the values are compatible
     
         let mut instance_fields = Vec::new();
         if let RootDataType::Structure(meta_structure) = structure_datatype.root_data_type() {
             if meta_structure.members.len() != args.len() {
-                return Err(format!("{} has {} members, but only {} values were provided", structure_datatype.as_string(), meta_structure.members.len(), args.len()));
+                return Err(ppl_error!(Argument, "{} has {} members, but only {} values were provided", structure_datatype.as_string(), meta_structure.members.len(), args.len()));
             }
     
             for i in 0..meta_structure.members.len() {
                 let member = &meta_structure.members[i];
                 let opt_member_datatype = workspace.try_get_datatype(&member.data_type.as_string().as_str());
                 if opt_member_datatype.is_none() {
-                    return Err(format!("Datatype {} is no longer defined", member.name.as_string()));
+                    return Err(ppl_error!(Name, "Datatype {} is no longer defined", member.name.as_string()));
                 }
                 let member_datatype = opt_member_datatype.unwrap();
                 let member_datatype_name = member_datatype.as_name().as_string();
@@ -228,7 +273,7 @@ impl StructureInstance {
     
             Ok(Value::Structure(StructureInstance::from_string(&structure_datatype.as_string(), instance_fields)))
         } else {
-            Err(format!("internal error"))
+            Err(ppl_error!(Runtime, "internal error"))
         }
     }
 
@@ -6,7 +6,11 @@ use std::char;
 use std::fmt;
 use context::Context;
 
+#[cfg(feature = "bignum")]
+use num_bigint::BigInt;
+
 use crate::workspace::WorkSpace;
+use crate::error::PplError;
 
 
 pub type LineNumber = u32;
@@ -42,8 +46,12 @@ impl fmt::Debug for Token {
 pub enum TokenType {
     Punctuation(String),
     Integer(i32),
+    Integer64(i64),
+    #[cfg(feature = "bignum")]
+    BigInteger(BigInt),
     Double(f64),
     Real(f32),
+    Imaginary(f32),
     Identifier(String),
     Operator(String),
     Character(char),
@@ -58,8 +66,12 @@ impl fmt::Debug for TokenType {
         match self {
             TokenType::Punctuation(p) => write!(f, "Punctuation(\"{}\")", p),
             TokenType::Integer(i) => write!(f, "Int({})", i),
+            TokenType::Integer64(i) => write!(f, "Int64({})", i),
+            #[cfg(feature = "bignum")]
+            TokenType::BigInteger(i) => write!(f, "Bignum({})", i),
             TokenType::Double(d) => write!(f, "Dbl({})", d),
             TokenType::Real(r) => write!(f, "Real({})", r),
+            TokenType::Imaginary(r) => write!(f, "Imaginary({})", r),
             TokenType::Identifier(id) => write!(f, "Identifier({})", id),
             TokenType::Operator(op) => write!(f, "Operator(\"{}\")", op),
             TokenType::Character(c) => write!(f, "Char('{}')", c),
@@ -76,8 +88,12 @@ impl fmt::Display for TokenType {
         match self {
             TokenType::Punctuation(p) => writeln!(fmt, "Punctuation: {}", p),
             TokenType::Integer(v) => writeln!(fmt, "Integer: {}", v),
+            TokenType::Integer64(v) => writeln!(fmt, "Integer64: {}", v),
+            #[cfg(feature = "bignum")]
+            TokenType::BigInteger(v) => writeln!(fmt, "BigInteger: {}", v),
             TokenType::Real(v) => writeln!(fmt, "Real: {}", v),
             TokenType::Double(v) => writeln!(fmt, "Double: {}", v),
+            TokenType::Imaginary(v) => writeln!(fmt, "Imaginary: {}", v),
             TokenType::Identifier(i) => writeln!(fmt, "Identifier: {}", i),
             TokenType::Operator(op) => writeln!(fmt, "Operator: {}", op),
             TokenType::Character(c) => writeln!(fmt, "Character: {}", c),
@@ -162,7 +178,7 @@ pub struct  Lexer<'a,'b: 'a> {
 }
 
 impl<'a,'b: 'a> Lexer<'a,'b> {
-    fn character(&self, tokens: &mut Vec<Token>) -> Result<bool, String> { 
+    fn character(&self, tokens: &mut Vec<Token>) -> Result<bool,PplError> { 
         self.context.literal_next();
         match self.context.get_current_character_class() {
             CharacterClass::Eos => {
@@ -180,7 +196,7 @@ impl<'a,'b: 'a> Lexer<'a,'b> {
         }
     }
 
-    fn comment(&self, tokens: &mut Vec<Token>) -> Result<bool, String> {
+    fn comment(&self, tokens: &mut Vec<Token>) -> Result<bool,PplError> {
         loop {
             match self.context.get_current_character_class() {
                 CharacterClass::Newline => {
@@ -203,7 +219,7 @@ impl<'a,'b: 'a> Lexer<'a,'b> {
         return Ok(true);
     }
 
-    fn dot(&self, tokens: &mut Vec<Token>) -> Result<bool, String> {
+    fn dot(&self, tokens: &mut Vec<Token>) -> Result<bool,PplError> {
         match self.context.peek() {
             CharacterClass::Numeric => {
                 self.context.clear_number();
@@ -216,22 +232,11 @@ impl<'a,'b: 'a> Lexer<'a,'b> {
         }
     }
 
-    fn error(&self, message: &str) -> Result<bool, String> {
-        let current_position = self.context.get_current_position();
-        let mut target_line =  self.context.source_string;
-        let mut remaining_lines =  current_position.line_number;
-        for source_line in self.context.source_string.lines() {
-            target_line = source_line;
-            remaining_lines -= 1;
-            if remaining_lines == 0 {
-                break;
-            }
-        }
-        let column = if current_position.column > 0 {current_position.column - 1} else { 0 };
-        Err(format!("\n{}\n{}^\n{}", target_line, " ".repeat(column as usize), message))
+    fn error(&self, message: &str) -> Result<bool,PplError> {
+        Err(PplError::syntax(self.context.source_string, &self.context.get_current_position(), message))
     }
 
-    fn exponent(&self, tokens: &mut Vec<Token>) -> Result<bool, String> {
+    fn exponent(&self, tokens: &mut Vec<Token>) -> Result<bool,PplError> {
         match self.context.get_current_character_class() {
             CharacterClass::Numeric => {
                 return self.exponent_value(tokens);
@@ -251,7 +256,7 @@ impl<'a,'b: 'a> Lexer<'a,'b> {
         }
     }
 
-    fn exponent_value(&self, tokens: &mut Vec<Token>) -> Result<bool, String> {
+    fn exponent_value(&self, tokens: &mut Vec<Token>) -> Result<bool,PplError> {
         match self.context.get_current_character_class() {
             CharacterClass::Numeric => {
                 self.context.add_exponential_digit()?;
@@ -268,6 +273,7 @@ impl<'a,'b: 'a> Lexer<'a,'b> {
                     self.context.add_exponential_digit()?;
                     self.context.next();
                 },
+                _ if self.is_imaginary_suffix() => return self.push_imaginary_token(tokens),
                 _ => {
                     self.push_float_token(tokens);
                     return Ok(true);
@@ -276,7 +282,7 @@ impl<'a,'b: 'a> Lexer<'a,'b> {
         }
     }
 
-    fn fraction(&self, tokens: &mut Vec<Token>) -> Result<bool, String> {
+    fn fraction(&self, tokens: &mut Vec<Token>) -> Result<bool,PplError> {
         loop {
             match self.context.get_current_character_class() {
                 CharacterClass::Alpha => {
@@ -290,6 +296,7 @@ impl<'a,'b: 'a> Lexer<'a,'b> {
                             self.context.next();
                             return self.exponent(tokens);
                         },
+                        _ if self.is_imaginary_suffix() => return self.push_imaginary_token(tokens),
                         _ => {
                             self.push_float_token(tokens);
                             return Ok(true);
@@ -309,7 +316,7 @@ impl<'a,'b: 'a> Lexer<'a,'b> {
 
     }
 
-    fn identifier(&self, tokens: &mut Vec<Token>) -> Result<bool, String> {
+    fn identifier(&self, tokens: &mut Vec<Token>) -> Result<bool,PplError> {
         loop {
             match self.context.get_current_character_class() {
                 CharacterClass::Alpha | CharacterClass::Numeric => {
@@ -340,7 +347,7 @@ impl<'a,'b: 'a> Lexer<'a,'b> {
         }
     }
 
-    fn integer(&self, tokens: &mut Vec<Token>) -> Result<bool, String> {
+    fn integer(&self, tokens: &mut Vec<Token>) -> Result<bool,PplError> {
         self.context.clear_number();
         loop {
             match self.context.get_current_character_class() {
@@ -355,13 +362,8 @@ impl<'a,'b: 'a> Lexer<'a,'b> {
                             self.context.next();
                             return self.exponent(tokens);
                         },
-                        _ => {
-                            tokens.push(Token { 
-                                token_type: TokenType::Integer(self.context.get_integer_value() as i32), 
-                                string_value: self.context.get_lexeme_source(),
-                                starting_position: self.context.get_starting_position() });
-                            return Ok(true);
-                        },
+                        _ if self.is_imaginary_suffix() => return self.push_imaginary_token(tokens),
+                        _ => return self.push_integer_token(tokens),
                     }
                 }
                 CharacterClass::Numeric => {
@@ -372,21 +374,63 @@ impl<'a,'b: 'a> Lexer<'a,'b> {
                     self.context.next();
                     return self.fraction(tokens);
                 },
-                _ => {
-                    if self.context.get_integer_value() > i32::MAX as i64 || self.context.get_integer_value() < i32::MIN as i64 {
-                        return Err(format!("integer overflow"));
-                    }
-                    tokens.push(Token { 
-                        token_type: TokenType::Integer(self.context.get_integer_value() as i32), 
-                        string_value: self.context.get_lexeme_source(),
-                        starting_position: self.context.get_starting_position() });
-                    return Ok(true);
-                },
+                _ => return self.push_integer_token(tokens),
             }
         }
     }
 
-    fn newline(&self, tokens: &mut Vec<Token>) -> Result<bool, String> { 
+    //  A number written with an i after it, as in 2.5i, is imaginary, unless the i begins a name
+
+    fn is_imaginary_suffix(&self) -> bool {
+        matches!(self.context.get_current_character(), 'i' | 'I') &&
+            !matches!(self.context.peek(), CharacterClass::Alpha | CharacterClass::Numeric)
+    }
+
+    //  The parts of a complex value are reals, so a number that would be a dbl (with a D exponent, or more digits
+    //  than a real holds) can't be made imaginary without losing what made it one
+
+    fn push_imaginary_token(&self, tokens: &mut Vec<Token>) -> Result<bool,PplError> {
+        if self.context.is_double() {
+            return Err(ppl_error!(Syntax, "{}i is a dbl, and the parts of a complex value are reals", self.context.get_lexeme_source()));
+        }
+        let digits = self.context.get_lexeme_source().replace(['d', 'D'], "e");
+        let value = digits.parse::<f64>().unwrap_or_else(|_| self.context.get_float_value());
+        self.context.next();
+        tokens.push(Token { 
+            token_type: TokenType::Imaginary(value as f32), 
+            string_value: self.context.get_lexeme_source(),
+            starting_position: self.context.get_starting_position() });
+        Ok(true)
+    }
+
+    //  An integer too large for an int is an int64, and with the bignum feature, one too large for that is a bignum
+
+    fn push_integer_token(&self, tokens: &mut Vec<Token>) -> Result<bool,PplError> {
+        let value = self.context.get_integer_value();
+        let token_type = match i32::try_from(value) {
+            _ if self.context.is_big() => self.big_integer()?,
+            Ok(int) => TokenType::Integer(int),
+            Err(_) => TokenType::Integer64(value),
+        };
+        tokens.push(Token { 
+            token_type, 
+            string_value: self.context.get_lexeme_source(),
+            starting_position: self.context.get_starting_position() });
+        Ok(true)
+    }
+
+    #[cfg(feature = "bignum")]
+    fn big_integer(&self) -> Result<TokenType,PplError> {
+        let source = self.context.get_lexeme_source();
+        source.parse::<BigInt>().map(TokenType::BigInteger).map_err(|_| ppl_error!(Syntax, "{} is not an integer", source))
+    }
+
+    #[cfg(not(feature = "bignum"))]
+    fn big_integer(&self) -> Result<TokenType,PplError> {
+        Err(ppl_error!(Syntax, "integer overflow"))
+    }
+
+    fn newline(&self, tokens: &mut Vec<Token>) -> Result<bool,PplError> { 
         self.context.next();
         let p = self.context.get_lexeme_source();
         tokens.push(Token { 
@@ -396,7 +440,7 @@ impl<'a,'b: 'a> Lexer<'a,'b> {
         return Ok(true);
     }
 
-    fn operator(&self, tokens: &mut Vec<Token>) -> Result<bool, String> {
+    fn operator(&self, tokens: &mut Vec<Token>) -> Result<bool,PplError> {
 
         //  The current character is an operator. If it's composable (in the set of operator characters that can be used to form
         //  custom user-defined operators), then we check until we find a non-composable character.  Then we look at the set of known
@@ -453,7 +497,9 @@ impl<'a,'b: 'a> Lexer<'a,'b> {
 
             let current_token_class: PostProcessTokenClass;
             match token.token_type {
-                TokenType::Real(_) | TokenType::Double(_) | TokenType::Integer(_)=> current_token_class = PostProcessTokenClass::Number,
+                TokenType::Real(_) | TokenType::Double(_) | TokenType::Imaginary(_) | TokenType::Integer(_) | TokenType::Integer64(_) => current_token_class = PostProcessTokenClass::Number,
+                #[cfg(feature = "bignum")]
+                TokenType::BigInteger(_) => current_token_class = PostProcessTokenClass::Number,
                 TokenType::Operator(ref op) => {
                     match op.as_str() {
                         "-" => current_token_class = PostProcessTokenClass::Minus,
@@ -489,6 +535,13 @@ impl<'a,'b: 'a> Lexer<'a,'b> {
                                 string_value: (format!("-{}", &token.string_value)),
                                 starting_position: token.starting_position.clone() });
                         },
+                        TokenType::Imaginary(float) => {
+                            processed_tokens.pop();
+                            processed_tokens.push(Token { 
+                                token_type: TokenType::Imaginary(-float), 
+                                string_value: (format!("-{}", &token.string_value)),
+                                starting_position: token.starting_position.clone() });
+                        },
                         TokenType::Integer(int) => {
                             processed_tokens.pop();
                             processed_tokens.push(Token { 
@@ -496,6 +549,24 @@ impl<'a,'b: 'a> Lexer<'a,'b> {
                                 string_value: (format!("-{}", &token.string_value)),
                                 starting_position: token.starting_position.clone() });
                         },
+
+                        //  Negating can bring the smallest int64 (or int) back in range of the narrower type
+
+                        TokenType::Integer64(int) => {
+                            processed_tokens.pop();
+                            processed_tokens.push(Token { 
+                                token_type: i32::try_from(-int).map_or(TokenType::Integer64(-int), TokenType::Integer), 
+                                string_value: (format!("-{}", &token.string_value)),
+                                starting_position: token.starting_position.clone() });
+                        },
+                        #[cfg(feature = "bignum")]
+                        TokenType::BigInteger(int) => {
+                            processed_tokens.pop();
+                            processed_tokens.push(Token { 
+                                token_type: i64::try_from(-int).map_or(TokenType::BigInteger(-int), TokenType::Integer64), 
+                                string_value: (format!("-{}", &token.string_value)),
+                                starting_position: token.starting_position.clone() });
+                        },
                         _ => processed_tokens.push(token.clone()),
                     }
                     penultimate_token_class = PostProcessTokenClass::Other;
@@ -545,7 +616,7 @@ impl<'a,'b: 'a> Lexer<'a,'b> {
         processed_tokens
     }
 
-    fn punctuation(&self, tokens: &mut Vec<Token>) -> Result<bool, String> {
+    fn punctuation(&self, tokens: &mut Vec<Token>) -> Result<bool,PplError> {
         self.context.next();
         let p = self.context.get_lexeme_source();
         tokens.push(Token { 
@@ -556,20 +627,26 @@ impl<'a,'b: 'a> Lexer<'a,'b> {
     }
 
     fn push_float_token(&self, tokens: &mut Vec<Token>) {
+
+        //  Let the standard library do the decimal conversion when it can, since it rounds correctly and
+        //  accumulating the digits doesn't (which matters for saved workspaces reading back the same numbers)
+
+        let value = self.context.get_lexeme_source().replace(['d', 'D'], "e").parse::<f64>()
+            .unwrap_or_else(|_| self.context.get_float_value());
         if self.context.is_double() {
             tokens.push(Token { 
-                token_type: TokenType::Double(self.context.get_float_value()), 
+                token_type: TokenType::Double(value), 
                 string_value: self.context.get_lexeme_source(),
                 starting_position: self.context.get_starting_position() });
         } else {
             tokens.push(Token { 
-                token_type: TokenType::Real(self.context.get_float_value() as f32), 
+                token_type: TokenType::Real(value as f32), 
                 string_value: self.context.get_lexeme_source(),
                 starting_position: self.context.get_starting_position() });
         }
     }
 
-    fn string(&self, tokens: &mut Vec<Token>) -> Result<bool, String> {
+    fn string(&self, tokens: &mut Vec<Token>) -> Result<bool,PplError> {
         loop {
             self.context.literal_next();
             match self.context.get_current_character_class() {
@@ -598,7 +675,7 @@ impl<'a,'b: 'a> Lexer<'a,'b> {
         }
     }
 
-    fn start(&self, tokens: &mut Vec<Token>) -> Result<bool, String> {
+    fn start(&self, tokens: &mut Vec<Token>) -> Result<bool,PplError> {
         loop {
             self.context.set_lexeme_start();
             match self.context.get_current_character_class() {
@@ -627,7 +704,7 @@ impl<'a,'b: 'a> Lexer<'a,'b> {
         }
     }
 
-    pub fn tokenize(input_string: &'a str, workspace: &'b WorkSpace) -> Result<Vec<Token>, String> {
+    pub fn tokenize(input_string: &'a str, workspace: &'b WorkSpace) -> Result<Vec<Token>,PplError> {
 
         let context = Context::new(input_string, workspace);
         let lexer = Lexer { context: &context };
@@ -5,6 +5,7 @@ use std::cell::RefCell;
 use crate::{lexical::{CharacterClass, get_character_class}, workspace::WorkSpace};
 
 use super::{TokenPosition, LineNumber};
+use crate::error::PplError;
 
 
 
@@ -38,6 +39,7 @@ struct InteriorContext<'a> {
     exponent_value: i32,
     exponent_sign: i32,
     is_double: bool,
+    is_big: bool,
 }
 
 impl<'a,'b> Context<'a,'b> {
@@ -48,7 +50,7 @@ impl<'a,'b> Context<'a,'b> {
             interior: RefCell::new(InteriorContext::new(source_string)) }
     }
 
-    pub fn add_exponential_digit(self: &Self) -> Result<(),String>{
+    pub fn add_exponential_digit(self: &Self) -> Result<(),PplError>{
         self.interior.borrow_mut().add_exponential_digit()
     }
 
@@ -56,7 +58,7 @@ impl<'a,'b> Context<'a,'b> {
         self.interior.borrow_mut().add_fractional_digit();
     }
 
-    pub fn add_integer_digit(self: &Self) -> Result<(),String> {
+    pub fn add_integer_digit(self: &Self) -> Result<(),PplError> {
         self.interior.borrow_mut().add_integer_digit()
     }
 
@@ -92,6 +94,10 @@ impl<'a,'b> Context<'a,'b> {
         self.interior.borrow().get_starting_position()
     }
 
+    pub fn is_big(&self) -> bool {
+        self.interior.borrow().is_big
+    }
+
     pub fn is_double(&self) -> bool {
         self.interior.borrow().is_double
     }
@@ -143,20 +149,20 @@ impl<'a> InteriorContext<'a> {
             current_line_number: None,
             next_column: 0,
             current_column: None,
-            integer_value: 0, integer_digit_count: 0, fractions_integer_value: 0, fractional_digit_count: 0, exponent_value: 0, exponent_sign: 1, is_double: false };
+            integer_value: 0, integer_digit_count: 0, fractions_integer_value: 0, fractional_digit_count: 0, exponent_value: 0, exponent_sign: 1, is_double: false, is_big: false };
         context.next();
         context
     }
 
-    pub fn add_exponential_digit(self: &mut Self) -> Result<(),String> {
+    pub fn add_exponential_digit(self: &mut Self) -> Result<(),PplError> {
         match self.exponent_value.checked_mul(10) {
             Some(v) => {
                 match v.checked_add(self.current_character.to_digit(10).unwrap() as i32) {
                     Some(v) => self.exponent_value = v,
-                    None => return Err(format!("arithmetic overflow")),
+                    None => return Err(ppl_error!(Syntax, "arithmetic overflow")),
                 }
             },
-            None => return Err(format!("arithmetic overflow")),
+            None => return Err(ppl_error!(Syntax, "arithmetic overflow")),
         }
         Ok(())
     }
@@ -179,15 +185,14 @@ impl<'a> InteriorContext<'a> {
         }
     }
 
-    pub fn add_integer_digit(self: &mut Self) -> Result<(),String> {
-        match self.integer_value.checked_mul(10) {
-            Some(v) => {
-                match v.checked_add(i64::from(self.current_character.to_digit(10).unwrap())) {
-                    Some(v) => self.integer_value = v,
-                    None => return Err(format!("arithmetic overflow")),
-                }
-            },
-            None => return Err(format!("arithmetic overflow")),
+    pub fn add_integer_digit(self: &mut Self) -> Result<(),PplError> {
+
+        //  With bignums, an integer too large for an int64 is just big, and the lexer takes its value from the source
+
+        match self.integer_value.checked_mul(10).and_then(|v| v.checked_add(i64::from(self.current_character.to_digit(10).unwrap()))) {
+            Some(v) => self.integer_value = v,
+            None if cfg!(feature = "bignum") => self.is_big = true,
+            None => return Err(ppl_error!(Syntax, "arithmetic overflow")),
         }
         if self.integer_value != 0 {
             self.integer_digit_count += 1;
@@ -206,6 +211,7 @@ impl<'a> InteriorContext<'a> {
         self.exponent_value = 0;
         self.exponent_sign = 1;
         self.is_double = false;
+        self.is_big = false;
     }
 
     pub fn get_current_character(self: &Self) -> char {
@@ -318,7 +324,7 @@ impl<'a> InteriorContext<'a> {
             self.current_line_number = None;
             self.next_column = 0;
             self.current_column = None;
-            self.integer_value = 0; self.integer_digit_count = 0; self.fractions_integer_value = 0; self.fractional_digit_count = 0; self.exponent_value = 0; self.exponent_sign = 1; self.is_double = false;
+            self.integer_value = 0; self.integer_digit_count = 0; self.fractions_integer_value = 0; self.fractional_digit_count = 0; self.exponent_value = 0; self.exponent_sign = 1; self.is_double = false; self.is_big = false;
         
             for _ in 0..=n {
                 self.next();
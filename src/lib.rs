@@ -1,9 +1,16 @@
-//#![feature(asm)]
 
 
+//  Code derived by ppl-derive names this crate ppl, inside it as well as out
+
+extern crate self as ppl;
+
+#[macro_use]
+pub mod error;
+pub mod embed;
 pub mod execution;
 mod lexical;
 mod parser;
+pub mod repl;
 mod sequencer;
 mod symbols;
 mod utility;
@@ -1,35 +1,98 @@
-use std::{io::{self, Write}};
+use std::io;
+use std::process;
 
 
+use ppl::repl::{self, Terminal};
 use ppl::workspace::WorkSpace;
 use ppl::execution::evaluate;
 
-extern crate signal_hook;
 
+const USAGE: &str = r#"Usage:
+    ppl                                 Start the interactive interpreter
+    ppl run <file> [arguments...]       Run a script, exiting with a non-zero status if it fails
+    ppl -e <expression> [arguments...]  Evaluate one expression and print its value
+    ppl load <workspace>                Load a saved workspace, then start the interactive interpreter
 
+Scripts and expressions can get their arguments with args()"#;
 
-fn main() {
 
-    let version_query = format!("version()");
-    let eval_query2 = format!("exec(??)");
+fn main() {
     let workspace = WorkSpace::new();
-    let mut stdout = io::stdout();
 
+    #[cfg(feature = "signal-hook")]
     signal_hook::flag::register(signal_hook::consts::SIGINT, workspace.get_execution_sentinal_as_atomicbool()).expect("failed to register ^C handler");
 
+    let arguments: Vec<String> = std::env::args().skip(1).collect();
+    let status = match arguments.first().map(|a| a.as_str()) {
+        None => interact(&workspace),
+        Some("run") if arguments.len() >= 2 => {
+            workspace.set_arguments(arguments[2..].to_vec());
+            run_script(&arguments[1], &workspace)
+        },
+        Some("-e") if arguments.len() >= 2 => {
+            workspace.set_arguments(arguments[2..].to_vec());
+            evaluate_expression(&arguments[1], &workspace)
+        },
+        Some("load") if arguments.len() == 2 => {
+            match ppl::workspace::io::read(&arguments[1], &workspace) {
+                Ok(()) => interact(&workspace),
+                Err(e) => {
+                    eprintln!("{}: {}", arguments[1], e);
+                    1
+                },
+            }
+        },
+        Some("-h") | Some("--help") => {
+            println!("{}", USAGE);
+            0
+        },
+        Some(_) => {
+            eprintln!("{}", USAGE);
+            2
+        },
+    };
+    process::exit(status);
+}
+
+fn evaluate_expression(expression: &str, workspace: &WorkSpace) -> i32 {
+    match evaluate(expression, workspace) {
+        Ok(result) => {
+            println!("{}", result);
+            0
+        },
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            1
+        },
+    }
+}
+
+fn interact(workspace: &WorkSpace) -> i32 {
+    println!("{}", evaluate("version()", workspace).expect("internal error"));
+
+    let mut terminal = Terminal::new().expect("unable to open the terminal");
+    match repl::run(&mut terminal, &mut io::stdout(), workspace) {
+        Ok(()) => 0,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            1
+        },
+    }
+}
 
-    write!(stdout, "{}\n\t",evaluate(&version_query, &workspace).expect("internal error")).expect("internal error");
-    stdout.flush().expect("internal error");
-    
-    loop {
-        match evaluate(&eval_query2, &workspace) {
-            Ok(result) => {
-                write!(stdout, "{}\n\t", result).expect("internal error");
-            },
-            Err(s) => {
-                write!(stdout, "Error: {s}\n\t").expect("internal error");
-            },
-        }
-        stdout.flush().expect("internal error");
+fn run_script(filename: &str, workspace: &WorkSpace) -> i32 {
+    let script = match std::fs::read_to_string(filename) {
+        Ok(script) => script,
+        Err(e) => {
+            eprintln!("{}: {}", filename, e);
+            return 1;
+        },
+    };
+    match repl::run_script(filename, &script, workspace) {
+        Ok(()) => 0,
+        Err(e) => {
+            eprintln!("{}", e);
+            1
+        },
     }
 }
@@ -25,6 +25,7 @@ use crate::{execution::{runtime::executable::Executable,
 use self::statementbuilder::StatementBuilder;
 use self::tree::{Node, OperationNode, ReferenceNode, DefinitionNode, DefinitionType, SequenceDefinition, StructureDefinition, StructureMemberDescription, IndexNode};
 use self::scanner::TokenScanner;
+use crate::error::PplError;
 
 
 
@@ -36,14 +37,14 @@ pub struct Parser<'a> {
 
 impl<'a> Parser<'a> {
 
-    fn construct_label_database(&self, executable: &Executable) -> Result<HashMap<String,LineNumber>,String> {
+    fn construct_label_database(&self, executable: &Executable) -> Result<HashMap<String,LineNumber>,PplError> {
         let mut labels = HashMap::new();
         for statement_index in 1..=executable.get_statement_count() {
             let statement = executable.get_statement(statement_index).unwrap();
             if let Node::StatementLabel(ref label_node) = executable.get_node(statement.as_first_node_index()).as_ref() {
                 let normalized_name = label_node.name.to_ascii_lowercase();
                 if labels.contains_key(&normalized_name) {
-                    return Err(format!("Statement label {} is duplicated", label_node.name));
+                    return Err(ppl_error!(Syntax, "Statement label {} is duplicated", label_node.name));
                 }
                 labels.insert(normalized_name, statement.as_line_number());
             }
@@ -52,19 +53,8 @@ impl<'a> Parser<'a> {
         Ok(labels)
     }
 
-    fn error<T>(&self, starting_position: &TokenPosition, message: &str) -> Result<T, String> {
-        let source_lines = self.source.lines();
-        let mut target_line = self.source;
-        let mut remaining_lines = starting_position.line_number;
-        for source_line in source_lines {
-            target_line = source_line;
-            remaining_lines -= 1;
-            if remaining_lines == 0 {
-                break;
-            }
-        }
-        let column = if starting_position.column > 0 {starting_position.column - 1} else { 0 };
-        Err(format!("\n{}\n{}^\n{}", target_line, " ".repeat(column as usize), message))
+    fn error<T>(&self, starting_position: &TokenPosition, message: &str) -> Result<T,PplError> {
+        Err(PplError::syntax(self.source, starting_position, message))
     }
 
     fn is_diadic_operator(&self, op: &str) -> bool {
@@ -103,7 +93,7 @@ impl<'a> Parser<'a> {
         Parser { source: source, workspace: workspace, line_number_bias: RefCell::new(0), }
     }
 
-    pub fn parse(&self, source: &str) -> Result<Rc<Executable>,String> {
+    pub fn parse(&self, source: &str) -> Result<Rc<Executable>,PplError> {
         let mut executable = Executable::new(source);
         let tokens = Lexer::tokenize(source, self.workspace)?;
         let mut token_iterator = TokenScanner::new(&tokens);
@@ -117,7 +107,7 @@ impl<'a> Parser<'a> {
         Ok(Rc::new(executable))
     }
 
-    fn parse_alternate_definition(&self, identifier_name: &String, token_iterator: &mut TokenScanner, statement_builder: &mut StatementBuilder) -> Result<usize,String> {
+    fn parse_alternate_definition(&self, identifier_name: &String, token_iterator: &mut TokenScanner, statement_builder: &mut StatementBuilder) -> Result<usize,PplError> {
 
         //  Gather the list of alternate names
 
@@ -133,7 +123,7 @@ impl<'a> Parser<'a> {
         }
     }
 
-    fn parse_atomic_value(&self, token: &Token, statement_builder: &mut StatementBuilder) -> Result<usize,String> {
+    fn parse_atomic_value(&self, token: &Token, statement_builder: &mut StatementBuilder) -> Result<usize,PplError> {
         match token.token_type {
             TokenType::Character(c) => {
                 Ok(statement_builder.add_node(Node::Value(Value::Char(c))))
@@ -147,6 +137,23 @@ impl<'a> Parser<'a> {
             TokenType::Integer(v) => {
                 Ok(statement_builder.add_node(Node::Value(Value::Int(v as i32))))
             },
+
+            //  An imaginary literal is built each time it's evaluated, since a complex value is a structure whose
+            //  parts can be assigned to
+
+            TokenType::Imaginary(v) => {
+                let real_part = statement_builder.add_node(Node::Value(Value::Real(0.0)));
+                let imaginary_part = statement_builder.add_node(Node::Value(Value::Real(v)));
+                statement_builder.add_node(Node::Operation(OperationNode::from_str("complex", vec![real_part, imaginary_part])));
+                Ok(real_part)
+            },
+            TokenType::Integer64(v) => {
+                Ok(statement_builder.add_node(Node::Value(Value::Int64(v))))
+            },
+            #[cfg(feature = "bignum")]
+            TokenType::BigInteger(ref v) => {
+                Ok(statement_builder.add_node(Node::Value(Value::Bignum(v.clone()))))
+            },
             TokenType::String(ref s) => {
                 Ok(statement_builder.add_node(Node::Value(SequenceInstance::construct_string_sequence(s))))
             },
@@ -154,7 +161,7 @@ impl<'a> Parser<'a> {
         }
     }
 
-    fn parse_datatype_definition(&self, identifier_name: &String, token_iterator: &mut TokenScanner, statement_builder: &mut StatementBuilder) -> Result<usize,String> {
+    fn parse_datatype_definition(&self, identifier_name: &String, token_iterator: &mut TokenScanner, statement_builder: &mut StatementBuilder) -> Result<usize,PplError> {
         if token_iterator.consume_punctuation("[").is_ok() {
             self.parse_structure_or_sequence_definition(identifier_name, token_iterator, statement_builder)
         } else {
@@ -162,7 +169,7 @@ impl<'a> Parser<'a> {
         }
     }
 
-    fn parse_diadic_operation(&self, token: &Token, token_iterator: &mut TokenScanner, statement_builder: &mut StatementBuilder) -> Result<usize,String> {
+    fn parse_diadic_operation(&self, token: &Token, token_iterator: &mut TokenScanner, statement_builder: &mut StatementBuilder) -> Result<usize,PplError> {
         let left = self.parse_indexed_value(token, token_iterator, statement_builder)?;
 
         if let Ok(operator_name) = token_iterator.consume_any_operator() {
@@ -178,7 +185,7 @@ impl<'a> Parser<'a> {
         }
     }
 
-    fn parse_definition(&self, token_iterator: &mut TokenScanner, statement_builder: &mut StatementBuilder) -> Result<usize,String> {
+    fn parse_definition(&self, token_iterator: &mut TokenScanner, statement_builder: &mut StatementBuilder) -> Result<usize,PplError> {
         let identifier_name = token_iterator.consume_identifier()?.clone();
         
         if token_iterator.consume_operator("=").is_ok() {
@@ -188,13 +195,13 @@ impl<'a> Parser<'a> {
         }
     }
 
-    fn parse_expression(&self, token_iterator: &mut TokenScanner, statement_builder: &mut StatementBuilder) -> Result<usize,String> {
+    fn parse_expression(&self, token_iterator: &mut TokenScanner, statement_builder: &mut StatementBuilder) -> Result<usize,PplError> {
         let token = token_iterator.next().expect("internal error").clone();
 
         self.parse_diadic_operation(&token, token_iterator, statement_builder)
     }
 
-    fn parse_field_reference(&self, value_index_position: usize, token_iterator: &mut TokenScanner, statement_builder: &mut StatementBuilder) -> Result<usize,String> {
+    fn parse_field_reference(&self, value_index_position: usize, token_iterator: &mut TokenScanner, statement_builder: &mut StatementBuilder) -> Result<usize,PplError> {
         token_iterator.consume_operator(".")?;
         let member_name = token_iterator.consume_identifier()?;
         let member_name_index = statement_builder.add_node(Node::IdentifierByValue(ReferenceNode::from_string(&member_name)));
@@ -202,7 +209,7 @@ impl<'a> Parser<'a> {
         Ok(member_name_index)
     }
 
-    fn parse_function_call(&self, identifier_name: &String, token_iterator: &mut TokenScanner, statement_builder: &mut StatementBuilder) -> Result<usize,String> {
+    fn parse_function_call(&self, identifier_name: &String, token_iterator: &mut TokenScanner, statement_builder: &mut StatementBuilder) -> Result<usize,PplError> {
         let mut argument_list = Vec::new();
         let token = token_iterator.peek().expect("internal error").clone();
 
@@ -244,7 +251,7 @@ impl<'a> Parser<'a> {
         }
     }
 
-    fn parse_function_definition(&self, identifier_name: &String, token_iterator: &mut TokenScanner, statement_builder: &mut StatementBuilder) -> Result<usize,String> {
+    fn parse_function_definition(&self, identifier_name: &String, token_iterator: &mut TokenScanner, statement_builder: &mut StatementBuilder) -> Result<usize,PplError> {
         let mut formal_args = Vec::new();
         let mut local_variables = Vec::new();
         let mut function_executable = Executable::new(self.source);
@@ -313,7 +320,7 @@ impl<'a> Parser<'a> {
         })))))
     }
 
-    fn parse_identifier(&self, token: &Token, token_iterator: &mut TokenScanner, statement_builder: &mut StatementBuilder) -> Result<usize,String> {
+    fn parse_identifier(&self, token: &Token, token_iterator: &mut TokenScanner, statement_builder: &mut StatementBuilder) -> Result<usize,PplError> {
         if let TokenType::Operator(ref op) = token.token_type {
             if op.as_str() == "$" { 
 
@@ -338,7 +345,7 @@ impl<'a> Parser<'a> {
         Ok(statement_builder.add_node(Node::IdentifierByValue(ReferenceNode::from_string(&token.string_value))))
     }
 
-    fn parse_if(&self, token_iterator: &mut TokenScanner, statement_builder: &mut StatementBuilder) -> Result<usize,String> {
+    fn parse_if(&self, token_iterator: &mut TokenScanner, statement_builder: &mut StatementBuilder) -> Result<usize,PplError> {
 
         //  The IF keyword has already been scanned
 
@@ -371,7 +378,7 @@ impl<'a> Parser<'a> {
         Ok(bool_expression_index)
     }
 
-    fn parse_index(&self, value_index_position: usize, token_iterator: &mut TokenScanner, statement_builder: &mut StatementBuilder) -> Result<usize,String> {
+    fn parse_index(&self, value_index_position: usize, token_iterator: &mut TokenScanner, statement_builder: &mut StatementBuilder) -> Result<usize,PplError> {
         //  The leading [ has not been consumed yet
 
         let mut value_position = value_index_position;
@@ -389,7 +396,7 @@ impl<'a> Parser<'a> {
         return Ok(value_index_position)
     }
 
-    fn parse_indexed_value(&self, token: &Token, token_iterator: &mut TokenScanner, statement_builder: &mut StatementBuilder) -> Result<usize,String> {
+    fn parse_indexed_value(&self, token: &Token, token_iterator: &mut TokenScanner, statement_builder: &mut StatementBuilder) -> Result<usize,PplError> {
         let mut value_index_position = self.parse_value(token, token_iterator, statement_builder)?;
         let first_value_index_position = value_index_position;
         loop {
@@ -403,7 +410,7 @@ impl<'a> Parser<'a> {
         }
     }
 
-    fn parse_monadic_operator(&self, token: &Token, token_iterator: &mut TokenScanner, statement_builder: &mut StatementBuilder) -> Result<usize,String> {
+    fn parse_monadic_operator(&self, token: &Token, token_iterator: &mut TokenScanner, statement_builder: &mut StatementBuilder) -> Result<usize,PplError> {
         if self.is_monadic_operator(token.string_value.as_str()) {
             let argument_index = self.parse_expression(token_iterator, statement_builder)?;
             return Ok(statement_builder.add_node(Node::Operation( OperationNode::from_string(&token.string_value, vec![ argument_index ]))))
@@ -411,7 +418,7 @@ impl<'a> Parser<'a> {
         self.parse_nullary_operator(&token, statement_builder)
     }
 
-    fn parse_nullary_operator(&self, token: &Token, statement_builder: &mut StatementBuilder) -> Result<usize,String> {
+    fn parse_nullary_operator(&self, token: &Token, statement_builder: &mut StatementBuilder) -> Result<usize,PplError> {
         if let TokenType::Operator(operator_string_value) = &token.token_type {
             if self.is_nullary_operator(operator_string_value.as_str()) {
                 Ok(statement_builder.add_node(Node::Operation( OperationNode::from_string(&operator_string_value, vec![ ]))))
@@ -423,7 +430,7 @@ impl<'a> Parser<'a> {
         }
     }
 
-    fn parse_parenthetical_expression<'b>(&self, token_iterator: &mut TokenScanner, statement_builder: &mut StatementBuilder) -> Result<usize,String> {
+    fn parse_parenthetical_expression<'b>(&self, token_iterator: &mut TokenScanner, statement_builder: &mut StatementBuilder) -> Result<usize,PplError> {
         let root_index = self.parse_expression(token_iterator, statement_builder)?;
         if token_iterator.consume_punctuation(")").is_err() {
             return self.error(&token_iterator.peek().unwrap().starting_position, format!("missing close paren").as_str());
@@ -431,7 +438,7 @@ impl<'a> Parser<'a> {
         Ok(root_index)
     }
 
-    fn parse_sequence_definition(&self, identifier_name: &String, token_iterator: &mut TokenScanner, statement_builder: &mut StatementBuilder) -> Result<usize,String> {
+    fn parse_sequence_definition(&self, identifier_name: &String, token_iterator: &mut TokenScanner, statement_builder: &mut StatementBuilder) -> Result<usize,PplError> {
         let lower_bound = token_iterator.consume_integer()?;
         token_iterator.consume_operator(":")?;
 
@@ -448,7 +455,7 @@ impl<'a> Parser<'a> {
         Ok(statement_builder.add_node(Node::Definition(DefinitionNode::from_string(identifier_name, DefinitionType::Sequence(SequenceDefinition::from_string(&datatype_name, lower_bound, upper_bound))))))
     }
 
-    fn parse_statement(&self, token_iterator: &mut TokenScanner, executable: &mut Executable) -> Result<Option<usize>,String> {
+    fn parse_statement(&self, token_iterator: &mut TokenScanner, executable: &mut Executable) -> Result<Option<usize>,PplError> {
         let statement_starting_position = &token_iterator.peek().unwrap().starting_position;
         let mut statement_builder = StatementBuilder::begin_statement(statement_starting_position.line_number - *self.line_number_bias.borrow(), 
             statement_starting_position.index, executable);
@@ -465,7 +472,7 @@ impl<'a> Parser<'a> {
         }
     }
 
-    fn parse_statement_internal(&self, normalized_line_number: LineNumber, token_iterator: &mut TokenScanner, statement_builder: &mut StatementBuilder) -> Result<Option<usize>,String> {
+    fn parse_statement_internal(&self, normalized_line_number: LineNumber, token_iterator: &mut TokenScanner, statement_builder: &mut StatementBuilder) -> Result<Option<usize>,PplError> {
         if token_iterator.consume_newline().is_ok() || token_iterator.is_eos() {
 
             //  We've got an empty line
@@ -530,7 +537,7 @@ impl<'a> Parser<'a> {
         }
     }
 
-    fn parse_statement_block(&self, token_iterator: &mut TokenScanner, statement_builder: &mut StatementBuilder) -> Result<Option<usize>,String> {
+    fn parse_statement_block(&self, token_iterator: &mut TokenScanner, statement_builder: &mut StatementBuilder) -> Result<Option<usize>,PplError> {
 
         //  We've already processed the opening brace
 
@@ -554,7 +561,7 @@ impl<'a> Parser<'a> {
         Ok(first_node_index)
     }
 
-    fn parse_statement_block_without_braces(&self, token_iterator: &mut TokenScanner, executable: &mut Executable) -> Result<Option<usize>,String> {
+    fn parse_statement_block_without_braces(&self, token_iterator: &mut TokenScanner, executable: &mut Executable) -> Result<Option<usize>,PplError> {
         let mut first_node_index = None;
 
         loop {
@@ -570,7 +577,7 @@ impl<'a> Parser<'a> {
         Ok(first_node_index)
     }
 
-    fn parse_statement_label(&self, token_iterator: &mut TokenScanner, statement_builder: &mut StatementBuilder) -> Result<usize,String> {
+    fn parse_statement_label(&self, token_iterator: &mut TokenScanner, statement_builder: &mut StatementBuilder) -> Result<usize,PplError> {
         if self.workspace.features.borrow().is_set(&crate::workspace::optional_features::Feature::LineNames) {
 
             //  This requires looking ahead a couple of tokens, so we save the iterator in case we don't find a label
@@ -591,10 +598,10 @@ impl<'a> Parser<'a> {
             token_iterator.pop_iterator();
         }
 
-        Err(format!("Expected statement label"))
+        Err(ppl_error!(Syntax, "Expected statement label"))
     }
 
-    fn parse_structure_definition(&self, identifier_name: &String, token_iterator: &mut TokenScanner, statement_builder: &mut StatementBuilder) -> Result<usize,String> {
+    fn parse_structure_definition(&self, identifier_name: &String, token_iterator: &mut TokenScanner, statement_builder: &mut StatementBuilder) -> Result<usize,PplError> {
         let mut members = Vec::new();
         loop {
             members.push(self.parse_structure_member_definition(token_iterator)?);
@@ -610,14 +617,14 @@ impl<'a> Parser<'a> {
         }
     }
 
-    fn parse_structure_member_definition(&self, token_iterator: &mut TokenScanner) -> Result<StructureMemberDescription,String> {
+    fn parse_structure_member_definition(&self, token_iterator: &mut TokenScanner) -> Result<StructureMemberDescription,PplError> {
         let member_name = token_iterator.consume_identifier()?;
         token_iterator.consume_operator(":")?;
         let datatype_name = token_iterator.consume_identifier()?;
         Ok(StructureMemberDescription { name: Name::from_string(&member_name), datatype: Name::from_string(&datatype_name) })
     }
 
-    fn parse_structure_or_sequence_definition(&self, identifier_name: &String, token_iterator: &mut TokenScanner, statement_builder: &mut StatementBuilder) -> Result<usize,String> {
+    fn parse_structure_or_sequence_definition(&self, identifier_name: &String, token_iterator: &mut TokenScanner, statement_builder: &mut StatementBuilder) -> Result<usize,PplError> {
         if token_iterator.peek_identifier() {
             self.parse_structure_definition(identifier_name, token_iterator, statement_builder)
         } else {
@@ -625,7 +632,7 @@ impl<'a> Parser<'a> {
         }
     }
 
-    fn parse_tuple_expression<'b>(&self, token_iterator: &mut TokenScanner, statement_builder: &mut StatementBuilder) -> Result<usize,String> {
+    fn parse_tuple_expression<'b>(&self, token_iterator: &mut TokenScanner, statement_builder: &mut StatementBuilder) -> Result<usize,PplError> {
         let mut members = Vec::new();
 
         if token_iterator.consume_punctuation("]").is_err() {
@@ -650,7 +657,7 @@ impl<'a> Parser<'a> {
         }
     }
 
-    fn parse_value(&self, token: &Token, token_iterator: &mut TokenScanner, statement_builder: &mut StatementBuilder) -> Result<usize,String> {
+    fn parse_value(&self, token: &Token, token_iterator: &mut TokenScanner, statement_builder: &mut StatementBuilder) -> Result<usize,PplError> {
         match &token.token_type {
             TokenType::Identifier(_) => return self.parse_identifier(token, token_iterator, statement_builder),
             TokenType::Operator(ref op) => {
@@ -675,7 +682,7 @@ impl<'a> Parser<'a> {
         }
     }
 
-    fn parse_while(&self, token_iterator: &mut TokenScanner, statement_builder: &mut StatementBuilder) -> Result<usize,String> {
+    fn parse_while(&self, token_iterator: &mut TokenScanner, statement_builder: &mut StatementBuilder) -> Result<usize,PplError> {
 
         //  The WHILE keyword has already been scanned
 
@@ -3,6 +3,7 @@
 use std::iter::Peekable;
 
 use crate::lexical::{TokenType, Token, TokenPosition};
+use crate::error::PplError;
 
 
 
@@ -19,7 +20,7 @@ impl<'a> TokenScanner<'a> {
         &self.eol_token
     }
 
-    pub fn consume_any_operator(&mut self) -> Result<String,String> {
+    pub fn consume_any_operator(&mut self) -> Result<String,PplError> {
         let t = self.peek();
         match t {
             Some(token) => {
@@ -30,10 +31,10 @@ impl<'a> TokenScanner<'a> {
             },
             None => {},
         }
-        Err(format!("operator not found"))
+        Err(ppl_error!(Syntax, "operator not found"))
     }
 
-   /*  pub fn consume_any_punctuation(&mut self) -> Result<String,String> {
+   /*  pub fn consume_any_punctuation(&mut self) -> Result<String,PplError> {
         let t = self.peek();
         match t {
             Some(token) => {
@@ -44,10 +45,10 @@ impl<'a> TokenScanner<'a> {
             },
             None => {},
         }
-        Err(format!("punctuation not found"))
+        Err(ppl_error!(Syntax, "punctuation not found"))
     } */
 
-    pub fn consume_identifier(&mut self) -> Result<String,String> {
+    pub fn consume_identifier(&mut self) -> Result<String,PplError> {
         let t = self.peek();
         match t {
             Some(token) => {
@@ -58,10 +59,10 @@ impl<'a> TokenScanner<'a> {
             },
             None => {},
         }
-        Err(format!("identifier not found"))
+        Err(ppl_error!(Syntax, "identifier not found"))
     }
 
-    pub fn consume_integer(&mut self) -> Result<i32,String> {
+    pub fn consume_integer(&mut self) -> Result<i32,PplError> {
         let t = self.peek();
         match t {
             Some(token) => {
@@ -72,10 +73,10 @@ impl<'a> TokenScanner<'a> {
             },
             None => {},
         }
-        Err(format!("integer not found"))
+        Err(ppl_error!(Syntax, "integer not found"))
     }
 
-    pub fn consume_keyword(&mut self, expected: &str) -> Result<String,String> {
+    pub fn consume_keyword(&mut self, expected: &str) -> Result<String,PplError> {
         let t = self.peek();
         match t {
             Some(token) => {
@@ -88,10 +89,10 @@ impl<'a> TokenScanner<'a> {
             },
             None => {},
         }
-        Err(format!("{} not found", expected))
+        Err(ppl_error!(Syntax, "{} not found", expected))
     }
 
-    pub fn consume_newline(&mut self) -> Result<(),String> {
+    pub fn consume_newline(&mut self) -> Result<(),PplError> {
         let t = self.peek();
         match t {
             Some(token) => {
@@ -102,10 +103,10 @@ impl<'a> TokenScanner<'a> {
             },
             None => return Ok(()),
         }
-        Err(format!("newline not found"))
+        Err(ppl_error!(Syntax, "newline not found"))
     }
 
-    pub fn consume_operator(&mut self, expected: &str) -> Result<String,String> {
+    pub fn consume_operator(&mut self, expected: &str) -> Result<String,PplError> {
         let t = self.peek();
         match t {
             Some(token) => {
@@ -118,10 +119,10 @@ impl<'a> TokenScanner<'a> {
             },
             None => {},
         }
-        Err(format!("{} not found", expected))
+        Err(ppl_error!(Syntax, "{} not found", expected))
     }
 
-    pub fn consume_punctuation(&mut self, expected: &str) -> Result<String,String> {
+    pub fn consume_punctuation(&mut self, expected: &str) -> Result<String,PplError> {
         let t = self.peek();
         match t {
             Some(token) => {
@@ -134,7 +135,7 @@ impl<'a> TokenScanner<'a> {
             },
             None => {},
         }
-        Err(format!("{} not found", expected))
+        Err(ppl_error!(Syntax, "{} not found", expected))
     }
 
     pub fn discard_saved_iterator(&mut self) {
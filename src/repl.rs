@@ -0,0 +1,257 @@
+//  This module holds the interactive read-evaluate-print loop, and its non-interactive cousin that runs scripts.
+//
+//  Input comes from a LineSource: either the terminal, which gives cursor editing, history that's saved across
+//  sessions and tab completion of workspace names, or a scripted list of lines (used by scripts and the tests).
+//  The loop understands PPL's $ function definition mode, prompting with the line number being entered, and
+//  hands $name for an existing function over to the line editor.
+
+use std::{io::Write, path::PathBuf};
+
+use rustyline::{completion::Completer, error::ReadlineError, highlight::Highlighter, hint::Hinter,
+    history::DefaultHistory, validate::Validator, Context, Editor, Helper};
+
+use crate::{execution::{definition::{is_function_definition_end, is_function_definition_start}, editor::FunctionEditor, evaluate},
+    workspace::WorkSpace};
+use crate::{error::PplError, lexical::LineNumber};
+
+
+pub const IMMEDIATE_MODE_PROMPT: &str = "        ";
+const HISTORY_FILENAME: &str = ".ppl_history";
+
+
+pub trait LineSource {
+    //  Returns the next line of input (without its line terminator), or None at end of input
+
+    fn read_line(&mut self, prompt: &str, workspace: &WorkSpace) -> Result<Option<String>,PplError>;
+
+    fn add_history(&mut self, _line: &str) {}
+}
+
+
+//  Scripted input, for driving the loop from a test or another program
+
+pub struct ScriptedInput {
+    lines: std::vec::IntoIter<String>,
+    line_number: usize,
+    pub prompts: Vec<String>,
+}
+
+impl ScriptedInput {
+    pub fn get_line_number(&self) -> usize {
+        self.line_number
+    }
+
+    pub fn new(script: &str) -> ScriptedInput {
+        ScriptedInput { lines: script.lines().map(String::from).collect::<Vec<String>>().into_iter(), line_number: 0, prompts: Vec::new() }
+    }
+}
+
+impl LineSource for ScriptedInput {
+    fn read_line(&mut self, prompt: &str, _workspace: &WorkSpace) -> Result<Option<String>,PplError> {
+        self.prompts.push(String::from(prompt));
+        let line = self.lines.next();
+        if line.is_some() {
+            self.line_number += 1;
+        }
+        Ok(line)
+    }
+}
+
+
+//  The terminal, with line editing, history and completion
+
+pub struct Terminal {
+    editor: Editor<NameCompleter, DefaultHistory>,
+    history_file: Option<PathBuf>,
+}
+
+impl Terminal {
+    pub fn new() -> Result<Terminal,PplError> {
+        let mut editor = Editor::new().map_err(|e| ppl_error!(Io, "{}", e))?;
+        editor.set_helper(Some(NameCompleter { names: Vec::new() }));
+        let history_file = std::env::var_os("HOME").map(|home| PathBuf::from(home).join(HISTORY_FILENAME));
+        if let Some(ref history_file) = history_file {
+            let _ = editor.load_history(history_file);
+        }
+        Ok(Terminal { editor, history_file })
+    }
+}
+
+impl LineSource for Terminal {
+    fn read_line(&mut self, prompt: &str, workspace: &WorkSpace) -> Result<Option<String>,PplError> {
+
+        //  The completer can't hold onto the workspace, so give it a fresh copy of the names before each line
+
+        if let Some(helper) = self.editor.helper_mut() {
+            helper.names = workspace.get_completions("");
+        }
+
+        loop {
+            match self.editor.readline(prompt) {
+                Ok(line) => return Ok(Some(line)),
+                Err(ReadlineError::Interrupted) => continue,
+                Err(ReadlineError::Eof) => return Ok(None),
+                Err(e) => return Err(ppl_error!(Io, "{}", e)),
+            }
+        }
+    }
+
+    fn add_history(&mut self, line: &str) {
+        //  Save as we go, since exit() ends the process without unwinding
+
+        if !line.trim().is_empty() && self.editor.add_history_entry(line).unwrap_or(false) {
+            if let Some(ref history_file) = self.history_file {
+                let _ = self.editor.save_history(history_file);
+            }
+        }
+    }
+}
+
+
+pub struct NameCompleter {
+    names: Vec<String>,
+}
+
+impl Completer for NameCompleter {
+    type Candidate = String;
+
+    fn complete(&self, line: &str, pos: usize, _ctx: &Context<'_>) -> rustyline::Result<(usize, Vec<String>)> {
+        let start = start_of_name(line, pos);
+        let prefix = line[start..pos].to_ascii_lowercase();
+        Ok((start, self.names.iter().filter(|name| name.starts_with(prefix.as_str())).cloned().collect()))
+    }
+}
+
+impl Helper for NameCompleter {}
+
+impl Highlighter for NameCompleter {}
+
+impl Hinter for NameCompleter {
+    type Hint = String;
+}
+
+impl Validator for NameCompleter {}
+
+
+pub fn complete(line: &str, pos: usize, workspace: &WorkSpace) -> (usize, Vec<String>) {
+    let start = start_of_name(line, pos);
+    (start, workspace.get_completions(&line[start..pos]))
+}
+
+enum Command {
+    Source(String),
+    Edited,
+    EditFailed(PplError),
+    EndOfInput,
+}
+
+pub fn run(input: &mut dyn LineSource, output: &mut dyn Write, workspace: &WorkSpace) -> Result<(),PplError> {
+    loop {
+        let source = match read_command(input, output, workspace)? {
+            Command::Source(source) => source,
+            Command::Edited => continue,
+            Command::EditFailed(e) => {
+                writeln!(output, "Error: {}", e)?;
+                continue;
+            },
+            Command::EndOfInput => return Ok(()),
+        };
+
+        match evaluate(&source, workspace) {
+            Ok(result) => writeln!(output, "{}", result),
+            Err(e) => writeln!(output, "Error: {}", e),
+        }?;
+        output.flush()?;
+    }
+}
+
+pub fn run_script(filename: &str, script: &str, workspace: &WorkSpace) -> Result<(),PplError> {
+
+    //  Unlike the interactive loop, statement values aren't echoed (only what the script prints is output), and
+    //  the first error ends the script
+
+    let mut input = ScriptedInput::new(script);
+    loop {
+        let line_number = input.get_line_number() as LineNumber + 1;
+        let source = match read_command(&mut input, &mut std::io::sink(), workspace).map_err(|e| e.within(filename, Some(line_number)))? {
+            Command::Source(source) => source,
+            Command::Edited => continue,
+            Command::EditFailed(e) => return Err(e.within(filename, Some(line_number))),
+            Command::EndOfInput => return Ok(()),
+        };
+        if source.trim().is_empty() {
+            continue;
+        }
+        evaluate(&source, workspace).map_err(|e| e.within(filename, Some(line_number)))?;
+    }
+}
+
+fn read_command(input: &mut dyn LineSource, output: &mut dyn Write, workspace: &WorkSpace) -> Result<Command,PplError> {
+    let line = match input.read_line(IMMEDIATE_MODE_PROMPT, workspace)? {
+        Some(line) => line,
+        None => return Ok(Command::EndOfInput),
+    };
+    input.add_history(&line);
+
+    if let Some(name) = FunctionEditor::is_edit_request(&line, workspace) {
+        match edit_function(&name, input, output, workspace) {
+            Ok(()) => Ok(Command::Edited),
+            Err(e) => Ok(Command::EditFailed(e)),
+        }
+    } else if is_function_definition_start(&line) {
+        Ok(Command::Source(read_function_definition(line, input, workspace)?))
+    } else {
+        Ok(Command::Source(line))
+    }
+}
+
+fn edit_function(name: &str, input: &mut dyn LineSource, output: &mut dyn Write, workspace: &WorkSpace) -> Result<(),PplError> {
+    let mut editor = FunctionEditor::open(name, workspace)?;
+    loop {
+        let line = input.read_line(&editor.prompt(), workspace)?;
+        let message = match line {
+            None => return editor.close(workspace),
+            Some(ref line) if line.trim() == "$" => {
+                input.add_history(line);
+                return editor.close(workspace);
+            },
+            Some(line) => {
+                input.add_history(&line);
+                match editor.apply(&line, workspace) {
+                    Ok(message) => message,
+                    Err(e) => e.to_string(),
+                }
+            },
+        };
+        if !message.is_empty() {
+            writeln!(output, "{}", message)?;
+        }
+    }
+}
+
+fn read_function_definition(first_line: String, input: &mut dyn LineSource, workspace: &WorkSpace) -> Result<String,PplError> {
+    let mut source = first_line;
+    let mut line_number = 1;
+    loop {
+        match input.read_line(&format!("[{}] ", line_number), workspace)? {
+            Some(line) => {
+                input.add_history(&line);
+                source += "\n";
+                source += line.as_str();
+                if is_function_definition_end(&line) {
+                    return Ok(source);
+                }
+                line_number += 1;
+            },
+            None => return Ok(source),
+        }
+    }
+}
+
+fn start_of_name(line: &str, pos: usize) -> usize {
+    line[..pos].char_indices()
+        .rev()
+        .take_while(|(_, c)| c.is_alphanumeric() || *c == '.')
+        .last()
+        .map_or(pos, |(i, _)| i)
+}
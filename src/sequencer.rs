@@ -1,14 +1,21 @@
 //  This module holds the sequencer that controls interpretation of the nodes in the executable.
 
-use std::rc::Rc;
+use std::{rc::Rc, time::Instant};
 
-use crate::{execution::{runtime::{executable::Executable, invocation::ExecutionState}, 
+use crate::{execution::{runtime::{coverage::{Coverage, is_conditional_branch}, executable::Executable, invocation::{ExecutionState, Invocation}, profile::Profile, statement::Statement}, 
     value::{Value, sequence::SequenceInstance}, execute}, 
     workspace::{WorkSpace, debug::DebugOption}, utility::convert_escape_sequences};
+use crate::error::PplError;
 
-fn execute_nodes(workspace: &WorkSpace) -> Result<Option<Value>,String> {
+fn execute_nodes(workspace: &WorkSpace, floor: Option<usize>) -> Result<Option<Value>,PplError> {
     loop {
 
+        //  A nested evaluation is over once its own invocations are
+
+        if floor.is_some_and(|floor| workspace.get_invocation_depth() <= floor) {
+            break;
+        }
+
         //  Get the most recent invocation context
 
         if let Some(invocation) = workspace.current_invocation() {
@@ -21,7 +28,9 @@ fn execute_nodes(workspace: &WorkSpace) -> Result<Option<Value>,String> {
 
             if workspace.get_execution_sentinal().is_stop_requested() {
                 invocation.set_execution_state(crate::execution::runtime::invocation::ExecutionState::Stopped);
-                return Err(format!("interrupt!"));
+                workspace.select_frame(0);
+                *workspace.step.borrow_mut() = None;
+                return Err(locate(ppl_error!(Interrupt, "interrupt!"), &invocation, workspace));
             }
 
             //  Get the next node to execute and do so
@@ -30,14 +39,25 @@ fn execute_nodes(workspace: &WorkSpace) -> Result<Option<Value>,String> {
                 let current_statement = invocation.as_executable().get_statement_from_node_index(index);
 
                 if let Some(ref statement) = current_statement {
-                    if invocation.get_execution_state() != ExecutionState::Resumed && statement.is_stop_set() && invocation.as_executable().is_first_executable_node(statement, index) {
-                        invocation.set_execution_state(ExecutionState::Stopped);
-                        return Err(format!("Stop requested"));
+                    if invocation.get_execution_state() != ExecutionState::Resumed && invocation.as_executable().is_first_executable_node(statement, index) {
+                        let reason = if statement.is_stop_set() && is_breakpoint_hit(statement, &invocation, workspace).map_err(|e| locate(e, &invocation, workspace))? {
+                            Some(String::from("Stop requested"))
+                        } else if invocation.get_fib().is_some() {
+                            check_step(workspace).or_else(|| check_watchpoints(workspace))
+                        } else {
+                            None
+                        };
+                        if let Some(reason) = reason {
+                            invocation.set_execution_state(ExecutionState::Stopped);
+                            workspace.select_frame(0);
+                            *workspace.step.borrow_mut() = None;
+                            return Err(locate(ppl_error!(Stop, "{}", reason), &invocation, workspace));
+                        }
                     }
                 }
 
                 if workspace.debug_options.borrow().is_set(&DebugOption::Execution) {
-                    match current_statement {
+                    match &current_statement {
                         Some(statement) => println!("{}) {}: [{}]{:?}", statement.as_line_number(), convert_escape_sequences(statement.as_source().as_str()), index, node),
                         None => println!("{:?}", node),
                     }
@@ -48,9 +68,40 @@ fn execute_nodes(workspace: &WorkSpace) -> Result<Option<Value>,String> {
                     println!("\t\t{}) {:?}\t{}", index, node, workspace.get_stack_size());
                 }
 
+                //  Only user functions are profiled and covered, so immediate mode doesn't show up in the reports
+
+                let covered = match (&current_statement, invocation.get_fib()) {
+                    (Some(statement), Some(fib)) if workspace.coverage.borrow().as_ref().is_some_and(Coverage::is_running) => {
+                        if index == statement.as_first_node_index() {
+                            workspace.coverage.borrow_mut().as_mut().unwrap().record_statement(fib.function_description.name.as_str(), statement);
+                        }
+                        is_conditional_branch(&invocation.as_executable(), index)
+                    },
+                    _ => false,
+                };
+
+                let started = match (&current_statement, invocation.get_fib()) {
+                    (Some(_), Some(_)) if workspace.profile.borrow().as_ref().is_some_and(Profile::is_running) => Some(Instant::now()),
+                    _ => None,
+                };
+
+                workspace.limits.borrow_mut().count_node().map_err(|e| locate(e, &invocation, workspace))?;
+                workspace.limits.borrow().check_stack(workspace.get_stack_size()).map_err(|e| locate(e, &invocation, workspace))?;
                 invocation.set_execution_state(crate::execution::runtime::invocation::ExecutionState::Executing);
-                execute(node.as_ref(), workspace)?;
+                execute(node.as_ref(), workspace).map_err(|e| locate(e, &invocation, workspace))?;
                 invocation.set_execution_state(crate::execution::runtime::invocation::ExecutionState::NotExecuting);
+                workspace.limits.borrow().check_values(workspace.get_value_stack_size()).map_err(|e| locate(e, &invocation, workspace))?;
+
+                if let (true, Some(fib)) = (covered, invocation.get_fib()) {
+                    if let Some(coverage) = workspace.coverage.borrow_mut().as_mut() {
+                        coverage.record_branch(fib.function_description.name.as_str(), index, invocation.get_pending_goto_index().is_some());
+                    }
+                }
+                if let (Some(started), Some(statement), Some(fib)) = (started, &current_statement, invocation.get_fib()) {
+                    if let Some(profile) = workspace.profile.borrow_mut().as_mut() {
+                        profile.record_node(fib.function_description.name.as_str(), statement, index == statement.as_first_node_index(), started.elapsed());
+                    }
+                }
 
             } else {
 
@@ -71,7 +122,73 @@ fn execute_nodes(workspace: &WorkSpace) -> Result<Option<Value>,String> {
     }
 }
 
-pub fn start_execution(executable: &Rc<Executable>, workspace: &WorkSpace) -> Result<Option<Value>,String> {
+//  Errors, stops and interrupts record where they happened before anything unwinds
+
+fn locate(error: PplError, invocation: &Invocation, workspace: &WorkSpace) -> PplError {
+    error.located(invocation.get_fib().map(|fib| fib.function_description.name.as_string()), invocation.get_current_line_number())
+        .with_traceback(workspace.traceback())
+}
+
+//  A breakpoint's condition is evaluated in the stopping function's scope, and only hits with the condition true
+//  count towards stop.after()
+
+fn is_breakpoint_hit(statement: &Statement, invocation: &Invocation, workspace: &WorkSpace) -> Result<bool,PplError> {
+    if let Some(condition) = statement.get_stop_condition() {
+        let state = invocation.get_execution_state();
+        let last_statement_value = workspace.get_last_statement_value();
+        invocation.set_execution_state(ExecutionState::Executing);
+        let result = start_execution(&condition, workspace);
+        invocation.set_execution_state(state);
+        if let Some(value) = last_statement_value {
+            workspace.set_last_statement_value(&value);
+        }
+        if !result?.unwrap_or(Value::Empty).as_bool()? {
+            return Ok(false);
+        }
+    }
+    Ok(statement.count_hit() >= statement.get_stop_after())
+}
+
+fn check_step(workspace: &WorkSpace) -> Option<String> {
+    match *workspace.step.borrow() {
+        Some(step) if step.is_complete(workspace.get_invocation_depth()) => Some(String::from("Step completed")),
+        _ => None,
+    }
+}
+
+fn check_watchpoints(workspace: &WorkSpace) -> Option<String> {
+    let changes: Vec<String> = workspace.watchpoints.borrow_mut().iter_mut().filter_map(|watchpoint| watchpoint.check()).collect();
+    if changes.is_empty() {
+        None
+    } else {
+        Some(changes.join(", "))
+    }
+}
+
+pub fn start_execution(executable: &Rc<Executable>, workspace: &WorkSpace) -> Result<Option<Value>,PplError> {
+
+    //  A system function (read, say) can evaluate PPL while a statement is executing.  That evaluation has to
+    //  stop when it's done, rather than carry on with its caller's invocation, and clean up after itself if
+    //  it fails.  Immediate mode on top of a stopped function is different: resuming relies on carrying on.
+
+    let floor = match workspace.current_invocation() {
+        Some(invocation) if invocation.get_execution_state() == ExecutionState::Executing => Some(workspace.get_invocation_depth()),
+        _ => None,
+    };
+
+    //  Watchpoints report what functions change, not what the user does in immediate mode.  A step left over
+    //  from a function that has since returned is finished with.  The node budget covers everything one
+    //  command does, including what it evaluates along the way.
+
+    if floor.is_none() {
+        check_watchpoints(workspace);
+        *workspace.step.borrow_mut() = None;
+        workspace.limits.borrow_mut().start();
+    }
     workspace.start_immediate_mode(executable);
-    execute_nodes(workspace)
+    let result = execute_nodes(workspace, floor);
+    if let (Some(floor), Err(_)) = (floor, &result) {
+        workspace.unwind_invocations(floor);
+    }
+    result
 }
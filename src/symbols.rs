@@ -10,6 +10,7 @@ use crate::symbols::help::Help;
 use crate::workspace::WorkSpace;
 
 use self::name::Name;
+use crate::error::PplError;
 
 pub mod datatype;
 pub mod help;
@@ -57,7 +58,7 @@ impl<T: Help> SymbolTable<T> {
         names
     } 
 
-    pub fn help_all(&self, workspace: &WorkSpace) -> Result<Value,String> {
+    pub fn help_all(&self, workspace: &WorkSpace) -> Result<Value,PplError> {
         let mut result = String::new();
 
         //  Gather the list of all the symbols, together with their help text
@@ -4,12 +4,18 @@ use std::fmt;
 use crate::{execution::{value::{Value, sequence::SequenceInstance}, evaluate_identifier_by_value}, workspace::{WorkSpace, GeneralSymbol}};
 
 use super::{metadata::{BuiltAlternates, MetaSequence, MetaStructure, Metadata, MetaAlternate}, help::Help};
+use crate::error::PplError;
 
 
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum RootDataType {
     Int,
+    Int64,
+    #[cfg(feature = "bignum")]
+    Bignum,
+    Rational,
+    Decimal,
     Real,
     Dbl,
     Bool,
@@ -22,12 +28,12 @@ pub enum RootDataType {
 
 impl RootDataType {
 
-    pub fn coerce(&self, v: &Value, workspace: &WorkSpace) -> Result<Value,String> {
+    pub fn coerce(&self, v: &Value, workspace: &WorkSpace) -> Result<Value,PplError> {
 
         //  Before we can coerce the value, it has to be an atomic value
 
         match v {
-            Value::Selector(selector) => return Err(format!("{} is not a value", selector)),
+            Value::Selector(selector) => return Err(ppl_error!(Type, "{} is not a value", selector)),
             Value::Symbol(symbol) => return self.coerce(&evaluate_identifier_by_value(symbol, workspace)?, workspace),
             Value::ValueByReference(cell_ref) => return self.coerce(&*cell_ref.cell.borrow().as_ref_to_value(), workspace),
             Value::LogicalLink(link) => return self.coerce(&*link.as_ref_to_value(), workspace),
@@ -38,6 +44,11 @@ impl RootDataType {
 
         match self {
             RootDataType::Int => Ok(Value::Int(v.as_i32()?)),
+            RootDataType::Int64 => Ok(Value::Int64(v.as_i64()?)),
+            #[cfg(feature = "bignum")]
+            RootDataType::Bignum => Ok(Value::Bignum(v.as_bignum()?)),
+            RootDataType::Rational => Ok(Value::Rational(v.as_rational()?)),
+            RootDataType::Decimal => Ok(Value::Decimal(v.as_decimal()?)),
             RootDataType::Real =>  Ok(Value::Real(v.as_f32()?)),
             RootDataType::Dbl =>  Ok(Value::Double(v.as_f64()?)),
             RootDataType::Bool =>  Ok(Value::Bool(v.as_bool()?)),
@@ -45,7 +56,7 @@ impl RootDataType {
             RootDataType::Sequence(seq) =>  match v {
                 Value::Sequence(instance) => {
                     if instance.as_string() != seq.name.as_string() {
-                        Err(format!("Cannot convert value from {} to {}", v.as_datatype()?,  seq.name.as_string()))
+                        Err(ppl_error!(Type, "Cannot convert value from {} to {}", v.as_datatype()?,  seq.name.as_string()))
                     } else {
                         Ok(v.clone())
                     }
@@ -55,13 +66,13 @@ impl RootDataType {
                         dbg!(seq);
                         dbg!(v); 
                     }
-                    Err(format!("Cannot convert value {} to {}", v, seq.name.as_string()))
+                    Err(ppl_error!(Type, "Cannot convert value {} to {}", v, seq.name.as_string()))
                 },
             },
             RootDataType::Structure(structure) =>  match v {
                 Value::Structure(instance) => {
                     if instance.as_string() != structure.name.as_string() {
-                        Err(format!("Cannot convert value from {} to {}", instance.as_datatype(), structure.name.as_string()))
+                        Err(ppl_error!(Type, "Cannot convert value from {} to {}", instance.as_datatype(), structure.name.as_string()))
                     } else {
                         Ok(v.clone())
                     }
@@ -71,7 +82,7 @@ impl RootDataType {
                         dbg!(structure);
                         dbg!(v); 
                     }
-                    Err(format!("Cannot convert value {} to {}", v, structure.name.as_string()))
+                    Err(ppl_error!(Type, "Cannot convert value {} to {}", v, structure.name.as_string()))
                 }
             },
             RootDataType::Alternate(alternate) => {
@@ -82,7 +93,7 @@ impl RootDataType {
                         dbg!(self);
                         dbg!(v); 
                     }
-                    Err(format!("Cannot convert value"))
+                    Err(ppl_error!(Type, "Cannot convert value"))
                 }
             },
             RootDataType::BuiltinAlternate(alternate) => {
@@ -93,28 +104,33 @@ impl RootDataType {
                         dbg!(self);
                         dbg!(v); 
                     }
-                    Err(format!("Cannot convert value"))
+                    Err(ppl_error!(Type, "Cannot convert value"))
                  }
             },
         }
     }
 
-    pub fn from_value(v: &Value, workspace: &WorkSpace) -> Result<RootDataType,String> {
+    pub fn from_value(v: &Value, workspace: &WorkSpace) -> Result<RootDataType,PplError> {
         match v {
-            Value::Empty => Err(format!("Empty value")),
+            Value::Empty => Err(ppl_error!(Runtime, "Empty value")),
             Value::Bool(_) => Ok(RootDataType::Bool),
             Value::Int(_) => Ok(RootDataType::Int),
+            Value::Int64(_) => Ok(RootDataType::Int64),
+            #[cfg(feature = "bignum")]
+            Value::Bignum(_) => Ok(RootDataType::Bignum),
+            Value::Rational(_) => Ok(RootDataType::Rational),
+            Value::Decimal(_) => Ok(RootDataType::Decimal),
             Value::Real(_) => Ok(RootDataType::Real),
             Value::Double(_) => Ok(RootDataType::Dbl),
             Value::Char(_) => Ok(RootDataType::Char),
-            Value::Selector(sel) => Err(format!("{} is not a value", sel)),
+            Value::Selector(sel) => Err(ppl_error!(Type, "{} is not a value", sel)),
             Value::Sequence(seq) => workspace.resolve_datatype(&seq.as_datatype().as_string()),
             Value::Structure(structure) => workspace.resolve_datatype(&structure.as_datatype().as_string()),
             Value::Symbol(symbolic_reference) => {
                 if let GeneralSymbol::Variable(v) = symbolic_reference.as_symbol() {
                     RootDataType::from_value(&*v.cell.borrow().as_ref_to_value(), workspace)
                 } else {
-                    Err(format!("{} is not a value", symbolic_reference))
+                    Err(ppl_error!(Type, "{} is not a value", symbolic_reference))
                 }
             },
             Value::ValueByReference(c) => workspace.resolve_datatype(&c.datatype.as_string()),
@@ -128,6 +144,11 @@ impl fmt::Display for RootDataType {
     fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             RootDataType::Int => write!(fmt, "Int"),
+            RootDataType::Int64 => write!(fmt, "Int64"),
+            #[cfg(feature = "bignum")]
+            RootDataType::Bignum => write!(fmt, "Bignum"),
+            RootDataType::Rational => write!(fmt, "Rational"),
+            RootDataType::Decimal => write!(fmt, "Decimal"),
             RootDataType::Real => write!(fmt, "Real"),
             RootDataType::Dbl => write!(fmt, "Dbl"),
             RootDataType::Bool => write!(fmt, "Bool"),
@@ -141,6 +162,11 @@ impl Metadata for RootDataType {
     fn as_definition(&self) -> String {
         match self {
             RootDataType::Int => String::from("int"),
+            RootDataType::Int64 => String::from("int64"),
+            #[cfg(feature = "bignum")]
+            RootDataType::Bignum => String::from("bignum"),
+            RootDataType::Rational => String::from("rational"),
+            RootDataType::Decimal => String::from("decimal"),
             RootDataType::Real => String::from("real"),
             RootDataType::Dbl => String::from("dbl"),
             RootDataType::Bool => String::from("bool"),
@@ -157,10 +183,16 @@ impl Help for RootDataType {
     fn help_text(&self, _workspace: &WorkSpace) -> Option<String> {
         match self {
             RootDataType::Int => Some(format!("Holds integer values ranging from {} to {}", i32::MIN, i32::MAX)),
+            RootDataType::Int64 => Some(format!("Holds integer values ranging from {} to {}", i64::MIN, i64::MAX)),
+            #[cfg(feature = "bignum")]
+            RootDataType::Bignum => Some(String::from("Holds integer values of any size")),
+            RootDataType::Rational => Some(format!("Holds exact fractions whose numerator and denominator range from {} to {}", i64::MIN, i64::MAX)),
+            RootDataType::Decimal => Some(format!("Holds exact decimal values with up to {} decimal places", crate::execution::value::decimal::MAX_SCALE)),
             RootDataType::Real => Some(format!("Holds floating point values ranging from {} to {}", f32::MIN, f32::MAX)),
             RootDataType::Dbl => Some(format!("Holds floating point values ranging from {} to {}", f64::MIN, f64::MAX)),
             RootDataType::Bool => Some(format!("Holds TRUE or FALSE")),
-            RootDataType::Char => Some(format!("Holds a single Unicode character")),
+            RootDataType::Char => Some(format!("Holds a single Unicode character; char(n) makes the character with code n")),
+            RootDataType::Structure(s) if s.name.as_str() == "complex" => Some(String::from("Holds complex numbers, with a real part rp and an imaginary part ip")),
             _ => None,
         }        
     }
@@ -168,6 +200,11 @@ impl Help for RootDataType {
     fn pretty_print(&self) -> String {
         match self {
             RootDataType::Int => String::from("int"),
+            RootDataType::Int64 => String::from("int64"),
+            #[cfg(feature = "bignum")]
+            RootDataType::Bignum => String::from("bignum"),
+            RootDataType::Rational => String::from("rational"),
+            RootDataType::Decimal => String::from("decimal"),
             RootDataType::Real => String::from("real"),
             RootDataType::Dbl => String::from("dbl"),
             RootDataType::Bool => String::from("bool"),
@@ -184,20 +221,37 @@ impl Help for RootDataType {
         }    
     }
 
-    fn show_help(&self, name: &str, workspace: &WorkSpace) -> Result<Value,String> {
+    fn show_help(&self, name: &str, workspace: &WorkSpace) -> Result<Value,PplError> {
         match self.help_text(workspace) {
             Some(t) => Ok(SequenceInstance::construct_string_sequence(&format!("{} - {}", name, &t))),
-            None => Err(format!("help not implemented for this type")),
+            None => Err(ppl_error!(Runtime, "help not implemented for this type")),
         }
     }
 }
 
-pub fn is_an_instance_of(v: &Value, datatype: &RootDataType, workspace: &WorkSpace) -> Result<bool,String> {
+pub fn is_an_instance_of(v: &Value, datatype: &RootDataType, workspace: &WorkSpace) -> Result<bool,PplError> {
     match datatype {
         RootDataType::Int => match v {
             Value::Int(_) => Ok(true),
             _ => Ok(false),
         },
+        RootDataType::Int64 => match v {
+            Value::Int64(_) => Ok(true),
+            _ => Ok(false),
+        },
+        #[cfg(feature = "bignum")]
+        RootDataType::Bignum => match v {
+            Value::Bignum(_) => Ok(true),
+            _ => Ok(false),
+        },
+        RootDataType::Rational => match v {
+            Value::Rational(_) => Ok(true),
+            _ => Ok(false),
+        },
+        RootDataType::Decimal => match v {
+            Value::Decimal(_) => Ok(true),
+            _ => Ok(false),
+        },
         RootDataType::Real =>  match v {
             Value::Real(_) => Ok(true),
             _ => Ok(false),
@@ -227,7 +281,7 @@ pub fn is_an_instance_of(v: &Value, datatype: &RootDataType, workspace: &WorkSpa
     }
 }
 
-fn is_a_alternate(v: &Value, alternate: &MetaAlternate, workspace: &WorkSpace) -> Result<bool,String> {
+fn is_a_alternate(v: &Value, alternate: &MetaAlternate, workspace: &WorkSpace) -> Result<bool,PplError> {
     for alternate_name in &alternate.members {
         let opt_symbol = workspace.try_get_datatype(alternate_name.as_string().as_str());
         if opt_symbol.is_some() {
@@ -236,13 +290,13 @@ fn is_a_alternate(v: &Value, alternate: &MetaAlternate, workspace: &WorkSpace) -
             }
             continue;
         }
-        return Err(format!("Datatype {} not found", alternate_name.as_string()));
+        return Err(ppl_error!(Name, "Datatype {} not found", alternate_name.as_string()));
     }
 
     Ok(false)
 }
 
-fn is_a_builtin_alternate(v: &Value, alternate: &BuiltAlternates, workspace: &WorkSpace) -> Result<bool,String> {
+fn is_a_builtin_alternate(v: &Value, alternate: &BuiltAlternates, workspace: &WorkSpace) -> Result<bool,PplError> {
     match alternate {
         BuiltAlternates::Structure => {
             if let Value::Structure(_) = v {
@@ -269,7 +323,7 @@ fn is_a_builtin_alternate(v: &Value, alternate: &BuiltAlternates, workspace: &Wo
                     }
                     None => {},
                 }
-                Err(format!("{} is not a sequence", sequence.as_string()))
+                Err(ppl_error!(Type, "{} is not a sequence", sequence.as_string()))
             } else {
                 Ok(false)
             }
@@ -278,11 +332,16 @@ fn is_a_builtin_alternate(v: &Value, alternate: &BuiltAlternates, workspace: &Wo
     }
 }
 
-pub fn is_assignable_to(v: &Value, receiving_datatype: &RootDataType, workspace: &WorkSpace) -> Result<bool,String> {
+pub fn is_assignable_to(v: &Value, receiving_datatype: &RootDataType, workspace: &WorkSpace) -> Result<bool,PplError> {
     match receiving_datatype {
         RootDataType::Int => match v {
             Value::Bool(_) => Ok(true),
             Value::Int(_) => Ok(true),
+            Value::Int64(_) => Ok(true),
+            #[cfg(feature = "bignum")]
+            Value::Bignum(_) => Ok(true),
+            Value::Rational(_) => Ok(true),
+            Value::Decimal(_) => Ok(true),
             Value::Real(_) => Ok(true),
             Value::Double(_) => Ok(true),
             Value::Char(c) => Ok(char::is_ascii_digit(c)),
@@ -294,9 +353,90 @@ pub fn is_assignable_to(v: &Value, receiving_datatype: &RootDataType, workspace:
                 Ok(false)
             },
         },
+        RootDataType::Int64 => match v {
+            Value::Bool(_) => Ok(true),
+            Value::Int(_) => Ok(true),
+            Value::Int64(_) => Ok(true),
+            #[cfg(feature = "bignum")]
+            Value::Bignum(_) => Ok(true),
+            Value::Rational(_) => Ok(true),
+            Value::Decimal(_) => Ok(true),
+            Value::Real(_) => Ok(true),
+            Value::Double(_) => Ok(true),
+            Value::Char(c) => Ok(char::is_ascii_digit(c)),
+            _ => {
+                if workspace.debug_options.borrow().is_set(&crate::workspace::debug::DebugOption::DataConversion) {
+                    dbg!(&receiving_datatype);
+                    dbg!(v);
+                }
+                Ok(false)
+            },
+        },
+        #[cfg(feature = "bignum")]
+        RootDataType::Bignum => match v {
+            Value::Bool(_) => Ok(true),
+            Value::Int(_) => Ok(true),
+            Value::Int64(_) => Ok(true),
+            Value::Bignum(_) => Ok(true),
+            Value::Rational(_) => Ok(true),
+            Value::Decimal(_) => Ok(true),
+            Value::Real(_) => Ok(true),
+            Value::Double(_) => Ok(true),
+            Value::Char(c) => Ok(char::is_ascii_digit(c)),
+            _ => {
+                if workspace.debug_options.borrow().is_set(&crate::workspace::debug::DebugOption::DataConversion) {
+                    dbg!(&receiving_datatype);
+                    dbg!(v);
+                }
+                Ok(false)
+            },
+        },
+        RootDataType::Rational => match v {
+            Value::Bool(_) => Ok(true),
+            Value::Int(_) => Ok(true),
+            Value::Int64(_) => Ok(true),
+            #[cfg(feature = "bignum")]
+            Value::Bignum(_) => Ok(true),
+            Value::Rational(_) => Ok(true),
+            Value::Decimal(_) => Ok(true),
+            Value::Real(_) => Ok(true),
+            Value::Double(_) => Ok(true),
+            Value::Char(c) => Ok(char::is_ascii_digit(c)),
+            _ => {
+                if workspace.debug_options.borrow().is_set(&crate::workspace::debug::DebugOption::DataConversion) {
+                    dbg!(&receiving_datatype);
+                    dbg!(v);
+                }
+                Ok(false)
+            },
+        },
+        RootDataType::Decimal => match v {
+            Value::Bool(_) => Ok(true),
+            Value::Int(_) => Ok(true),
+            Value::Int64(_) => Ok(true),
+            #[cfg(feature = "bignum")]
+            Value::Bignum(_) => Ok(true),
+            Value::Rational(_) => Ok(true),
+            Value::Decimal(_) => Ok(true),
+            Value::Real(_) => Ok(true),
+            Value::Double(_) => Ok(true),
+            Value::Char(c) => Ok(char::is_ascii_digit(c)),
+            _ => {
+                if workspace.debug_options.borrow().is_set(&crate::workspace::debug::DebugOption::DataConversion) {
+                    dbg!(&receiving_datatype);
+                    dbg!(v);
+                }
+                Ok(false)
+            },
+        },
         RootDataType::Real =>  match v {
             Value::Bool(_) => Ok(true),
             Value::Int(_) => Ok(true),
+            Value::Int64(_) => Ok(true),
+            #[cfg(feature = "bignum")]
+            Value::Bignum(_) => Ok(true),
+            Value::Rational(_) => Ok(true),
+            Value::Decimal(_) => Ok(true),
             Value::Real(_) => Ok(true),
             Value::Double(_) => Ok(true),
             Value::Char(c) => Ok(char::is_ascii_digit(c)),
@@ -322,6 +462,7 @@ pub fn is_assignable_to(v: &Value, receiving_datatype: &RootDataType, workspace:
         RootDataType::Bool =>  match v {
             Value::Bool(_) => Ok(true),
             Value::Int(v) => Ok(*v == 0 || *v == 1),
+            Value::Int64(v) => Ok(*v == 0 || *v == 1),
             Value::Real(v) => Ok(*v == 0.0 || *v == 1.0),
             Value::Double(v) => Ok(*v == 0.0 || *v == 1.0),
             Value::Char(c) => Ok(c.to_ascii_lowercase() == 't' || c.to_ascii_lowercase() == 'f'),
@@ -369,7 +510,7 @@ pub fn is_assignable_to(v: &Value, receiving_datatype: &RootDataType, workspace:
     }
 }
 
-fn is_assignable_to_a_alternate(v: &Value, receiving_alternate: &MetaAlternate, workspace: &WorkSpace) -> Result<bool,String> {
+fn is_assignable_to_a_alternate(v: &Value, receiving_alternate: &MetaAlternate, workspace: &WorkSpace) -> Result<bool,PplError> {
     for alternate_name in &receiving_alternate.members {
         let opt_symbol = workspace.try_get_datatype(alternate_name.as_string().as_str());
         if opt_symbol.is_some() {
@@ -378,13 +519,15 @@ fn is_assignable_to_a_alternate(v: &Value, receiving_alternate: &MetaAlternate,
             }
             continue;
         }
-        return Err(format!("Datatype {} not found", alternate_name.as_string()));
+        return Err(ppl_error!(Name, "Datatype {} not found", alternate_name.as_string()));
     }
 
     Ok(false)
 }
 
-pub fn strongest_datatype<'a>(args: &[RootDataType], workspace: &WorkSpace) -> Result<RootDataType,String> {
+//  A number mixed with a complex value is taken as complex, with no imaginary part
+
+pub fn strongest_datatype<'a>(args: &[RootDataType], workspace: &WorkSpace) -> Result<RootDataType,PplError> {
     let mut strongest = &args[0];
     let string_datatype = workspace.resolve_datatype(&String::from("string"))?;
 
@@ -393,6 +536,11 @@ pub fn strongest_datatype<'a>(args: &[RootDataType], workspace: &WorkSpace) -> R
             RootDataType::Int => 
                 match args[i] {
                     RootDataType::Int => strongest = &RootDataType::Int,
+                    RootDataType::Int64 => strongest = &RootDataType::Int64,
+                    #[cfg(feature = "bignum")]
+                    RootDataType::Bignum => strongest = &RootDataType::Bignum,
+                    RootDataType::Rational => strongest = &RootDataType::Rational,
+                    RootDataType::Decimal => strongest = &RootDataType::Decimal,
                     RootDataType::Real => strongest = &RootDataType::Real,
                     RootDataType::Dbl => strongest = &RootDataType::Dbl,
                     RootDataType::Bool => strongest = &RootDataType::Int,
@@ -401,15 +549,21 @@ pub fn strongest_datatype<'a>(args: &[RootDataType], workspace: &WorkSpace) -> R
                         if seq.name.as_str() == "string" {
                             strongest = &RootDataType::Int;
                         } else {
-                            return Err(format!("INT is arithmetically incompatble with Sequence"));
+                            return Err(ppl_error!(Type, "INT is arithmetically incompatble with Sequence"));
                         }
                     },
-                    RootDataType::Structure(_) => return Err(format!("INT is arithmetically incompatble with Structure")),
-                    RootDataType::Alternate(_) | RootDataType::BuiltinAlternate(_) => return Err(format!("INT is arithmetically incompatble with Alternate")),
+                    RootDataType::Structure(ref structure) if structure.name.as_str() == "complex" => strongest = &args[i],
+                    RootDataType::Structure(_) => return Err(ppl_error!(Type, "INT is arithmetically incompatble with Structure")),
+                    RootDataType::Alternate(_) | RootDataType::BuiltinAlternate(_) => return Err(ppl_error!(Type, "INT is arithmetically incompatble with Alternate")),
                 },
             RootDataType::Real => 
             match args[i] {
                 RootDataType::Int => strongest = &RootDataType::Real,
+                RootDataType::Int64 => strongest = &RootDataType::Dbl,
+                #[cfg(feature = "bignum")]
+                RootDataType::Bignum => strongest = &RootDataType::Dbl,
+                RootDataType::Rational => strongest = &RootDataType::Dbl,
+                RootDataType::Decimal => strongest = &RootDataType::Dbl,
                 RootDataType::Real => strongest = &RootDataType::Real,
                 RootDataType::Dbl => strongest = &RootDataType::Dbl,
                 RootDataType::Bool => strongest = &RootDataType::Real,
@@ -418,15 +572,21 @@ pub fn strongest_datatype<'a>(args: &[RootDataType], workspace: &WorkSpace) -> R
                     if seq.name.as_str() == "string" {
                         strongest = &RootDataType::Real;
                     } else {
-                        return Err(format!("Real is arithmetically incompatble with Sequence"));
+                        return Err(ppl_error!(Type, "Real is arithmetically incompatble with Sequence"));
                     }
                 },
-                RootDataType::Structure(_) => return Err(format!("REAL is arithmetically incompatble with Structure")),
-                RootDataType::Alternate(_) | RootDataType::BuiltinAlternate(_) => return Err(format!("REAL is arithmetically incompatble with Alternate")),
+                RootDataType::Structure(ref structure) if structure.name.as_str() == "complex" => strongest = &args[i],
+                RootDataType::Structure(_) => return Err(ppl_error!(Type, "REAL is arithmetically incompatble with Structure")),
+                RootDataType::Alternate(_) | RootDataType::BuiltinAlternate(_) => return Err(ppl_error!(Type, "REAL is arithmetically incompatble with Alternate")),
             },
             RootDataType::Dbl => 
             match args[i] {
                 RootDataType::Int => strongest = &RootDataType::Dbl,
+                RootDataType::Int64 => strongest = &RootDataType::Dbl,
+                #[cfg(feature = "bignum")]
+                RootDataType::Bignum => strongest = &RootDataType::Dbl,
+                RootDataType::Rational => strongest = &RootDataType::Dbl,
+                RootDataType::Decimal => strongest = &RootDataType::Dbl,
                 RootDataType::Real => strongest = &RootDataType::Dbl,
                 RootDataType::Dbl => strongest = &RootDataType::Dbl,
                 RootDataType::Bool => strongest = &RootDataType::Dbl,
@@ -435,15 +595,21 @@ pub fn strongest_datatype<'a>(args: &[RootDataType], workspace: &WorkSpace) -> R
                     if seq.name.as_str() == "string" {
                         strongest = &RootDataType::Dbl;
                     } else {
-                        return Err(format!("Double is arithmetically incompatble with Sequence"));
+                        return Err(ppl_error!(Type, "Double is arithmetically incompatble with Sequence"));
                     }
                 },
-                RootDataType::Structure(_) => return Err(format!("DOUBLE is arithmetically incompatble with Structure")),
-                RootDataType::Alternate(_) | RootDataType::BuiltinAlternate(_) => return Err(format!("DOUBLE is arithmetically incompatble with Alternate")),
+                RootDataType::Structure(ref structure) if structure.name.as_str() == "complex" => strongest = &args[i],
+                RootDataType::Structure(_) => return Err(ppl_error!(Type, "DOUBLE is arithmetically incompatble with Structure")),
+                RootDataType::Alternate(_) | RootDataType::BuiltinAlternate(_) => return Err(ppl_error!(Type, "DOUBLE is arithmetically incompatble with Alternate")),
             },
             RootDataType::Bool => 
             match args[i] {
                 RootDataType::Int => strongest = &RootDataType::Int,
+                RootDataType::Int64 => strongest = &RootDataType::Int64,
+                #[cfg(feature = "bignum")]
+                RootDataType::Bignum => strongest = &RootDataType::Bignum,
+                RootDataType::Rational => strongest = &RootDataType::Rational,
+                RootDataType::Decimal => strongest = &RootDataType::Decimal,
                 RootDataType::Real => strongest = &RootDataType::Real,
                 RootDataType::Dbl => strongest = &RootDataType::Dbl,
                 RootDataType::Bool => strongest = &RootDataType::Bool,
@@ -452,15 +618,21 @@ pub fn strongest_datatype<'a>(args: &[RootDataType], workspace: &WorkSpace) -> R
                     if seq.name.as_str() == "string" {
                         strongest = &RootDataType::Bool;
                     } else {
-                        return Err(format!("Bool is arithmetically incompatble with Sequence"));
+                        return Err(ppl_error!(Type, "Bool is arithmetically incompatble with Sequence"));
                     }
                 },
-                RootDataType::Structure(_) => return Err(format!("BOOL is arithmetically incompatble with Structure")),
-                RootDataType::Alternate(_) | RootDataType::BuiltinAlternate(_) => return Err(format!("BOOL is arithmetically incompatble with Alternate")),
+                RootDataType::Structure(ref structure) if structure.name.as_str() == "complex" => strongest = &args[i],
+                RootDataType::Structure(_) => return Err(ppl_error!(Type, "BOOL is arithmetically incompatble with Structure")),
+                RootDataType::Alternate(_) | RootDataType::BuiltinAlternate(_) => return Err(ppl_error!(Type, "BOOL is arithmetically incompatble with Alternate")),
             },
             RootDataType::Char => 
             match args[i] {
                 RootDataType::Int => strongest = &RootDataType::Int,
+                RootDataType::Int64 => strongest = &RootDataType::Int64,
+                #[cfg(feature = "bignum")]
+                RootDataType::Bignum => strongest = &RootDataType::Bignum,
+                RootDataType::Rational => strongest = &RootDataType::Rational,
+                RootDataType::Decimal => strongest = &RootDataType::Decimal,
                 RootDataType::Real => strongest = &RootDataType::Real,
                 RootDataType::Dbl => strongest = &RootDataType::Dbl,
                 RootDataType::Bool => strongest = &RootDataType::Bool,
@@ -469,11 +641,104 @@ pub fn strongest_datatype<'a>(args: &[RootDataType], workspace: &WorkSpace) -> R
                     if seq.name.as_str() == "string" {
                         strongest = &string_datatype;
                     } else {
-                        return Err(format!("Char is incompatble with Sequence"));
+                        return Err(ppl_error!(Type, "Char is incompatble with Sequence"));
+                    }
+                },
+                RootDataType::Structure(ref structure) if structure.name.as_str() == "complex" => strongest = &args[i],
+                RootDataType::Structure(_) => return Err(ppl_error!(Type, "CHAR is arithmetically incompatble with Structure")),
+                RootDataType::Alternate(_) | RootDataType::BuiltinAlternate(_) => return Err(ppl_error!(Type, "CHAR is arithmetically incompatble with Alternate")),
+            },
+            RootDataType::Int64 => 
+            match args[i] {
+                RootDataType::Int => strongest = &RootDataType::Int64,
+                RootDataType::Int64 => strongest = &RootDataType::Int64,
+                #[cfg(feature = "bignum")]
+                RootDataType::Bignum => strongest = &RootDataType::Bignum,
+                RootDataType::Rational => strongest = &RootDataType::Rational,
+                RootDataType::Decimal => strongest = &RootDataType::Decimal,
+                RootDataType::Real => strongest = &RootDataType::Dbl,
+                RootDataType::Dbl => strongest = &RootDataType::Dbl,
+                RootDataType::Bool => strongest = &RootDataType::Int64,
+                RootDataType::Char => strongest = &RootDataType::Int64,
+                RootDataType::Sequence(ref seq) => {
+                    if seq.name.as_str() == "string" {
+                        strongest = &RootDataType::Int64;
+                    } else {
+                        return Err(ppl_error!(Type, "Int64 is arithmetically incompatble with Sequence"));
+                    }
+                },
+                RootDataType::Structure(ref structure) if structure.name.as_str() == "complex" => strongest = &args[i],
+                RootDataType::Structure(_) => return Err(ppl_error!(Type, "INT64 is arithmetically incompatble with Structure")),
+                RootDataType::Alternate(_) | RootDataType::BuiltinAlternate(_) => return Err(ppl_error!(Type, "INT64 is arithmetically incompatble with Alternate")),
+            },
+            #[cfg(feature = "bignum")]
+            RootDataType::Bignum => 
+            match args[i] {
+                RootDataType::Int => strongest = &RootDataType::Bignum,
+                RootDataType::Int64 => strongest = &RootDataType::Bignum,
+                RootDataType::Bignum => strongest = &RootDataType::Bignum,
+                RootDataType::Rational => strongest = &RootDataType::Rational,
+                RootDataType::Decimal => strongest = &RootDataType::Decimal,
+                RootDataType::Real => strongest = &RootDataType::Dbl,
+                RootDataType::Dbl => strongest = &RootDataType::Dbl,
+                RootDataType::Bool => strongest = &RootDataType::Bignum,
+                RootDataType::Char => strongest = &RootDataType::Bignum,
+                RootDataType::Sequence(ref seq) => {
+                    if seq.name.as_str() == "string" {
+                        strongest = &RootDataType::Bignum;
+                    } else {
+                        return Err(ppl_error!(Type, "Bignum is arithmetically incompatble with Sequence"));
+                    }
+                },
+                RootDataType::Structure(ref structure) if structure.name.as_str() == "complex" => strongest = &args[i],
+                RootDataType::Structure(_) => return Err(ppl_error!(Type, "BIGNUM is arithmetically incompatble with Structure")),
+                RootDataType::Alternate(_) | RootDataType::BuiltinAlternate(_) => return Err(ppl_error!(Type, "BIGNUM is arithmetically incompatble with Alternate")),
+            },
+            RootDataType::Rational => 
+            match args[i] {
+                RootDataType::Int => strongest = &RootDataType::Rational,
+                RootDataType::Int64 => strongest = &RootDataType::Rational,
+                #[cfg(feature = "bignum")]
+                RootDataType::Bignum => strongest = &RootDataType::Rational,
+                RootDataType::Rational => strongest = &RootDataType::Rational,
+                RootDataType::Decimal => strongest = &RootDataType::Rational,
+                RootDataType::Real => strongest = &RootDataType::Dbl,
+                RootDataType::Dbl => strongest = &RootDataType::Dbl,
+                RootDataType::Bool => strongest = &RootDataType::Rational,
+                RootDataType::Char => strongest = &RootDataType::Rational,
+                RootDataType::Sequence(ref seq) => {
+                    if seq.name.as_str() == "string" {
+                        strongest = &RootDataType::Rational;
+                    } else {
+                        return Err(ppl_error!(Type, "Rational is arithmetically incompatble with Sequence"));
+                    }
+                },
+                RootDataType::Structure(ref structure) if structure.name.as_str() == "complex" => strongest = &args[i],
+                RootDataType::Structure(_) => return Err(ppl_error!(Type, "RATIONAL is arithmetically incompatble with Structure")),
+                RootDataType::Alternate(_) | RootDataType::BuiltinAlternate(_) => return Err(ppl_error!(Type, "RATIONAL is arithmetically incompatble with Alternate")),
+            },
+            RootDataType::Decimal => 
+            match args[i] {
+                RootDataType::Int => strongest = &RootDataType::Decimal,
+                RootDataType::Int64 => strongest = &RootDataType::Decimal,
+                #[cfg(feature = "bignum")]
+                RootDataType::Bignum => strongest = &RootDataType::Decimal,
+                RootDataType::Rational => strongest = &RootDataType::Rational,
+                RootDataType::Decimal => strongest = &RootDataType::Decimal,
+                RootDataType::Real => strongest = &RootDataType::Dbl,
+                RootDataType::Dbl => strongest = &RootDataType::Dbl,
+                RootDataType::Bool => strongest = &RootDataType::Decimal,
+                RootDataType::Char => strongest = &RootDataType::Decimal,
+                RootDataType::Sequence(ref seq) => {
+                    if seq.name.as_str() == "string" {
+                        strongest = &RootDataType::Decimal;
+                    } else {
+                        return Err(ppl_error!(Type, "Decimal is arithmetically incompatble with Sequence"));
                     }
                 },
-                RootDataType::Structure(_) => return Err(format!("CHAR is arithmetically incompatble with Structure")),
-                RootDataType::Alternate(_) | RootDataType::BuiltinAlternate(_) => return Err(format!("CHAR is arithmetically incompatble with Alternate")),
+                RootDataType::Structure(ref structure) if structure.name.as_str() == "complex" => strongest = &args[i],
+                RootDataType::Structure(_) => return Err(ppl_error!(Type, "DECIMAL is arithmetically incompatble with Structure")),
+                RootDataType::Alternate(_) | RootDataType::BuiltinAlternate(_) => return Err(ppl_error!(Type, "DECIMAL is arithmetically incompatble with Alternate")),
             },
             _ => continue,
         }
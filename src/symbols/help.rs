@@ -3,6 +3,7 @@
 use crate::{execution::value::{Value, sequence::SequenceInstance}, workspace::{GeneralSymbol, WorkSpace}};
 
 use super::{metadata::{FunctionArgumentList, FunctionDescription, FunctionImplementation, FunctionClass, FormalArgument, SelectorDescription, VariableDescription}, name::Name};
+use crate::error::PplError;
 
 
 
@@ -10,20 +11,20 @@ use super::{metadata::{FunctionArgumentList, FunctionDescription, FunctionImplem
 pub trait Help {
     fn help_text(&self, workspace: &WorkSpace) -> Option<String>;
     fn pretty_print(&self) -> String;
-    fn show_help(&self, name: &str, workspace: &WorkSpace) -> Result<Value,String>;
+    fn show_help(&self, name: &str, workspace: &WorkSpace) -> Result<Value,PplError>;
 }
 
 
 
 
 impl Help for GeneralSymbol {
-    fn show_help(&self, name: &str, workspace: &WorkSpace) -> Result<Value,String> {
+    fn show_help(&self, name: &str, workspace: &WorkSpace) -> Result<Value,PplError> {
         match self {
             GeneralSymbol::Datatype(d) => d.show_help (name, workspace),
             GeneralSymbol::Function(f) => f.show_help (name, workspace),
             GeneralSymbol::Selector(s) => s.show_help (name, workspace),
             GeneralSymbol::Variable(v) => v.show_help (name, workspace),
-            GeneralSymbol::Unresolved(u) => Err(format!("No help available for {}", u))
+            GeneralSymbol::Unresolved(u) => Err(ppl_error!(Name, "No help available for {}", u))
         }
     }
 
@@ -70,7 +71,7 @@ impl Help for FunctionDescription {
         format!("{:?}", self)
     }
 
-    fn show_help(&self, name: &str, _workspace: &WorkSpace) -> Result<Value,String> {
+    fn show_help(&self, name: &str, _workspace: &WorkSpace) -> Result<Value,PplError> {
         Ok(self.format_help_text(name).unwrap())
     }
 }
@@ -92,7 +93,7 @@ impl FunctionDescription {
         return result;
     }
 
-    pub fn format_help_text(&self, function_name: &str) -> Result<Value,String> {
+    pub fn format_help_text(&self, function_name: &str) -> Result<Value,PplError> {
         let result:String;
 
         match &self.implementation_class {
@@ -125,7 +126,7 @@ impl FunctionDescription {
                             if let FunctionArgumentList::Fixed(ref args) = self.arguments {
                                 result = format!("{} {} {} -> {}\n\t- {}", args[0].format(), function_name, args[1].format(), self.format_return_value(), &self.help_text);
                             } else {
-                                return Err(format!("Inconsistent internal metadata"));
+                                return Err(ppl_error!(Runtime, "Inconsistent internal metadata"));
                             }
                         }
                     },
@@ -136,7 +137,7 @@ impl FunctionDescription {
                             if let FunctionArgumentList::Fixed(ref args) = self.arguments {
                                 result = format!("{} {} {}\n\t- {}", args[0].format(), function_name, args[1].format(), &self.help_text);
                             } else {
-                                return Err(format!("Inconsistent internal metadata"));
+                                return Err(ppl_error!(Runtime, "Inconsistent internal metadata"));
                             }
                         }
                     },
@@ -149,6 +150,9 @@ impl FunctionDescription {
                     FunctionClass::NullValuedVarying(_) => {
                         result = format!("{} {} ( ... )\n\t- {}", self.format_return_value(), function_name.to_ascii_uppercase(), &self.help_text);
                     },
+                    FunctionClass::Host(_) => {
+                        result = format!("{} {} ( {} )\n\t- {}", self.format_return_value(), function_name.to_ascii_uppercase(), self.format_arguments(), &self.help_text);
+                    },
                     FunctionClass::Varying(_) => {
                         result = format!("{} {} ( ... ) -> {}\n\t- {}", self.format_return_value(), function_name.to_ascii_uppercase(), self.format_return_value(), &self.help_text);
                     },
@@ -181,7 +185,7 @@ impl Help for SelectorDescription {
         format!("{:?}", self)
     }
 
-    fn show_help(&self, name: &str, _workspace: &WorkSpace) -> Result<Value,String> {
+    fn show_help(&self, name: &str, _workspace: &WorkSpace) -> Result<Value,PplError> {
         Ok(SequenceInstance::construct_string_sequence(&format!("Selects the {} field in a structure", name.to_ascii_uppercase())))
     }
 }
@@ -196,10 +200,10 @@ impl Help for VariableDescription {
         self.cell.borrow().as_ref_to_value().help_text(workspace)
     }
 
-    fn show_help(&self, _name: &str, workspace: &WorkSpace) -> Result<Value,String> {
+    fn show_help(&self, _name: &str, workspace: &WorkSpace) -> Result<Value,PplError> {
         match self.help_text(workspace) {
             Some(t) => Ok(SequenceInstance::construct_string_sequence(&t)),
-            None => Err(format!("help not implemented for this type")),
+            None => Err(ppl_error!(Runtime, "help not implemented for this type")),
         }
     }
 }
@@ -4,13 +4,14 @@ use std::{fmt, collections::HashMap};
 use std::cell::RefCell;
 use std::rc::Rc;
 
-use crate::{execution::{runtime::executable::Executable, value::{Value, Cell}},
+use crate::{execution::{definition::define_structure, runtime::executable::Executable, value::{Value, Cell}},
     lexical::LineNumber,
     symbols::{ Name, help::Help}, 
     utility::convert_escape_sequences,
     workspace::WorkSpace};
 
-use super::datatype::RootDataType;
+use super::datatype::{RootDataType, is_an_instance_of, is_assignable_to};
+use crate::error::PplError;
 
 
 
@@ -21,16 +22,31 @@ pub trait Metadata {
 
 #[derive(Debug,Clone)]
 pub enum FunctionClass {
-    Nullary(fn(&WorkSpace) -> Result<Value, String>),
-    Monadic(fn(&Value,&WorkSpace) -> Result<Value, String>),
-    Diadic(fn(&Value,&Value,&WorkSpace) -> Result<Value, String>),
-    Triadic(fn(&Value,&Value,&Value,&WorkSpace) -> Result<Value, String>),
-    Varying(fn(&Vec<Value>,&WorkSpace) -> Result<Value, String>),
-    NullValuedNullary(fn(&WorkSpace) -> Result<(), String>),
-    NullValuedMonadic(fn(&Value,&WorkSpace) -> Result<(), String>),
-    NullValuedDiadic(fn(&Value,&Value,&WorkSpace) -> Result<(), String>),
-    NullValuedTriadic(fn(&Value,&Value,&Value,&WorkSpace) -> Result<(), String>),
-    NullValuedVarying(fn(&Vec<Value>,&WorkSpace) -> Result<(), String>),
+    Nullary(fn(&WorkSpace) -> Result<Value,PplError>),
+    Monadic(fn(&Value,&WorkSpace) -> Result<Value,PplError>),
+    Diadic(fn(&Value,&Value,&WorkSpace) -> Result<Value,PplError>),
+    Triadic(fn(&Value,&Value,&Value,&WorkSpace) -> Result<Value,PplError>),
+    Varying(fn(&Vec<Value>,&WorkSpace) -> Result<Value,PplError>),
+    NullValuedNullary(fn(&WorkSpace) -> Result<(),PplError>),
+    NullValuedMonadic(fn(&Value,&WorkSpace) -> Result<(),PplError>),
+    NullValuedDiadic(fn(&Value,&Value,&WorkSpace) -> Result<(),PplError>),
+    NullValuedTriadic(fn(&Value,&Value,&Value,&WorkSpace) -> Result<(),PplError>),
+    NullValuedVarying(fn(&Vec<Value>,&WorkSpace) -> Result<(),PplError>),
+    Host(HostFunction),
+}
+
+//  A function supplied by a program embedding PPL.  Unlike the builtins, it's a closure, so it can carry state
+//  of its own.
+
+pub type HostClosure = dyn Fn(&[Value]) -> Result<Value,PplError>;
+
+#[derive(Clone)]
+pub struct HostFunction(pub Rc<HostClosure>);
+
+impl fmt::Debug for HostFunction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "HostFunction")
+    }
 }
 
 
@@ -133,6 +149,29 @@ impl FunctionDescription {
                 true
             }
         }
+
+        //  True if each value passed by value suits its argument: exactly, as an instance of the argument's
+        //  datatype, or else as a value that converts to it
+
+        pub fn accepts_arguments(&self, actual_argument_values: &[Value], exactly: bool, workspace: &WorkSpace) -> Result<bool,PplError> {
+            if let FunctionArgumentList::Fixed(fixed) = &self.arguments {
+                for (formal_argument, value) in fixed.iter().zip(actual_argument_values) {
+                    if let ArgumentMechanism::ByValue = formal_argument.mechanism {
+                        let datatype = workspace.resolve_datatype(&formal_argument.datatype.as_string())?;
+                        let value = value.dereference();
+                        let accepted = if exactly {
+                            is_an_instance_of(&value, &datatype, workspace)?
+                        } else {
+                            is_assignable_to(&value, &datatype, workspace)?
+                        };
+                        if !accepted {
+                            return Ok(false);
+                        }
+                    }
+                }
+            }
+            Ok(true)
+        }
     }
 
 impl fmt::Display for FunctionDescription {
@@ -331,7 +370,7 @@ impl Help for MetaDataType {
         self.root_data_type.pretty_print()    
     }
 
-    fn show_help(&self, name: &str, workspace: &WorkSpace) -> Result<Value,String> {
+    fn show_help(&self, name: &str, workspace: &WorkSpace) -> Result<Value,PplError> {
         self.root_data_type.show_help(name, workspace)
     }
 }
@@ -402,6 +441,14 @@ pub struct MetaStructure {
     pub members: Vec<MetaStructureMember>,
 }
 
+impl MetaStructure {
+    pub fn new(name: &str, members: Vec<(&str, String)>) -> MetaStructure {
+        MetaStructure {
+            name: Name::from_str(name),
+            members: members.into_iter().map(|(member, datatype)| MetaStructureMember { name: Name::from_str(member), data_type: MetaDataTypeName::from_string(&datatype) }).collect() }
+    }
+}
+
 impl fmt::Display for MetaStructure {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "${} = [", self.name)?;
@@ -447,6 +494,11 @@ impl Metadata for MetaSequence {
 
 pub fn init(workspace: &WorkSpace) {
     workspace.add_datatype("int", MetaDataType::from_str("int", RootDataType::Int));
+    workspace.add_datatype("int64", MetaDataType::from_str("int64", RootDataType::Int64));
+    #[cfg(feature = "bignum")]
+    workspace.add_datatype("bignum", MetaDataType::from_str("bignum", RootDataType::Bignum));
+    workspace.add_datatype("rational", MetaDataType::from_str("rational", RootDataType::Rational));
+    workspace.add_datatype("decimal", MetaDataType::from_str("decimal", RootDataType::Decimal));
     workspace.add_datatype("real", MetaDataType::from_str("real", RootDataType::Real));
     workspace.add_datatype("dbl", MetaDataType::from_str("dbl", RootDataType::Dbl));
     workspace.add_datatype("bool", MetaDataType::from_str("bool", RootDataType::Bool));
@@ -455,33 +507,52 @@ pub fn init(workspace: &WorkSpace) {
         RootDataType::Sequence(MetaSequence { name: Name::from_str("string"), lower_index_bound: 1, upper_index_bound: None, member_type: MetaDataTypeName::from_str("char") })));
     workspace.add_datatype("tuple", MetaDataType::from_str("tuple",
         RootDataType::Sequence(MetaSequence { name: Name::from_str("tuple"), lower_index_bound: 1, upper_index_bound: None, member_type: MetaDataTypeName::from_str("general") })));
+    define_structure(MetaStructure::new("complex", vec![("rp", String::from("real")), ("ip", String::from("real"))]), workspace);
     workspace.add_datatype("arith", MetaDataType::from_str("arith",
-        RootDataType::Alternate(MetaAlternate::new("arith",
-                                vec![MetaDataTypeName::from_str("int"),
+        RootDataType::Alternate(MetaAlternate::new("arith", integer_datatypes().into_iter().chain(
+                                vec![MetaDataTypeName::from_str("rational"),
+                                     MetaDataTypeName::from_str("decimal"),
                                      MetaDataTypeName::from_str("real"),
-                                     MetaDataTypeName::from_str("dbl")]))));
+                                     MetaDataTypeName::from_str("dbl"),
+                                     MetaDataTypeName::from_str("complex")]).collect()))));
     workspace.add_datatype("atomic", MetaDataType::from_str("atomic",
-        RootDataType::Alternate(MetaAlternate::new("atomic",
-                                vec![MetaDataTypeName::from_str("int"),
+        RootDataType::Alternate(MetaAlternate::new("atomic", integer_datatypes().into_iter().chain(
+                                vec![MetaDataTypeName::from_str("rational"),
+                                     MetaDataTypeName::from_str("decimal"),
                                      MetaDataTypeName::from_str("real"),
                                      MetaDataTypeName::from_str("dbl"),
                                      MetaDataTypeName::from_str("bool"),
-                                     MetaDataTypeName::from_str("char")]))));
+                                     MetaDataTypeName::from_str("char")]).collect()))));
     workspace.add_datatype("structure", MetaDataType::from_str("structure", RootDataType::BuiltinAlternate(BuiltAlternates::Structure)));
     workspace.add_datatype("sequence", MetaDataType::from_str("sequence", RootDataType::BuiltinAlternate(BuiltAlternates::Sequence)));
     workspace.add_datatype("v.sequence", MetaDataType::from_str("v.sequence", RootDataType::BuiltinAlternate(BuiltAlternates::VSequence)));
     workspace.add_datatype("general", MetaDataType::from_str("general", RootDataType::BuiltinAlternate(BuiltAlternates::General)));
 }
 
+fn integer_datatypes() -> Vec<MetaDataTypeName> {
+    let mut result = vec![MetaDataTypeName::from_str("int"), MetaDataTypeName::from_str("int64")];
+    if cfg!(feature = "bignum") {
+        result.push(MetaDataTypeName::from_str("bignum"));
+    }
+    result
+}
+
 pub fn get_system_datatypes() -> Vec<String> {
     let mut result = Vec::new();
     result.push(String::from("int"));
+    result.push(String::from("int64"));
+    if cfg!(feature = "bignum") {
+        result.push(String::from("bignum"));
+    }
+    result.push(String::from("rational"));
+    result.push(String::from("decimal"));
     result.push(String::from("real"));
     result.push(String::from("dbl"));
     result.push(String::from("bool"));
     result.push(String::from("char"));
     result.push(String::from("string"));
     result.push(String::from("tuple"));
+    result.push(String::from("complex"));
     result.push(String::from("arith"));
     result.push(String::from("atomic"));
     result.push(String::from("structure"));
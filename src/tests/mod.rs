@@ -15,6 +15,7 @@ mod tests {
     mod chapter12;
     mod chapter13;
     mod extensions;
+    mod math;
     mod negative;
 
     #[test]
@@ -1,4 +1,4 @@
-use crate::{tests::tests::{run, run_divert_stdout}, workspace::WorkSpace};
+use crate::{error::PplError, execution::{editor::FunctionEditor, evaluate}, repl::{self, ScriptedInput}, tests::tests::{run, run_divert_stdout}, workspace::WorkSpace};
 
 
 #[test]
@@ -38,4 +38,1050 @@ fn extensions() {
     assert_eq!("n=1n!=1", run_divert_stdout("f3(1)", &workspace));
     assert_eq!("n!=1", run_divert_stdout("f3(2)", &workspace));
       
-}
\ No newline at end of file
+}
+
+#[cfg(unix)]
+#[test]
+fn edit() {
+    let workspace = WorkSpace::new();
+
+    assert_eq!("", run(
+        r#"$f(n)
+        f_n+1
+        $"#, &workspace));
+    assert_eq!("3", run("f(2)", &workspace));
+
+    //  The editor is set for the workspace, in place of VISUAL or EDITOR; sed stands in for a user
+
+    run(r#"edit.editor("sed -i s/n+1/n+2/")"#, &workspace);
+    run("edit($f)", &workspace);
+    assert_eq!("4", run("f(2)", &workspace));
+
+    //  A definition that doesn't parse leaves the old one in place
+
+    run(r#"edit.editor("sed -i s/n+2/n+*/")"#, &workspace);
+    assert_eq!("", run("edit($f)", &workspace));
+    assert_eq!("4", run("f(2)", &workspace));
+
+    //  An editor that quits without saving changes nothing, and one that fails cancels the edit
+
+    run(r#"edit.editor("true")"#, &workspace);
+    assert_eq!("No changes", run("edit($f)", &workspace));
+    run(r#"edit.editor("false")"#, &workspace);
+    assert_eq!("", run("edit($f)", &workspace));
+    assert_eq!("4", run("f(2)", &workspace));
+    assert_eq!("false", run("edit.editor", &workspace));
+}
+
+
+#[test]
+fn line_editor() {
+    let workspace = WorkSpace::new();
+
+    assert_eq!("", run(
+        r#"$f(n)
+        f_n
+        loop: f_f*2
+        (f<100)-->loop
+        $"#, &workspace));
+    assert_eq!("128", run("f(1)", &workspace));
+    assert_eq!("", run("stop($f, 3)", &workspace));
+    assert_eq!("", run("trace($f, 2)", &workspace));
+
+    //  Insert, replace and delete lines by number; the numbers don't shift until editing ends
+
+    assert_eq!(Some(String::from("f")), FunctionEditor::is_edit_request("$f", &workspace));
+    assert_eq!(None, FunctionEditor::is_edit_request("$g", &workspace));
+    assert!(matches!(FunctionEditor::open("g", &workspace), Err(PplError::Name(_))));
+    let mut editor = FunctionEditor::open("f", &workspace).unwrap();
+    assert_eq!("[4] ", editor.prompt());
+    assert_eq!("", editor.apply("[1.5] f_f+1", &workspace).unwrap());
+    assert_eq!("[1.6] ", editor.prompt());
+    assert_eq!("", editor.apply("[~1]", &workspace).unwrap());
+    assert_eq!("", editor.apply("[0] $f(n); k", &workspace).unwrap());
+    assert_eq!("", editor.apply("[1] f_n", &workspace).unwrap());
+    assert_eq!("$f(n); k\n[1] f_n\n[1.5] f_f+1\n[2] loop: f_f*2\n[3] (f<100)-->loop\n", editor.apply("[?]", &workspace).unwrap());
+    assert!(editor.apply("[4.5] f_f+", &workspace).unwrap().len() > 0);
+    assert_eq!("", editor.apply("[~4.5]", &workspace).unwrap());
+    assert!(editor.apply("[~7]", &workspace).is_err());
+    assert!(editor.apply("[x] f_1", &workspace).is_err());
+    assert!(matches!(editor.apply("[2 f_1", &workspace), Err(PplError::Syntax(_))));
+    assert!(matches!(editor.apply("[4294967.5] f_1", &workspace), Err(PplError::Argument(_))));
+    assert!(matches!(editor.apply("[4294967] f_1", &workspace), Err(PplError::Argument(_))));
+    assert!(!editor.apply("[?]", &workspace).unwrap().contains("4294967"));
+    editor.close(&workspace).unwrap();
+
+    assert_eq!("$f(n:general); k\n[1] f_n\n[2] f_f+1\n[3] loop: f_f*2\n[4] (f<100)-->loop\n[5] $\n", run("?f", &workspace));
+
+    //  The stop and trace points followed their lines
+
+    if let crate::symbols::metadata::FunctionImplementation::User(body) = &workspace.try_get_user_function("f").unwrap().implementation_class {
+        assert!(body.executable.get_statement_by_line_number(4).unwrap().is_stop_set());
+        assert!(!body.executable.get_statement_by_line_number(3).unwrap().is_stop_set());
+        assert!(body.executable.get_statement_by_line_number(3).unwrap().is_trace_set());
+    }
+    assert_eq!("", run("unstop($f)", &workspace));
+    assert_eq!("", run("untrace($f)", &workspace));
+    assert_eq!("192", run("f(2)", &workspace));
+
+    //  A function whose edited text won't parse is left alone
+
+    let mut editor = FunctionEditor::open("f", &workspace).unwrap();
+    editor.apply("[2] f_f+", &workspace).unwrap();
+    assert!(editor.close(&workspace).is_err());
+    assert!(run("?f", &workspace).contains("[2] f_f+1"));
+
+    //  The header can change the arguments but not the name, which would lose the original function
+
+    let mut editor = FunctionEditor::open("f", &workspace).unwrap();
+    assert!(editor.apply("[0] $g(n)", &workspace).is_err());
+    assert_eq!("", editor.apply("[0] $F(n)", &workspace).unwrap());
+    editor.close(&workspace).unwrap();
+    assert_eq!("192", run("f(2)", &workspace));
+    assert_eq!("", run("g", &workspace));
+
+    //  An if spans the lines of its branches, and its stop still follows it
+
+    assert_eq!("", run(
+        r#"$h(n)
+        if n<0
+        h_-1
+        else
+        h_1
+        $"#, &workspace));
+    assert_eq!("", run("stop($h, 1)", &workspace));
+    assert_eq!("", run("trace($h, 4)", &workspace));
+    let mut editor = FunctionEditor::open("h", &workspace).unwrap();
+    assert_eq!("", editor.apply("[0.5] h_0", &workspace).unwrap());
+    editor.close(&workspace).unwrap();
+    if let crate::symbols::metadata::FunctionImplementation::User(body) = &workspace.try_get_user_function("h").unwrap().implementation_class {
+        let settings: Vec<(u32, bool, bool)> = (1..=body.executable.get_statement_count())
+            .map(|index| body.executable.get_statement(index).unwrap())
+            .map(|statement| (statement.as_line_number(), statement.is_stop_set(), statement.is_trace_set()))
+            .filter(|(line_number, _, _)| (1..=5).contains(line_number))
+            .collect();
+        assert!(settings.contains(&(1, false, false)));
+        assert!(settings.contains(&(2, true, false)));
+        assert!(settings.contains(&(3, false, false)));
+        assert!(settings.contains(&(5, false, true)));
+    }
+}
+
+
+#[test]
+fn repl() {
+    let workspace = WorkSpace::new();
+
+    let mut input = ScriptedInput::new("$f(n)\nf_n*2\n$\nf(3)\n$f\n[1] f_n*3\n$\nf(3)\ncounter_1\nundefined(1)");
+    let mut output = Vec::new();
+    repl::run(&mut input, &mut output, &workspace).unwrap();
+    let output = String::from_utf8(output).unwrap();
+    let lines: Vec<&str> = output.lines().collect();
+    assert_eq!(vec!["", "6", "9", "1"], lines[..4].to_vec());
+    assert!(lines[4].starts_with("Error: "));
+    assert_eq!(vec![repl::IMMEDIATE_MODE_PROMPT, "[1] ", "[2] ", repl::IMMEDIATE_MODE_PROMPT, repl::IMMEDIATE_MODE_PROMPT, "[2] ", "[2] "], input.prompts[..7].to_vec());
+
+    //  Tab completion draws on every symbol table
+
+    assert_eq!((0, vec![String::from("print")]), repl::complete("pri", 3, &workspace));
+    assert_eq!((2, vec![String::from("counter")]), repl::complete("x_cou", 5, &workspace));
+    assert_eq!((2, vec![String::from("f"), String::from("false"), String::from("feature"), String::from("floor"), String::from("format"), String::from("frame")]), repl::complete("x_f", 3, &workspace));
+}
+
+
+#[test]
+fn scripts() {
+    let workspace = WorkSpace::new();
+    workspace.set_arguments(vec![String::from("first"), String::from("second")]);
+
+    assert_eq!("[first, second]", run("args()", &workspace));
+    assert_eq!(Ok(()), repl::run_script("test.ppl", "$double(n)\ndouble_n*2\n$\n\nx_double(length(args()))", &workspace));
+    assert_eq!("4", run("x", &workspace));
+
+    //  Errors carry the script's name and the line they came from, and end the script
+
+    let error = repl::run_script("test.ppl", "y_1\n\ny_undefined(1)\ny_2", &workspace).unwrap_err();
+    assert!(error.to_string().starts_with("test.ppl:3: "));
+    assert_eq!((Some("test.ppl"), Some(3)), (error.source_name(), error.line_number()));
+    assert_eq!("@[3] y_undefined(1)", error.traceback().last().unwrap().to_string());
+    let error = repl::run_script("test.ppl", "$bad(n)\nbad_undefined(n)\n$\nbad(1)", &workspace).unwrap_err();
+    let frames: Vec<String> = error.traceback().iter().map(|frame| frame.to_string()).collect();
+    assert_eq!((Some(4), vec![String::from("@bad [1] bad_undefined(n)"), String::from("@[4] bad(1)")]), (error.line_number(), frames));
+    assert_eq!("1", run("y", &workspace));
+}
+
+#[test]
+fn char_codes() {
+    let workspace = WorkSpace::new();
+    assert_eq!("A", run("char(65)", &workspace));
+    assert_eq!("2", run("length(string('a, char(10)))", &workspace));
+    assert_eq!("true", run("char(10)=char(10)", &workspace));
+
+    //  Only the constructor takes a code
+
+    run("$initial(c:char)\ninitial_c\n$", &workspace);
+    assert_eq!("b", run("initial('b)", &workspace));
+    assert!(evaluate("initial(98)", &workspace).is_err());
+    assert!(evaluate("char(-1)", &workspace).is_err());
+}
+
+#[test]
+fn float_literals() {
+
+    //  A literal is the nearest float to its decimal value, so that a saved workspace reads back the numbers it wrote
+
+    let workspace = WorkSpace::new();
+    assert_eq!("true", run("0.3D0=dbl(3)/10", &workspace));
+    assert_eq!("true", run("0.7D0=dbl(7)/10", &workspace));
+    assert_eq!("true", run("2.5E-3=real(25)/10000", &workspace));
+}
+
+#[test]
+fn workspace_files() {
+    let filename = std::env::temp_dir().join(format!("ppl-workspace-{}.ppl", std::process::id())).display().to_string();
+    let workspace = WorkSpace::new();
+
+    for source in [
+        "$complex=[rp:real, ip:real]",
+        "$circle=[centre:complex, radius:dbl, label:string]",
+        "$triple=[-1:1]real",
+        "$vec=[0:]int",
+        "$num=int!real",
+        "$nums=[1:]num",
+        "$cadd(a, b)\ncadd_complex(add(a[rp], b[rp]), add(a[ip], b[ip]))\n$",
+        r#"binary("+", cadd)"#,
+        "c_complex(1.5, real(2.0E-7))",
+        r#"ring_circle(c, dbl(0.1), "a ""ring""")"#,
+        "t_triple(1, 2, 3)",
+        "v_vec(10, 20)",
+        "n_nums(1, real(2.5), 3)",
+        "b_true",
+        "d_dbl(1.0D30)/3",
+        r#"s_"text""#,
+        "nl_string('a, char(10), 'b)",
+        "alias__c",
+    ] {
+        evaluate(source, &workspace).unwrap_or_else(|e| panic!("{}: {}", source, e));
+    }
+    run(&format!(r#"write("{}")"#, filename), &workspace);
+
+    let saved = std::fs::read_to_string(&filename).unwrap();
+    assert!(saved.starts_with("... PPL workspace, format 2\n"));
+    assert!(saved.contains("... datatypes: circle num nums triple vec\n"));
+    assert!(saved.contains("... functions: cadd\n"));
+    assert!(saved.contains("... operators: +\n"));
+    assert!(saved.contains("... variables: alias b c d n nl ring s t v\n"));
+
+    let reloaded = WorkSpace::new();
+    assert_eq!(Ok(()), crate::workspace::io::read(&filename, &reloaded));
+    //  Each expression has to work in both, so that an error on each side can't pass for a match
+
+    for expression in ["c", "ring", "type(ring)", "t", "t[-1]", "v", "v[0]", "n", "type(n[2])", "b", "d", "s", "nl", "length(nl)", "alias", "c+c"] {
+        let expected = evaluate(expression, &workspace).unwrap_or_else(|e| panic!("{}: {}", expression, e));
+        assert_eq!(Ok(expected), evaluate(expression, &reloaded).map_err(|e| e.to_string()), "{}", expression);
+    }
+    assert_eq!("[rp:3., ip:0.0000004]", run("c+c", &reloaded));
+    assert_eq!("true", run("d=dbl(1.0D30)/3", &reloaded));
+
+    //  Loading carries on past a bad item, and reports where each problem was
+
+    std::fs::write(&filename, "... PPL workspace, format 2\n... variables: a b c\na_1\nb_nosuchtype(1)\nc_3\n").unwrap();
+    let partial = WorkSpace::new();
+    let error = crate::workspace::io::read(&filename, &partial).unwrap_err();
+    assert!(error.to_string().starts_with(&format!("{}:4: ", filename)), "{}", error);
+    assert_eq!("1", run("a", &partial));
+    assert_eq!("3", run("c", &partial));
+
+    //  A file that's been cut short is caught by its manifest, and a newer format is refused
+
+    std::fs::write(&filename, "... PPL workspace, format 2\n... variables: a b\na_1\n").unwrap();
+    let error = crate::workspace::io::read(&filename, &WorkSpace::new()).unwrap_err();
+    assert_eq!(format!("{}: the file is incomplete, missing b", filename), error.to_string());
+    std::fs::write(&filename, "... PPL workspace, format 3\na_1\n").unwrap();
+    assert!(crate::workspace::io::read(&filename, &WorkSpace::new()).is_err());
+
+    let _ = std::fs::remove_file(&filename);
+}
+
+#[test]
+fn workspace_files_sharing() {
+    let filename = std::env::temp_dir().join(format!("ppl-sharing-{}.ppl", std::process::id())).display().to_string();
+    let workspace = WorkSpace::new();
+
+    for source in [
+        "$foo=[1:3]general",
+        "$pntr=[val:general]",
+        "x_1",
+        "alias__x",
+        "chain__alias",
+        "y_foo(1, 2, 3)",
+        "y[2]__y",
+        "w_foo(1, 2, 3)",
+        "w[1]__w[3]",
+        "p_pntr(0)",
+        "p[val]__w[2]",
+        "gone_[4, 5]",
+        "first__gone",
+        "second__gone",
+        "erase(gone)",
+    ] {
+        evaluate(source, &workspace).unwrap_or_else(|e| panic!("{}: {}", source, e));
+    }
+    crate::workspace::io::write(&filename, &workspace).unwrap();
+
+    let reloaded = WorkSpace::new();
+    assert_eq!(Ok(()), crate::workspace::io::read(&filename, &reloaded));
+    for expression in ["x", "alias", "chain", "y", "w", "p", "first", "second", "gone"] {
+        assert_eq!(run(expression, &workspace), run(expression, &reloaded), "{}", expression);
+    }
+
+    //  Changes show through every reference, just as they did before saving
+
+    for changes in [
+        ["x_7", "alias", "chain"],
+        ["y[1]_10", "y", "y[2][2][1]"],
+        ["w[3]_8", "w", "w[1]"],
+        ["w[2]_9", "p", "p[val]"],
+    ] {
+        for expression in changes {
+            assert_eq!(run(expression, &workspace), run(expression, &reloaded), "{}", expression);
+        }
+    }
+
+    let _ = std::fs::remove_file(&filename);
+}
+
+#[test]
+fn workspace_copy() {
+    let filename = std::env::temp_dir().join(format!("ppl-copy-{}.ppl", std::process::id())).display().to_string();
+    let source = WorkSpace::new();
+    for text in [
+        "$complex=[rp:real, ip:real]",
+        "$cadd(a, b)\ncadd_complex(add(a[rp], b[rp]), add(a[ip], b[ip]))\n$",
+        r#"binary("+", cadd)"#,
+        "$twice(n)\ntwice_2*n\n$",
+        "c_complex(1, 2)",
+        "x_1",
+        "alias__x",
+        "$pair=[first:int, second:int]",
+        "$swap(p)\nswap_pair(p[second], p[first])\n$",
+        "q_pair(1, 2)",
+    ] {
+        evaluate(text, &source).unwrap_or_else(|e| panic!("{}: {}", text, e));
+    }
+    crate::workspace::io::write(&filename, &source).unwrap();
+
+    let workspace = WorkSpace::new();
+    assert_eq!("datatypes: pair\nfunctions: cadd swap twice\noperators: +\nvariables: alias c q x", run(&format!(r#"contents("{}")"#, filename), &workspace));
+
+    //  Only what's asked for comes over
+
+    run("x_100", &workspace);
+    run("$twice(n)\ntwice_3*n\n$", &workspace);
+    assert_eq!("cadd, +, c", run(&format!(r#"copy("{}", "cadd", "+", "c")"#, filename), &workspace));
+    assert_eq!("[rp:2., ip:4.]", run("c+c", &workspace));
+    assert_eq!("100", run("x", &workspace));
+
+    //  By default a name that's in use is refused, and nothing is copied
+
+    assert_eq!("fail", run("copy.policy()", &workspace).to_ascii_lowercase());
+    assert!(crate::workspace::io::copy(&filename, &[String::from("twice"), String::from("x")], &workspace).is_err());
+    assert_eq!("100", run("x", &workspace));
+    assert_eq!("300", run("twice(100)", &workspace));
+
+    //  Renaming keeps both, and overwriting replaces the old one
+
+    run(r#"copy.policy("rename")"#, &workspace);
+    assert_eq!("twice as twice.1", run(&format!(r#"copy("{}", "twice")"#, filename), &workspace));
+    assert_eq!("300", run("twice(100)", &workspace));
+    assert_eq!("200", run("twice.1(100)", &workspace));
+
+    //  What refers to a renamed datatype or function follows it
+
+    run("$pair=[left:int, right:int]", &workspace);
+    assert_eq!("pair as pair.1, swap, q", run(&format!(r#"copy("{}", "pair", "swap", "q")"#, filename), &workspace));
+    assert_eq!("[first:2, second:1]", run("swap(q)", &workspace));
+    assert_eq!("true", run("q==pair.1", &workspace));
+    assert_eq!("true", run("swap(q)==pair.1", &workspace));
+    assert_eq!("cadd as cadd.1, c as c.1", run(&format!(r#"copy("{}", "cadd", "c")"#, filename), &workspace));
+    assert_eq!("[rp:2., ip:4.]", run("cadd.1(c.1, c.1)", &workspace));
+    run(r#"copy.policy("overwrite")"#, &workspace);
+    assert_eq!("twice", run(&format!(r#"copy("{}", "twice")"#, filename), &workspace));
+    assert_eq!("200", run("twice(100)", &workspace));
+
+    //  Variables copied together keep sharing their cells
+
+    assert_eq!("alias, x", run(&format!(r#"copy("{}", "alias", "x")"#, filename), &workspace));
+    run("x_5", &workspace);
+    assert_eq!("5", run("alias", &workspace));
+
+    assert!(crate::workspace::io::copy(&filename, &[String::from("nosuchname")], &workspace).is_err());
+
+    //  An overwrite that fails partway through leaves the workspace as it was; q needs the pair datatype
+
+    let workspace = WorkSpace::new();
+    run("x_7", &workspace);
+    run("$twice(n)\ntwice_4*n\n$", &workspace);
+    run(r#"copy.policy("overwrite")"#, &workspace);
+    assert!(crate::workspace::io::copy(&filename, &[String::from("twice"), String::from("x"), String::from("q")], &workspace).is_err());
+    assert_eq!("7", run("x", &workspace));
+    assert_eq!("4", run("twice(1)", &workspace));
+    assert!(evaluate("q", &workspace).is_err());
+    let _ = std::fs::remove_file(&filename);
+}
+
+#[test]
+fn stopped_frames() {
+    let workspace = WorkSpace::new();
+    evaluate("$outer(x)\ny_x+1\nouter_inner(y)+y\n$", &workspace).unwrap();
+    evaluate("$inner(x)\ninner_x*2\n$", &workspace).unwrap();
+    evaluate("stop(inner, 1)", &workspace).unwrap();
+    assert!(evaluate("outer(5)", &workspace).is_err());
+
+    //  Immediate mode starts in the stopped function, and can move out to its caller and back
+
+    assert!(run("x", &workspace).starts_with("6"));
+    assert!(run("frame()", &workspace).starts_with("0: @inner [1] inner_x*2"));
+    assert!(run("up()", &workspace).starts_with("1: @outer [2] outer_inner(y)+y"));
+    assert!(run("x", &workspace).starts_with("5"));
+    assert!(evaluate("up()", &workspace).is_err());
+
+    //  Assignments change the selected frame's variables, and the function resumes with them
+
+    run("y_100", &workspace);
+    assert!(run("down()", &workspace).starts_with("0: @inner"));
+    run("x_1", &workspace);
+    assert!(evaluate("down()", &workspace).is_err());
+    assert_eq!("102", run("-->1", &workspace));
+    assert!(evaluate("frame()", &workspace).is_err());
+}
+
+#[test]
+fn conditional_breakpoints() {
+    let workspace = WorkSpace::new();
+    evaluate("$count(n)\ni_0\nloop: i_i+1\ntotal_total+i\n(i<n)-->loop\ncount_i\n$", &workspace).unwrap();
+    evaluate("total_0", &workspace).unwrap();
+
+    //  A condition is evaluated in the function, and a count skips the first hits
+
+    assert!(matches!(evaluate(r#"stop.if(count, 3, "i=")"#, &workspace), Err(PplError::Syntax(_))));
+    evaluate(r#"stop.if(count, 3, "i=5")"#, &workspace).unwrap();
+    assert!(matches!(evaluate("count(10)", &workspace), Err(PplError::Stop(_))));
+    assert!(run("i", &workspace).starts_with("5"));
+    assert!(run("-->3", &workspace).starts_with("10"));
+
+    evaluate("stop.after(count, 3, 4)", &workspace).unwrap();
+    assert!(evaluate("count(10)", &workspace).is_err());
+    assert!(run("i", &workspace).starts_with("4"));
+    assert!(evaluate("-->3", &workspace).is_err());
+    assert!(run("i", &workspace).starts_with("5"));
+    evaluate("unstop(count)", &workspace).unwrap();
+    assert!(run("-->3", &workspace).starts_with("10"));
+
+    //  A watchpoint stops the statement after the variable changes, but not for changes made in immediate mode
+
+    evaluate("total_0", &workspace).unwrap();
+    evaluate("watch(total)", &workspace).unwrap();
+    let error = evaluate("count(3)", &workspace).unwrap_err();
+    assert!(matches!(error, PplError::Stop(_)), "{:?}", error);
+    assert_eq!("total changed from 0 to 1", error.message());
+    assert_eq!(Some(4), error.line_number());
+    run("total_100", &workspace);
+    let error = evaluate("-->4", &workspace).unwrap_err();
+    assert_eq!("total changed from 100 to 102", error.message());
+    evaluate("unwatch()", &workspace).unwrap();
+    assert!(run("-->4", &workspace).starts_with("3"));
+    assert_eq!("105", run("total", &workspace));
+
+    //  Writing the value a variable already has is still a write, and so is writing one of its members
+
+    evaluate("$pair=[first:int, second:int]", &workspace).unwrap();
+    evaluate("$keep()\ntotal_total\nrange[second]_9\nkeep_0\n$", &workspace).unwrap();
+    evaluate("range_pair(1, 2)", &workspace).unwrap();
+    evaluate("watch(total, range)", &workspace).unwrap();
+    let error = evaluate("keep()", &workspace).unwrap_err();
+    assert_eq!("total was set to 105 again", error.message());
+    let error = evaluate("-->2", &workspace).unwrap_err();
+    assert_eq!("range changed from [first:1, second:2] to [first:1, second:9]", error.message());
+}
+
+#[test]
+fn stepping() {
+    let workspace = WorkSpace::new();
+    evaluate("$outer(x)\ny_x+1\nouter_inner(y)+y\n$", &workspace).unwrap();
+    evaluate("$inner(x)\nz_x*2\ninner_z+1\n$", &workspace).unwrap();
+    evaluate("stop(outer, 1)", &workspace).unwrap();
+    assert!(evaluate("outer(5)", &workspace).is_err());
+
+    let stopped_at = |command: &str| {
+        let error = evaluate(command, &workspace).unwrap_err();
+        assert!(matches!(error, PplError::Stop(_)), "{:?}", error);
+        (error.function_name().map(String::from), error.line_number())
+    };
+
+    //  Step runs one statement, stepping into calls; over runs them; out runs to the caller
+
+    assert_eq!((Some(String::from("outer")), Some(2)), stopped_at("step()"));
+    assert_eq!((Some(String::from("inner")), Some(1)), stopped_at("step()"));
+    assert_eq!((Some(String::from("inner")), Some(2)), stopped_at("step.over()"));
+    assert_eq!((Some(String::from("outer")), Some(3)), stopped_at("step.out()"));
+    assert!(run("outer", &workspace).starts_with("19"));
+    assert!(run("step()", &workspace).starts_with("19"));
+
+    assert!(evaluate("outer(5)", &workspace).is_err());
+    assert_eq!((Some(String::from("outer")), Some(2)), stopped_at("step.over()"));
+    assert_eq!((Some(String::from("outer")), Some(3)), stopped_at("step.over()"));
+    assert!(run("step.over()", &workspace).starts_with("19"));
+    assert!(evaluate("step()", &workspace).is_err());
+}
+
+#[test]
+fn trace_log() {
+    let workspace = WorkSpace::new();
+    evaluate("$outer(x)\nouter_inner(x+1)+x\n$", &workspace).unwrap();
+    evaluate("$inner(x)\ninner_x*2\n$", &workspace).unwrap();
+
+    //  Strip the timestamps, which can't be predicted
+
+    let entries = |workspace: &WorkSpace| -> Vec<String> {
+        run("trace.log()", workspace).lines()
+            .map(|line| line.split_once(' ').unwrap().1.to_string())
+            .collect()
+    };
+
+    evaluate(r#"trace.log("", "text")"#, &workspace).unwrap();
+    assert_eq!("17", run("outer(5)", &workspace));
+    assert_eq!(vec!["  1 outer(5)", "  2   inner(6)", "  2   inner = 12", "  1 outer = 17"], entries(&workspace));
+
+    evaluate(r#"trace.log("", "json")"#, &workspace).unwrap();
+    assert_eq!("12", run("inner(6)", &workspace));
+    assert_eq!(vec![
+        r#""depth":1,"event":"call","function":"inner","arguments":["6"]}"#,
+        r#""depth":1,"event":"return","function":"inner","value":"12"}"#],
+        run("trace.log()", &workspace).lines().map(|line| line.split_once(',').unwrap().1.to_string()).collect::<Vec<String>>());
+
+    let path = std::env::temp_dir().join(format!("ppl-trace-{}.log", std::process::id()));
+    evaluate(format!(r#"trace.log("{}", "text")"#, path.display()).as_str(), &workspace).unwrap();
+    run("inner(1)", &workspace);
+    evaluate("untrace.log()", &workspace).unwrap();
+    let logged = std::fs::read_to_string(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert!(logged.lines().last().unwrap().ends_with("  1 inner = 2"));
+
+    assert!(evaluate("trace.log()", &workspace).is_err());
+    assert!(matches!(evaluate(r#"trace.log("", "xml")"#, &workspace), Err(PplError::Argument(_))));
+}
+
+#[test]
+fn profiling() {
+    let workspace = WorkSpace::new();
+    evaluate("$sum(n)\nsum_0\nloop: sum_sum+term(n)\n((n_n-1)>0)-->loop\n$", &workspace).unwrap();
+    evaluate("$term(x)\nterm_x\n$", &workspace).unwrap();
+    assert!(evaluate("profile.report()", &workspace).is_err());
+
+    evaluate("profile()", &workspace).unwrap();
+    assert_eq!("15", run("sum(5)", &workspace));
+    evaluate("unprofile()", &workspace).unwrap();
+    run("sum(5)", &workspace);
+
+    //  Times vary from run to run, so look at the counts that follow them
+
+    let report = run("profile.report()", &workspace);
+    let counts = |suffix: &str| -> Vec<String> {
+        report.lines().filter(|line| line.ends_with(suffix))
+            .map(|line| line.split_whitespace().nth(1).unwrap().to_string()).collect()
+    };
+    assert_eq!(vec!["1"], counts("  sum"));
+    assert_eq!(vec!["5"], counts("  term"));
+    assert_eq!(vec!["1"], counts("@sum [1] sum_0"));
+    assert_eq!(vec!["5"], counts("@sum [2] loop: sum_sum+term(n)"));
+    assert_eq!(vec!["5"], counts("@term [1] term_x"));
+    assert_eq!(2, run("profile.report(1)", &workspace).split("\n\n").nth(1).unwrap().lines().count());
+}
+
+#[test]
+fn coverage() {
+    let workspace = WorkSpace::new();
+    evaluate("$sign(n)\nif n<0\nsign_-1\nelse\nsign_1\n$", &workspace).unwrap();
+    evaluate("$unused(x)\nunused_x\n$", &workspace).unwrap();
+    assert!(evaluate("coverage.report()", &workspace).is_err());
+
+    evaluate("coverage()", &workspace).unwrap();
+    assert_eq!("1", run("sign(2)", &workspace));
+    assert_eq!("1", run("sign(3)", &workspace));
+    evaluate("uncoverage()", &workspace).unwrap();
+    run("sign(-1)", &workspace);
+
+    let report = run("coverage.report()", &workspace);
+    let lines: Vec<&str> = report.lines().map(|line| line.trim_start()).collect();
+    assert_eq!(vec![
+        "@sign\t3 of 4 lines, 1 of 2 branches",
+        "2  [1] if n<0\t[true 0, false 2]",
+        "#####  [2] sign_-1",
+        "2  [4] sign_1",
+        "2  [5] $",
+        "",
+        "@unused\t0 of 2 lines, 0 of 0 branches",
+        "#####  [1] unused_x",
+        "#####  [2] $",
+        ""], lines);
+
+    let path = std::env::temp_dir().join(format!("ppl-coverage-{}.info", std::process::id()));
+    evaluate(format!(r#"coverage.lcov("{}")"#, path.display()).as_str(), &workspace).unwrap();
+    let lcov = std::fs::read_to_string(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    let sign = lcov.split("end_of_record\n").next().unwrap();
+    for line in ["SF:sign", "FNDA:2,sign", "BRDA:1,0,0,0", "BRDA:1,0,1,2", "BRF:2", "BRH:1", "DA:2,0", "DA:4,2", "LF:4", "LH:3"] {
+        assert!(sign.lines().any(|l| l == line), "{} not in\n{}", line, sign);
+    }
+    assert!(lcov.contains("SF:unused\nFN:1,unused\nFNDA:0,unused\n"));
+
+    //  A function is reported under the name it was defined with, whatever its case
+
+    evaluate("$Twice(n)\nTwice_2*n\n$", &workspace).unwrap();
+    evaluate("coverage()", &workspace).unwrap();
+    assert_eq!("4", run("twice(2)", &workspace));
+    assert!(run("coverage.report()", &workspace).contains("@Twice\t2 of 2 lines, 0 of 0 branches\n"));
+}
+
+#[test]
+fn ppl_tests() {
+    let workspace = WorkSpace::new();
+    evaluate("$double(x)\ndouble_x*2\n$", &workspace).unwrap();
+
+    assert_eq!("", run("assert(1<2)", &workspace));
+    assert!(matches!(evaluate("assert(2<1)", &workspace), Err(PplError::Assertion(_))));
+    assert_eq!("too big", evaluate(r#"assert(2<1, "too big")"#, &workspace).unwrap_err().message());
+    assert!(evaluate("assert.equal(double(2), 4)", &workspace).is_ok());
+    assert!(evaluate("assert.equal(double(2), 5)", &workspace).unwrap_err().message().starts_with("Expected 5, got 4"));
+    assert_eq!("xyz not found", run(r#"expect.error("double(xyz)")"#, &workspace));
+    assert!(matches!(evaluate(r#"expect.error("double(1)")"#, &workspace), Err(PplError::Assertion(_))));
+
+    evaluate("$test.double\nassert.equal(double(3), 6)\ncount_count+1\n$", &workspace).unwrap();
+    evaluate("$Test.Error\nexpect.error(\"xyz\")\n$", &workspace).unwrap();
+    evaluate("count_0", &workspace).unwrap();
+    assert_eq!("PASS test.double\nPASS Test.Error\n2 tests, 2 passed, 0 failed", run("test.run()", &workspace));
+    assert_eq!("0", run("count", &workspace));
+
+    //  A failing test doesn't stop the rest, or leave anything behind
+
+    evaluate("$test.broken\nleftover_1\nassert.equal(double(3), 7)\n$", &workspace).unwrap();
+    let error = evaluate("test.run()", &workspace).unwrap_err();
+    assert!(matches!(error, PplError::Assertion(_)));
+    assert!(error.message().starts_with(
+        "FAIL test.broken\n    Expected 7, got 6\n    @test.broken [2] assert.equal(double(3), 7)\nPASS test.double\nPASS Test.Error\n3 tests, 2 passed, 1 failed"),
+        "{}", error.message());
+    assert!(evaluate("leftover", &workspace).is_err());
+    assert_eq!("0", run("count", &workspace));
+
+    //  A saved workspace's tests run in a workspace of their own
+
+    let filename = std::env::temp_dir().join(format!("ppl-tests-{}.ppl", std::process::id())).display().to_string();
+    run(&format!(r#"write("{}")"#, filename), &workspace);
+    evaluate("erase(test.broken)", &workspace).unwrap();
+    let error = evaluate(&format!(r#"test.run("{}")"#, filename), &workspace).unwrap_err();
+    std::fs::remove_file(&filename).unwrap();
+    assert!(error.message().contains("3 tests, 2 passed, 1 failed"), "{}", error.message());
+}
+
+#[test]
+fn resource_limits() {
+    let workspace = WorkSpace::new();
+    evaluate("$deep(n)\ndeep_deep(n+1)\n$", &workspace).unwrap();
+    evaluate("$spin\nloop: -->loop\n$", &workspace).unwrap();
+    assert_eq!("0", run("limit.depth()", &workspace));
+
+    evaluate("limit.depth(50)", &workspace).unwrap();
+    assert_eq!("50", run("limit.depth()", &workspace));
+    let error = evaluate("deep(1)", &workspace).unwrap_err();
+    assert!(matches!(error, PplError::Limit(_)), "{:?}", error);
+    assert_eq!("More than 50 functions called", error.message());
+    assert_eq!(Some("deep"), error.function_name());
+    assert_eq!("2", run("1+1", &workspace));
+    assert!(matches!(evaluate(r#"expect.error("deep(1)")"#, &workspace), Err(PplError::Limit(_))));
+    evaluate("limit.depth(0)", &workspace).unwrap();
+
+    evaluate("limit.nodes(1000)", &workspace).unwrap();
+    assert!(matches!(evaluate("spin", &workspace), Err(PplError::Limit(_))));
+    assert_eq!("2", run("1+1", &workspace));
+    evaluate("limit.nodes(0)", &workspace).unwrap();
+
+    evaluate("limit.values(100)", &workspace).unwrap();
+    assert!(evaluate("deep(1)", &workspace).unwrap_err().message().contains("100 values"));
+    assert_eq!(0, workspace.get_value_stack_size());
+    assert_eq!("2", run("1+1", &workspace));
+}
+
+#[test]
+fn stack_limit() {
+    let workspace = WorkSpace::new();
+    assert_eq!("1024", run("limit.stack()", &workspace));
+
+    //  Evaluations nested in evaluations use the process stack, which runs out long before the heap does
+
+    evaluate("$nest(n)\nnest_n\ncheck_expect.error(\"nest(n+1)\")\n$", &workspace).unwrap();
+    evaluate("limit.stack(64)", &workspace).unwrap();
+    let error = evaluate("nest(1)", &workspace).unwrap_err();
+    assert!(matches!(error, PplError::Limit(_)), "{}", error);
+    assert_eq!("More than 64KB of stack used", error.message());
+    assert_eq!("2", run("1+1", &workspace));
+    evaluate("limit.stack(1024)", &workspace).unwrap();
+    assert!(matches!(evaluate("nest(1)", &workspace), Err(PplError::Limit(_))));
+
+    evaluate("$usage(n)\nusage_stack.usage\n$", &workspace).unwrap();
+    assert!(run("usage(1)", &workspace).parse::<i32>().is_ok());
+
+    //  Resuming measures from where the resuming command is, not from where the function stopped
+
+    fn with_stack_used<T>(kilobytes: usize, f: &dyn Fn() -> T) -> T {
+        let padding = [0u8; 1024];
+        let result = if kilobytes == 0 { f() } else { with_stack_used(kilobytes - 1, f) };
+        std::hint::black_box(&padding);
+        result
+    }
+    evaluate("$work(n)\nwork_n+1\n$", &workspace).unwrap();
+    evaluate("stop(work, 1)", &workspace).unwrap();
+    evaluate("limit.stack(64)", &workspace).unwrap();
+    assert!(matches!(evaluate("work(1)", &workspace), Err(PplError::Stop(_))));
+    assert_eq!("2", with_stack_used(128, &|| run("-->1", &workspace)));
+}
+
+#[test]
+fn embedding() {
+    use crate::embed::{Argument, Interpreter, Value};
+    use std::{cell::RefCell, rc::Rc};
+
+    let interpreter = Interpreter::new();
+
+    //  Host functions are closures, so they can keep state of their own
+
+    let log = Rc::new(RefCell::new(Vec::new()));
+    let host_log = log.clone();
+    interpreter.register("host.scale", &[Argument::new("x", "int"), Argument::new("factor", "int")], Some("int"), "Scales x",
+        move |arguments| {
+            host_log.borrow_mut().push(arguments[0].as_i32()?);
+            Ok(Value::Int(arguments[0].as_i32()? * arguments[1].as_i32()?))
+        }).unwrap();
+    assert_eq!("42", interpreter.evaluate("host.scale(21, 2)").unwrap());
+    assert_eq!("30", interpreter.evaluate("host.scale(3.7, 10)").unwrap());
+    assert_eq!(vec![21, 3], *log.borrow());
+    assert!(interpreter.register("bad", &[Argument::new("x", "nosuchtype")], None, "", |_| Ok(Value::Empty)).is_err());
+
+    interpreter.set_variable("limit", &Value::Int(10)).unwrap();
+    assert_eq!("11", interpreter.evaluate("limit+1").unwrap());
+    interpreter.evaluate("limit_limit*2").unwrap();
+    assert!(matches!(interpreter.get_variable("limit"), Some(Value::Int(20))));
+    assert!(interpreter.get_variable("nothing").is_none());
+
+    interpreter.evaluate("$bounded(x)\nbounded_host.scale(x, 2)+limit\n$").unwrap();
+    assert!(matches!(interpreter.call("bounded", &[Value::Int(1)]), Ok(Value::Int(22))));
+    assert!(matches!(interpreter.call("host.scale", &[Value::Int(2), Value::Int(3)]), Ok(Value::Int(6))));
+    assert!(matches!(interpreter.call("nothing", &[]), Err(PplError::Name(_))));
+    assert!(matches!(interpreter.call("bounded", &[]), Err(PplError::Argument(_))));
+    assert_eq!("2", interpreter.evaluate("1+1").unwrap());
+}
+
+#[test]
+fn conversions() {
+    use crate::embed::{Interpreter, Value};
+    use std::collections::HashMap;
+
+    let interpreter = Interpreter::new();
+    interpreter.set_variable("names", &Value::from(vec!["ada", "alan"])).unwrap();
+    assert_eq!("alan", interpreter.evaluate("names[2]").unwrap());
+    assert_eq!(Ok(vec![String::from("ada"), String::from("alan")]), Vec::<String>::try_from(&interpreter.get_variable("names").unwrap()));
+    assert_eq!(Ok(vec![1.5, 3.0]), Vec::<f64>::try_from(&interpreter.call("tuple", &[Value::from(1.5), Value::from(3)]).unwrap()));
+    assert!(matches!(String::try_from(&Value::from(3)), Err(PplError::Type(_))));
+    assert!(matches!(i32::try_from(&Value::from("three")), Err(PplError::Type(_))));
+
+    let ages = HashMap::from([("ada", 36), ("alan", 41)]);
+    interpreter.set_variable("ages", &Value::from(ages)).unwrap();
+    assert_eq!("[[ada, 36], [alan, 41]]", interpreter.evaluate("ages").unwrap());
+    interpreter.evaluate("$person = [name:string, age:int]").unwrap();
+    let person = HashMap::<String,Value>::try_from(&interpreter.call("person", &[Value::from("grace"), Value::from(85)]).unwrap()).unwrap();
+    assert_eq!(Ok(85), i32::try_from(&person["age"]));
+}
+
+#[cfg(feature = "derive")]
+#[test]
+fn derived_structures() {
+    use crate::embed::{Interpreter, Structure, Value};
+
+    #[derive(Structure, Debug, PartialEq)]
+    #[ppl(name = "point")]
+    struct Point {
+        x: i32,
+        y: i32,
+    }
+
+    #[derive(Structure, Debug, PartialEq)]
+    struct Shape {
+        name: String,
+        #[ppl(name = "corners")]
+        corner_points: Vec<Point>,
+    }
+
+    let interpreter = Interpreter::new();
+    interpreter.define::<Point>().unwrap();
+    interpreter.define::<Shape>().unwrap();
+    assert!(matches!(interpreter.define::<Point>(), Err(PplError::Name(_))));
+
+    interpreter.set_variable("origin", &Value::from(Point { x: 0, y: 0 })).unwrap();
+    assert_eq!("0", interpreter.evaluate("origin[y]").unwrap());
+    interpreter.evaluate("triangle_Shape(\"triangle\", tuple(origin, point(4, 0), point(0, 3)))").unwrap();
+    let triangle = Shape::try_from(&interpreter.get_variable("triangle").unwrap()).unwrap();
+    assert_eq!("triangle", triangle.name);
+    assert_eq!(Point { x: 4, y: 0 }, triangle.corner_points[1]);
+    assert!(matches!(Point::try_from(&interpreter.get_variable("triangle").unwrap()), Err(PplError::Type(_))));
+}
+
+#[test]
+fn wide_integers() {
+    let workspace = WorkSpace::new();
+
+    //  Literals too large for an int are int64s
+
+    assert_eq!("3000000001", run("3000000000+1", &workspace));
+    assert_eq!("true", run("3000000000==\"int64\"", &workspace));
+    assert_eq!("true", run("-2147483648==\"int\"", &workspace));
+    assert_eq!("2147483648", run("2147483647+int64(1)", &workspace));
+    assert_eq!("1099511627776", run("int64(2)^40", &workspace));
+    assert_eq!("428571428", run("3000000000/7", &workspace));
+    assert_eq!("3000000001.5", run("3000000000+1.5", &workspace));
+    assert_eq!("true", run("int64(3)=3", &workspace));
+    assert_eq!("true", run("3000000000>2147483647", &workspace));
+    assert_eq!(" 3000000000", run("iformat(\"9z2d\", 3000000000)", &workspace));
+    assert!(matches!(evaluate("int(3000000000)", &workspace), Err(PplError::Arithmetic(_))));
+
+    //  And they save and load like any other value
+
+    let filename = std::env::temp_dir().join(format!("ppl-int64-{}.ppl", std::process::id())).display().to_string();
+    run("big_int64(-9000000000)", &workspace);
+    crate::workspace::io::write(&filename, &workspace).unwrap();
+    let reloaded = WorkSpace::new();
+    assert_eq!(Ok(()), crate::workspace::io::read(&filename, &reloaded));
+    assert_eq!("true", run("big==\"int64\"", &reloaded));
+    assert_eq!("-9000000000", run("big", &reloaded));
+    let _ = std::fs::remove_file(&filename);
+}
+
+#[test]
+fn integer_overflow() {
+    let workspace = WorkSpace::new();
+    let overflows = |s: &str| matches!(evaluate(s, &workspace), Err(PplError::Arithmetic(_)));
+
+    //  By default an integer result too large for its datatype is an error
+
+    assert!(overflows("2147483647+1"));
+    assert!(overflows("add(2147483647, 1)"));
+    assert!(overflows("-2147483647-2"));
+    assert!(overflows("sub(-2147483647, 2)"));
+    assert!(overflows("65536*32768"));
+    assert!(overflows("mul(65536, 32768)"));
+    assert!(overflows("2^31"));
+    assert!(overflows("power(2, 31)"));
+    run("a_-2147483648", &workspace);
+    assert!(overflows("-a"));
+    assert!(overflows("minus(a)"));
+    assert!(overflows("a/-1"));
+    assert!(overflows("div(a, -1)"));
+    assert!(overflows("9223372036854775807+1"));
+    assert!(overflows("int64(2)^63"));
+    assert_eq!("2147483647", run("2147483646+1", &workspace));
+    assert_eq!("-2147483648", run("(2^30)*-2", &workspace));
+    assert_eq!("1073741824", run("2^30", &workspace));
+    assert_eq!("0", run("2^-1", &workspace));
+    assert_eq!("-1", run("-1^-3", &workspace));
+
+    //  Division by zero is an error whatever the datatype
+
+    assert!(overflows("1/0"));
+    assert!(overflows("div(1, 0)"));
+    assert!(overflows("int64(1)/0"));
+    assert!(overflows("1.5/0"));
+    assert!(overflows("dbl(1)/0"));
+    assert!(overflows("0^-1"));
+
+    //  With PromoteOnOverflow set the result is given the next wider datatype
+
+    run("feature(\"set\", \"PromoteOnOverflow\")", &workspace);
+    assert_eq!("2147483648", run("2147483647+1", &workspace));
+    assert_eq!("true", run("(2147483647+1)==\"int64\"", &workspace));
+    assert_eq!("true", run("(2147483646+1)==\"int\"", &workspace));
+    assert_eq!("2147483648", run("add(2147483647, 1)", &workspace));
+    assert_eq!("-2147483649", run("-2147483647-2", &workspace));
+    assert_eq!("-2147483649", run("sub(-2147483647, 2)", &workspace));
+    assert_eq!("2147483648", run("65536*32768", &workspace));
+    assert_eq!("2147483648", run("mul(65536, 32768)", &workspace));
+    assert_eq!("2147483648", run("2^31", &workspace));
+    assert_eq!("2147483648", run("power(2, 31)", &workspace));
+    assert_eq!("2147483648", run("-a", &workspace));
+    assert_eq!("2147483648", run("minus(a)", &workspace));
+    assert_eq!("2147483648", run("a/-1", &workspace));
+    assert_eq!("2147483648", run("div(a, -1)", &workspace));
+    assert!(overflows("1/0"));
+    #[cfg(not(feature = "bignum"))]
+    assert!(overflows("9223372036854775807+1"));
+    #[cfg(feature = "bignum")]
+    assert_eq!("9223372036854775808", run("9223372036854775807+1", &workspace));
+    #[cfg(feature = "bignum")]
+    assert_eq!("9223372036854775808", run("int64(2)^63", &workspace));
+
+    run("feature(\"clear\", \"PromoteOnOverflow\")", &workspace);
+    assert!(overflows("2147483647+1"));
+}
+
+#[test]
+fn exact_numbers() {
+    let workspace = WorkSpace::new();
+
+    //  Rationals are exact fractions, in lowest terms
+
+    assert_eq!("1/3", run("rational(1, 3)", &workspace));
+    assert_eq!("1/2", run("rational(1, 3)+rational(1, 6)", &workspace));
+    assert_eq!("-1/6", run("rational(1, 6)-rational(1, 3)", &workspace));
+    assert_eq!("1", run("rational(\"1/3\")*3", &workspace));
+    assert_eq!("1/3", run("1/rational(3)", &workspace));
+    assert_eq!("-1/3", run("-rational(2, 6)", &workspace));
+    assert_eq!("9/4", run("rational(2, 3)^-2", &workspace));
+    assert_eq!("1/10", run("rational(0.1)", &workspace));
+    assert_eq!("true", run("rational(2, 4)==\"rational\"", &workspace));
+    assert_eq!("true", run("rational(1, 3)<rational(1, 2)", &workspace));
+
+    //  Decimals keep their scale, and round half away from zero when they have to lose digits
+
+    assert_eq!("12.50", run("decimal(\"12.50\")", &workspace));
+    assert_eq!("0.30", run("decimal(\"0.10\")+decimal(\"0.20\")", &workspace));
+    assert_eq!("true", run("(decimal(\"0.1\")+decimal(\"0.2\"))=decimal(\"0.3\")", &workspace));
+    assert_eq!("9.00", run("decimal(\"10.00\")-1", &workspace));
+    assert_eq!("1.2100", run("decimal(\"1.10\")*decimal(\"1.10\")", &workspace));
+    assert_eq!("3.33", run("decimal(\"10.00\")/3", &workspace));
+    assert_eq!("6.67", run("decimal(\"20.00\")/3", &workspace));
+    assert_eq!("-3", run("decimal(\"-2.5\", 0)", &workspace));
+    assert_eq!("2.68", run("decimal(2.675, 2)", &workspace));
+    assert_eq!("2.25", run("decimal(\"1.5\")^2", &workspace));
+    assert_eq!("-1.25", run("-decimal(\"1.25\")", &workspace));
+    assert_eq!("true", run("decimal(\"0.3\")=decimal(\"0.30\")", &workspace));
+    assert_eq!("true", run("decimal(\"1.25\")>1", &workspace));
+
+    //  Mixed arithmetic: a decimal is exactly a rational, but anything with a float in it is a dbl
+
+    assert_eq!("11/6", run("decimal(\"1.5\")+rational(1, 3)", &workspace));
+    assert_eq!("true", run("rational(1, 2)=decimal(\"0.5\")", &workspace));
+    assert_eq!("true", run("(decimal(1)+0.5)==\"dbl\"", &workspace));
+    assert_eq!("2.", run("rational(4)^rational(1, 2)", &workspace));
+
+    //  Conversions to the other numeric types
+
+    assert_eq!("3", run("int(rational(7, 2))", &workspace));
+    assert_eq!("-3", run("int64(decimal(\"-3.99\"))", &workspace));
+    assert_eq!("0.25", run("dbl(rational(1, 4))", &workspace));
+    assert_eq!("2.5", run("real(decimal(\"2.5\"))", &workspace));
+    assert_eq!("0.25", run("decimal(rational(1, 4))", &workspace));
+    assert_eq!("1/4", run("rational(decimal(\"0.25\"))", &workspace));
+
+    //  Formatting is exact, rounded to the picture
+
+    assert_eq!("   0.67", run("iformat(\"3z1d.2d\", rational(2, 3))", &workspace));
+    assert_eq!("-123.46", run("iformat(\"3z1d.2d\", decimal(\"-123.455\"))", &workspace));
+    assert_eq!("   .5 ", run("iformat(\"3z.2z\", decimal(\"0.50\"))", &workspace));
+    assert_eq!("   2", run("iformat(\"3z1d\", decimal(\"1.5\"))", &workspace));
+    assert_eq!("12.35e 01", run("iformat(\"e1z1d.2d\", decimal(\"123.5\"))", &workspace));
+    assert_eq!("1/3", run("iformat(\"ff\", rational(1, 3))", &workspace));
+
+    //  Errors
+
+    let fails = |s: &str| matches!(evaluate(s, &workspace), Err(PplError::Arithmetic(_)));
+    assert!(fails("rational(1, 0)"));
+    assert!(fails("rational(1, 3)/0"));
+    assert!(fails("decimal(1)/0"));
+    assert!(fails("rational(9223372036854775807)+1"));
+    assert!(matches!(evaluate("decimal(\"abc\")", &workspace), Err(PplError::Type(_))));
+
+    //  And they save and load exactly
+
+    let filename = std::env::temp_dir().join(format!("ppl-exact-{}.ppl", std::process::id())).display().to_string();
+    run("third_rational(-1, 3)", &workspace);
+    run("price_decimal(\"19.90\")", &workspace);
+    crate::workspace::io::write(&filename, &workspace).unwrap();
+    let reloaded = WorkSpace::new();
+    assert_eq!(Ok(()), crate::workspace::io::read(&filename, &reloaded));
+    assert_eq!("-1/3", run("third", &reloaded));
+    assert_eq!("19.90", run("price", &reloaded));
+    assert_eq!("true", run("price==\"decimal\"", &reloaded));
+    let _ = std::fs::remove_file(&filename);
+}
+
+#[test]
+fn complex_numbers() {
+    let workspace = WorkSpace::new();
+
+    //  An i after a number makes it imaginary, and the parts of a complex value are reals
+
+    assert_eq!("[rp:3., ip:4.]", run("z_3+4i", &workspace));
+    assert_eq!("[rp:0., ip:-2.5]", run("-2.5i", &workspace));
+    assert_eq!("[rp:0., ip:25.]", run("2.5e1i", &workspace));
+    assert!(matches!(evaluate("1.234567890123d0i", &workspace), Err(PplError::Syntax(_))));
+    assert!(matches!(evaluate("1.234567890123i", &workspace), Err(PplError::Syntax(_))));
+
+    //  A program can still define complex itself, as long as it's defined the same way
+
+    assert!(evaluate("$complex=[rp:real, ip:real]", &workspace).is_ok());
+    assert!(matches!(evaluate("$complex=[re:real, im:real]", &workspace), Err(PplError::Name(_))));
+    assert_eq!("true", run("z==complex", &workspace));
+    assert_eq!("3.", run("z[rp]", &workspace));
+    assert_eq!("4.", run("ip(z)", &workspace));
+    assert_eq!("10", run("i_5\n2*i", &workspace));
+
+    //  Arithmetic, with numbers taken as complex values
+
+    assert_eq!("[rp:-7., ip:24.]", run("z*z", &workspace));
+    assert_eq!("[rp:-1., ip:2.]", run("z/(1-2i)", &workspace));
+    assert_eq!("[rp:-2., ip:-4.]", run("1-z", &workspace));
+    assert_eq!("[rp:-1., ip:0.]", run("1i^2", &workspace));
+    assert_eq!("[rp:2., ip:1.]", run("z^0.5", &workspace));
+    assert_eq!("true", run("z=3+4i", &workspace));
+    assert_eq!("true", run("3=complex(3, 0)", &workspace));
+
+    //  The functions
+
+    assert_eq!("5.", run("abs(z)", &workspace));
+    assert_eq!("1.5708E0", run("arg(1i)", &workspace));
+    assert_eq!("[rp:3., ip:-4.]", run("conj(z)", &workspace));
+    assert_eq!("2.718282", run("abs(exp(1+3i))", &workspace));
+    assert_eq!("[rp:0., ip:2.]", run("sqrt(-4+0i)", &workspace));
+    assert_eq!("[rp:1., ip:-2.]", run("sqrt(-3-4i)", &workspace));
+
+    //  Operators can be bound to functions of complex values
+
+    run(r#"unary("~", conj)"#, &workspace);
+    assert_eq!("[rp:3., ip:-4.]", run("~z", &workspace));
+    run("$inner(a, b)\ninner_a*conj(b)\n$", &workspace);
+    run(r#"binary("@@", inner)"#, &workspace);
+    assert_eq!("[rp:4., ip:-3.]", run("z@@1i", &workspace));
+
+    //  A literal makes a new value each time, so changing a part of one doesn't change the function it came from
+
+    run("$f()\nf_2i\n$", &workspace);
+    run("w_f()", &workspace);
+    run("w[ip]_5", &workspace);
+    assert_eq!("[rp:0., ip:2.]", run("f()", &workspace));
+
+    let fails = |s: &str| matches!(evaluate(s, &workspace), Err(PplError::Arithmetic(_)));
+    assert!(fails("z/0"));
+    assert!(fails("0i^-1"));
+}
+
+#[cfg(feature = "bignum")]
+#[test]
+fn bignums() {
+    let workspace = WorkSpace::new();
+    assert_eq!("1267650600228229401496703205376", run("bignum(2)^100", &workspace));
+    assert_eq!("99999999999999999999", run("99999999999999999999", &workspace));
+    assert_eq!("-9223372036854775808", run("-9223372036854775808", &workspace));
+    assert_eq!("true", run("-9223372036854775808==\"int64\"", &workspace));
+    assert_eq!("100000000000000000001", run("99999999999999999999+2", &workspace));
+    assert_eq!("true", run("99999999999999999999>3000000000", &workspace));
+    assert!(matches!(evaluate("bignum(10)/0", &workspace), Err(PplError::Arithmetic(_))));
+    assert!(matches!(evaluate("int64(99999999999999999999)", &workspace), Err(PplError::Arithmetic(_))));
+}
@@ -0,0 +1,98 @@
+use crate::{error::PplError, execution::evaluate, tests::tests::run, workspace::WorkSpace};
+
+
+#[test]
+fn math() {
+    let workspace = WorkSpace::new();
+
+    //  The homonym for the datatype of the argument is the one called
+
+    assert_eq!("4.", run("sqrt(16)", &workspace));
+    assert_eq!("true", run("sqrt(16)==real", &workspace));
+    assert_eq!("1.414213562373D0", run("sqrt(dbl(2))", &workspace));
+    assert_eq!("true", run("sqrt(dbl(2))==dbl", &workspace));
+    assert_eq!("3", run("abs(-3)", &workspace));
+    assert_eq!("true", run("abs(-3)==int", &workspace));
+    assert_eq!("2.5", run("abs(-2.5)", &workspace));
+
+    //  Powers, logarithms and trigonometry
+
+    assert_eq!("2.7183E0", run("exp(1)", &workspace));
+    assert_eq!("3.", run("log10(1000)", &workspace));
+    assert_eq!("0.", run("ln(1)", &workspace));
+    assert_eq!("0.", run("sin(0)", &workspace));
+    assert_eq!("1.", run("cos(0)", &workspace));
+    assert_eq!("0.", run("tan(0)", &workspace));
+    assert_eq!("1.5708E0", run("asin(1)", &workspace));
+    assert_eq!("0.7853982", run("atan(1)", &workspace));
+    assert_eq!("3.141592653590D0", run("pi", &workspace));
+    assert_eq!("true", run("pi==dbl", &workspace));
+
+    //  Rounding keeps the datatype of the argument
+
+    assert_eq!("-3.", run("floor(-2.5)", &workspace));
+    assert_eq!("-2.", run("ceil(-2.5)", &workspace));
+    assert_eq!("3.", run("round(2.5)", &workspace));
+    assert_eq!("-2.", run("trunc(-2.7)", &workspace));
+    assert_eq!("true", run("floor(7)==int", &workspace));
+    assert_eq!("-1", run("sign(-4.5)", &workspace));
+    assert_eq!("0", run("sign(0)", &workspace));
+
+    //  mod takes the sign of the divisor and rem the sign of the dividend
+
+    assert_eq!("2", run("mod(-7, 3)", &workspace));
+    assert_eq!("-1", run("rem(-7, 3)", &workspace));
+    assert_eq!("-2", run("mod(7, -3)", &workspace));
+    assert_eq!("1", run("rem(7, -3)", &workspace));
+    assert_eq!("1.5", run("mod(7.5, 2)", &workspace));
+    assert_eq!("0", run("rem(-2147483648, -1)", &workspace));
+    assert_eq!("0", run("mod(-2147483648, -1)", &workspace));
+
+    assert_eq!("3", run("min(3, 4)", &workspace));
+    assert_eq!("4.5", run("max(3, 4.5)", &workspace));
+    assert_eq!("6", run("gcd(-12, 18)", &workspace));
+    assert_eq!("true", run("gcd(int64(12), 18)==int64", &workspace));
+
+    //  A value outside the domain is an error rather than a NaN; only a complex argument gives a complex root
+
+    assert!(matches!(evaluate("sqrt(-1)", &workspace), Err(PplError::Arithmetic(_))));
+    assert!(matches!(evaluate("ln(0)", &workspace), Err(PplError::Arithmetic(_))));
+    assert!(matches!(evaluate("acos(2)", &workspace), Err(PplError::Arithmetic(_))));
+    assert!(matches!(evaluate("mod(5, 0)", &workspace), Err(e) if e.message() == "Division by zero in mod"));
+    assert_eq!("[rp:0., ip:1.]", run("sqrt(-1+0i)", &workspace));
+    assert_eq!("true", run("sqrt(-1+0i)==complex", &workspace));
+    assert_eq!("true", run("exp(1)==real", &workspace));
+    assert_eq!("5.", run("abs(3+4i)", &workspace));
+}
+
+#[test]
+fn homonyms() {
+    let workspace = WorkSpace::new();
+
+    //  A program's own variable or function of the same name hides the library's
+
+    assert!(evaluate("pi_3.1415926", &workspace).is_ok());
+    assert_eq!("3.1416E0", run("pi", &workspace));
+    assert!(evaluate("$max(a,b)\n(a>b)-->its.a\nmax_b\n-->0\nits.a: max_a\n$", &workspace).is_ok());
+    assert_eq!("6", run("max(1,6)", &workspace));
+    assert!(evaluate("$sign(n)\nsign_n\n$", &workspace).is_ok());
+    assert_eq!("-4", run("sign(-4)", &workspace));
+    assert_eq!("2", run("abs(-2)", &workspace));
+    assert!(matches!(evaluate("$print(x)\nprint_x\n$", &workspace), Err(PplError::Name(_))));
+
+    //  Other homonyms are still told apart by their number of arguments, and the first that takes that many is the
+    //  one called, whatever the datatypes of the values passed
+
+    assert_eq!("-3", run("-3", &workspace));
+    assert_eq!("2", run("5-3", &workspace));
+    evaluate("$join(a, b)\njoin_\"joined\"\n$", &workspace).unwrap();
+    evaluate(r#"binary("+", join)"#, &workspace).unwrap();
+    assert_eq!("joined", run("1+2", &workspace));
+    assert_eq!("-2", run("+-2", &workspace));
+
+    //  A user function bound to an overloaded name is called as it was
+
+    evaluate("$first(a, b)\nfirst_a\n$", &workspace).unwrap();
+    evaluate(r#"binary("min", first)"#, &workspace).unwrap();
+    assert_eq!("6", run("min(6, 1)", &workspace));
+}
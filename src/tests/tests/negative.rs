@@ -1,4 +1,4 @@
-use crate::{tests::tests::run, workspace::WorkSpace};
+use crate::{error::PplError, execution::evaluate, tests::tests::run, workspace::WorkSpace};
 
 
 #[test]
@@ -14,4 +14,52 @@ fn negative() {
     assert_eq!("", run("a[1]_$b", &workspace));
     assert_eq!("", run("a[1]_$b[2]", &workspace));
 
-}
\ No newline at end of file
+}
+
+#[test]
+fn error_kinds() {
+    let workspace = WorkSpace::new();
+
+    //  Syntax errors point at the offending column and render as they always have
+
+    let error = evaluate("a_(1", &workspace).unwrap_err();
+    assert!(matches!(error, PplError::Syntax(_)), "{:?}", error);
+    assert_eq!(Some(1), error.line_number());
+    assert_eq!(Some(4), error.span().map(|span| span.column));
+    assert!(error.to_string().starts_with("\na_(1\n   ^\n"), "{}", error);
+
+    assert!(matches!(evaluate("nosuchname", &workspace), Err(PplError::Name(_))));
+    assert!(matches!(evaluate("int('a)", &workspace), Err(PplError::Type(_))));
+    assert!(matches!(evaluate("1[2]", &workspace), Err(PplError::Index(_))));
+    assert!(matches!(evaluate("add(1)", &workspace), Err(PplError::Argument(_))));
+
+    //  Runtime errors know the function and line that were executing
+
+    evaluate("$f(x)\ny_x\nf_y+nosuchname\n$", &workspace).unwrap();
+    let error = evaluate("f(1)", &workspace).unwrap_err();
+    assert!(matches!(error, PplError::Name(_)), "{:?}", error);
+    assert_eq!((Some("f"), Some(2)), (error.function_name(), error.line_number()));
+    assert_eq!("nosuchname not found", error.message());
+}
+
+#[test]
+fn tracebacks() {
+    let workspace = WorkSpace::new();
+    evaluate("$outer(x)\ny_x+1\nouter_inner(y)\n$", &workspace).unwrap();
+    evaluate("$inner(x)\ninner_x+nosuchname\n$", &workspace).unwrap();
+
+    //  Each frame, innermost first, down to the immediate mode command
+
+    let error = evaluate("outer(1)", &workspace).unwrap_err();
+    let frames: Vec<String> = error.traceback().iter().map(|frame| frame.to_string()).collect();
+    assert_eq!(vec!["@inner [1] inner_x+nosuchname", "@outer [2] outer_inner(y)", "@[1] outer(1)"], frames);
+    assert_eq!("nosuchname not found\n@inner [1] inner_x+nosuchname\n@outer [2] outer_inner(y)\n@[1] outer(1)", error.to_string());
+
+    //  where() shows the same frames while a function is stopped
+
+    assert_eq!("", run("where()", &workspace));
+    evaluate("stop(inner, 1)", &workspace).unwrap();
+    assert!(matches!(evaluate("outer(1)", &workspace), Err(PplError::Stop(_))));
+    let trace = run("where()", &workspace);
+    assert!(trace.starts_with("@inner [1] inner_x+nosuchname\n@outer [2] outer_inner(y)\n@[1] outer(1)\n"), "{}", trace);
+}
@@ -3,12 +3,21 @@
 use core::fmt;
 use num_traits::Num;
 
+use crate::execution::value::{Value, recursion_detector};
+
 
 
 pub fn convert_escape_sequences(input_string: &str) -> String {
     input_string.trim_end().replace('\r', "\\r").replace('\n', "\\n").replace('"', "\"\"")
 }
 
+//  A value as it displays, on one line
+
+pub fn as_text(value: &Value) -> String {
+    recursion_detector::Cycle::start();
+    convert_escape_sequences(format!("{}", value).as_str())
+}
+
 
 //  The Set struct is used to efficiently hold ranges of numbers
 
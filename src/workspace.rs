@@ -5,8 +5,11 @@
 //  function, we'll pop the immediate mode command off and apply the goto to the most recent (stopped) function. Thus
 //  we need a way for execution to not end the immediate mode invocation.
 
-use std::arch::asm;
+//  While a function is stopped, immediate mode commands see the variables of the selected frame: 0 is the stopped
+//  function itself, 1 its caller, and so on.  Stopping again, or clearing the stack, selects frame 0.
+
 use std::cell::{Ref, RefCell, RefMut};
+use std::collections::HashSet;
 use std::io::Cursor;
 use std::ops::Deref;
 use std::rc::Rc;
@@ -15,7 +18,7 @@ use std::sync::Arc;
 use std::sync::atomic::AtomicBool;
 
 use crate::execution::functions::FunctionInvocationBlock;
-use crate::execution::runtime::{invocation::Invocation, executable::Executable};
+use crate::execution::runtime::{invocation::{ExecutionState, Invocation}, executable::Executable};
 use crate::execution::sentinal::ExecutionSentinal;
 use crate::execution::system_functions;
 use crate::execution::value::Value;
@@ -26,13 +29,18 @@ use crate::symbols::help::Help;
 use crate::symbols::metadata::{VariableDescription, self};
 use crate::symbols::name::Name;
 use crate::symbols::{metadata::{MetaDataType, FunctionDescription, SelectorDescription}, SymbolTable};
+use crate::utility::convert_escape_sequences;
 
-use self::debug::DebugOption;
+use self::debug::{DebugOption, Step, Watchpoint};
+use self::limits::Limits;
+use crate::execution::runtime::{coverage::Coverage, debug::TraceLog, profile::Profile};
 use self::optional_features::Feature;
 use self::options::Options;
+use crate::error::{Frame, PplError};
 
 pub mod debug;
 pub mod io;
+pub mod limits;
 pub mod optional_features;
 pub mod options;
 
@@ -41,18 +49,29 @@ pub mod options;
  pub struct WorkSpace {
     datatype_symbol_table: RefCell<SymbolTable<MetaDataType>>,
     system_function_symbol_table: RefCell<SymbolTable<RefCell<Vec<Rc<FunctionDescription>>>>>,
+    library_functions: RefCell<HashSet<String>>,     //  System functions a program's own can hide, told apart by datatype
     user_function_symbol_table: RefCell<SymbolTable<FunctionDescription>>,
     selector_symbol_table: RefCell<SymbolTable<SelectorDescription>>,
     variable_symbol_table: RefCell<SymbolTable<VariableDescription>>,
     invocation_stack: RefCell<Vec<Rc<Invocation>>>,
+    selected_frame: RefCell<usize>,
     value_stack: RefCell<Vec<Value>>,
     last_statement_value: RefCell<Option<Value>>,
     stack_start: RefCell<usize>,
     format_parser: crate::execution::value::format::format_parser::Parser,
     floating_point_parser: crate::execution::value::format::floating_point::Parser,
     alternate_print_destination: RefCell<Option<Cursor<Vec<u8>>>>,
+    arguments: RefCell<Vec<String>>,
+    pub copy_policy: RefCell<io::CollisionPolicy>,
+    pub editor: RefCell<Option<String>>,
     pub debug_options: RefCell<Options<DebugOption>>,
+    pub step: RefCell<Option<Step>>,
+    pub watchpoints: RefCell<Vec<Watchpoint>>,
+    pub trace_log: RefCell<Option<TraceLog>>,
+    pub profile: RefCell<Option<Profile>>,
+    pub coverage: RefCell<Option<Coverage>>,
     pub features: RefCell<Options<Feature>>,
+    pub limits: RefCell<Limits>,
     pub execution_sentinal: RefCell<ExecutionSentinal>,
 }
 
@@ -65,7 +84,7 @@ impl Help for Rc<FunctionDescription> {
         self.deref().pretty_print()
     }
 
-    fn show_help(&self, name: &str, workspace: &WorkSpace) -> Result<Value,String> {
+    fn show_help(&self, name: &str, workspace: &WorkSpace) -> Result<Value,PplError> {
         self.deref().show_help(name, workspace)
     }
 }
@@ -91,7 +110,7 @@ impl Help for RefCell<Vec<Rc<FunctionDescription>>> {
         help_text
     }
 
-    fn show_help(&self, name: &str, workspace: &WorkSpace) -> Result<Value,String> {
+    fn show_help(&self, name: &str, workspace: &WorkSpace) -> Result<Value,PplError> {
         let mut help_text = String::new();
         for func in self.borrow().iter() {
             help_text += format!("{} - {}\n", name, func.help_text(workspace).or(Some(String::from(""))).unwrap()).as_str();
@@ -148,18 +167,29 @@ impl WorkSpace {
         let workspace = WorkSpace {
             datatype_symbol_table: RefCell::new(SymbolTable::new()),
             system_function_symbol_table: RefCell::new(SymbolTable::new()),
+            library_functions: RefCell::new(HashSet::new()),
             user_function_symbol_table: RefCell::new(SymbolTable::new()),
             selector_symbol_table: RefCell::new(SymbolTable::new()),
             variable_symbol_table: RefCell::new(SymbolTable::new()),
             invocation_stack: RefCell::new(Vec::new()),
+            selected_frame: RefCell::new(0),
             value_stack: RefCell::new(Vec::new()),
             last_statement_value: RefCell::new(None),
             stack_start: RefCell::new(0),
             format_parser: crate::execution::value::format::format_parser::Parser::new(),
             floating_point_parser: crate::execution::value::format::floating_point::Parser::new(),
             alternate_print_destination: RefCell::new(None),
+            arguments: RefCell::new(Vec::new()),
+            copy_policy: RefCell::new(io::CollisionPolicy::Fail),
+            editor: RefCell::new(None),
             debug_options: RefCell::new(DebugOption::new()),
+            step: RefCell::new(None),
+            watchpoints: RefCell::new(Vec::new()),
+            trace_log: RefCell::new(None),
+            profile: RefCell::new(None),
+            coverage: RefCell::new(None),
             features: RefCell::new(Feature::new()),
+            limits: RefCell::new(Limits::new()),
             execution_sentinal: RefCell::new(ExecutionSentinal::new()) };
         workspace.init();
         workspace
@@ -187,6 +217,14 @@ impl WorkSpace {
         }
     }
 
+    //  Adds a homonym to the standard library.  It's chosen by the datatypes of the arguments passed, not just by how
+    //  many there are, and a program can use the name for a function or variable of its own.
+
+    pub fn add_library_function(&self, name: &str, f: FunctionDescription) {
+        self.library_functions.borrow_mut().insert(name.to_ascii_lowercase());
+        self.add_system_function(name, f);
+    }
+
     pub fn add_system_function_by_reference (&self, name: &str, f: Rc<FunctionDescription>) {
         let opt_homonym_list = self.system_function_symbol_table.borrow_mut().try_get(name);
         match opt_homonym_list {
@@ -228,12 +266,13 @@ impl WorkSpace {
     }
 
     pub fn current_fib(&self) -> Option<Rc<FunctionInvocationBlock>> {
-        for invocation in self.invocation_stack.borrow().iter().rev() {
-            if invocation.get_fib().is_some() {
-                return invocation.get_fib();
-            }
+        let invocation_stack = self.invocation_stack.borrow();
+        let mut frames = invocation_stack.iter().rev().filter(|invocation| invocation.get_fib().is_some());
+        let innermost = frames.next()?;
+        if innermost.get_execution_state() != ExecutionState::Stopped {
+            return innermost.get_fib();
         }
-        None
+        std::iter::once(innermost).chain(frames).take(*self.selected_frame.borrow() + 1).last().and_then(|invocation| invocation.get_fib())
     }
 
     pub fn current_invocation(&self) -> Option<Rc<Invocation>> {
@@ -286,6 +325,33 @@ impl WorkSpace {
         self.execution_sentinal.borrow().as_atomicbool().clone()
     }
 
+    pub fn get_arguments(&self) -> Vec<String> {
+        self.arguments.borrow().clone()
+    }
+
+    //  The number of user functions executing, including any that are stopped
+
+    pub fn get_call_depth(&self) -> usize {
+        self.invocation_stack.borrow().iter().filter(|invocation| invocation.get_fib().is_some()).count()
+    }
+
+    pub fn get_completions(&self, prefix: &str) -> Vec<String> {
+        let prefix = prefix.to_ascii_lowercase();
+        let mut names = Vec::new();
+        if let Some(fib) = &self.current_fib() {
+            names.append(&mut fib.variable_symbol_table.borrow().get_all_names());
+        }
+        names.append(&mut self.variable_symbol_table.borrow().get_all_names());
+        names.append(&mut self.datatype_symbol_table.borrow().get_all_names());
+        names.append(&mut self.selector_symbol_table.borrow().get_all_names());
+        names.append(&mut self.user_function_symbol_table.borrow().get_all_names());
+        names.append(&mut self.system_function_symbol_table.borrow().get_all_names());
+        names.retain(|name| name.starts_with(prefix.as_str()) && Name::is_name_a_legal_identifier(name));
+        names.sort();
+        names.dedup();
+        names
+    }
+
     pub fn get_floating_point_parser(&self) -> &crate::execution::value::format::floating_point::Parser {
         &self.floating_point_parser
     }
@@ -302,18 +368,51 @@ impl WorkSpace {
     }
 
     pub fn get_stack_size(&self) -> usize {
-        *self.stack_start.borrow() - stack_ptr!()
+        self.stack_start.borrow().saturating_sub(stack_ptr!())
+    }
+
+    //  The workspace's own variables, not any function's
+
+    pub fn get_global_variables(&self) -> Vec<(String, Rc<VariableDescription>)> {
+        self.variable_symbol_table.borrow().get_all()
+    }
+
+    pub fn get_invocation_depth(&self) -> usize {
+        self.invocation_stack.borrow().len()
+    }
+
+    pub fn get_selected_frame(&self) -> usize {
+        *self.selected_frame.borrow()
+    }
+
+    //  The invocation depth that leaves the most recently stopped function on top, if one is stopped
+
+    pub fn get_stopped_invocation_depth(&self) -> Option<usize> {
+        self.invocation_stack.borrow().iter()
+            .rposition(|invocation| invocation.get_execution_state() == ExecutionState::Stopped)
+            .map(|index| index + 1)
+    }
+
+    //  User functions by their own names, leaving out the names of operators bound to them
+
+    pub fn get_user_functions(&self) -> Vec<Rc<FunctionDescription>> {
+        let mut functions: Vec<Rc<FunctionDescription>> = self.user_function_symbol_table.borrow().get_all().into_iter()
+            .filter(|(name, f)| f.name.as_str().eq_ignore_ascii_case(name))
+            .map(|(_, f)| f)
+            .collect();
+        functions.sort_by_key(|f| f.name.as_str().to_ascii_lowercase());
+        functions
     }
 
     pub fn get_value_stack_size(&self) -> usize {
         self.value_stack.borrow().len()
     }
 
-    pub fn help_all(&self) -> Result<Value,String> {
+    pub fn help_all(&self) -> Result<Value,PplError> {
         self.system_function_symbol_table.borrow().help_all(self)
     }
     
-    pub fn help_one(&self, name: &str) -> Result<Value,String> {
+    pub fn help_one(&self, name: &str) -> Result<Value,PplError> {
         let mut result = String::new();
         let symbols = self.try_get_functions(name);
         for symbol in symbols {
@@ -330,6 +429,12 @@ impl WorkSpace {
         metadata::init(&self);
     }
 
+    pub fn is_function_stopped(&self) -> bool {
+        self.invocation_stack.borrow().iter().rev()
+            .find(|invocation| invocation.get_fib().is_some())
+            .is_some_and(|invocation| invocation.get_execution_state() == ExecutionState::Stopped)
+    }
+
     pub fn pop_value(&self) -> Value {
         self.value_stack.borrow_mut().pop().unwrap()
     }
@@ -340,6 +445,13 @@ impl WorkSpace {
 
     pub fn remove(&self, name: &str) {
         self.reset_function_state();
+        self.remove_symbol(name);
+    }
+
+    fn remove_symbol(&self, name: &str) {
+
+        //  Leaves any stopped functions alone, for callers that are themselves running
+
         match self.try_get_any(name) {
             GeneralSymbol::Datatype(_) => self.datatype_symbol_table.borrow_mut().remove(name),
             GeneralSymbol::Function(_) => self.user_function_symbol_table.borrow_mut().remove(name),
@@ -349,6 +461,12 @@ impl WorkSpace {
         }
     }
 
+    //  Unlike remove(), this leaves running functions running
+
+    pub fn remove_global_variable(&self, name: &str) {
+        self.variable_symbol_table.borrow_mut().remove(name);
+    }
+
     pub fn remove_all(&self) {
         self.reset_function_state();
         self.datatype_symbol_table.borrow_mut().clear();
@@ -361,21 +479,31 @@ impl WorkSpace {
 
     pub fn reset(&self) {
         self.invocation_stack.borrow_mut().clear();
+        *self.selected_frame.borrow_mut() = 0;
         self.value_stack.borrow_mut().clear();
         *self.last_statement_value.borrow_mut() = None;
     }
 
     pub fn reset_function_state(&self) {
         self.invocation_stack.borrow_mut().clear();
+        *self.selected_frame.borrow_mut() = 0;
     }
 
-    pub fn resolve_datatype(&self, datatype_name: &String) -> Result<RootDataType,String> {
+    pub fn resolve_datatype(&self, datatype_name: &String) -> Result<RootDataType,PplError> {
         let opt_symbol = self.try_get_datatype(datatype_name.as_str());
         if opt_symbol.is_some() {
             return Ok(opt_symbol.unwrap().root_data_type().clone());
         }
 
-        Err(format!("{} not found", datatype_name))
+        Err(ppl_error!(Name, "{} not found", datatype_name))
+    }
+
+    pub fn select_frame(&self, frame: usize) {
+        *self.selected_frame.borrow_mut() = frame;
+    }
+
+    pub fn set_arguments(&self, arguments: Vec<String>) {
+        *self.arguments.borrow_mut() = arguments;
     }
 
     pub fn set_last_statement_value(&self, value: &Value) {
@@ -383,7 +511,14 @@ impl WorkSpace {
     }
 
     pub fn start_immediate_mode(&self, executable: &Rc<Executable>) {
-        *self.stack_start.borrow_mut() = stack_ptr!();
+
+        //  Stack usage is measured from the outermost evaluation, so that it includes any evaluations nested in it.
+        //  A command given while a function is stopped is outermost too, and resuming the function runs it on
+        //  this command's stack, not the one it stopped on.
+
+        if !self.current_invocation().is_some_and(|invocation| invocation.get_execution_state() == ExecutionState::Executing) {
+            *self.stack_start.borrow_mut() = stack_ptr!();
+        }
         let invocation = Invocation::new(executable.clone(), self.get_value_stack_size());
         self.invocation_stack.borrow_mut().push(invocation.clone());
     }
@@ -393,6 +528,21 @@ impl WorkSpace {
         self.invocation_stack.borrow_mut().push(invocation.clone());
     }
     
+    //  Describes each active invocation, innermost first, for tracebacks and where()
+
+    pub fn traceback(&self) -> Vec<Frame> {
+        self.invocation_stack.borrow().iter().rev()
+            .filter_map(|invocation| {
+                let statement = invocation.get_current_statement()?;
+                Some(Frame {
+                    function_name: invocation.get_fib().map(|fib| fib.function_description.name.as_string()),
+                    line_number: statement.as_line_number(),
+                    source: convert_escape_sequences(statement.as_source().as_str()),
+                })
+            })
+            .collect()
+    }
+
     pub fn try_get_any(&self, name: &str) -> GeneralSymbol {
         if let Some(fib) = &self.current_fib() {
             if let Some(v) = fib.variable_symbol_table.borrow().try_get(name) {
@@ -444,6 +594,10 @@ impl WorkSpace {
         }
     }
 
+    pub fn is_library_function(&self, name: &str) -> bool {
+        self.library_functions.borrow().contains(&name.to_ascii_lowercase())
+    }
+
     pub fn try_get_functions(&self, name: &str) -> Vec<GeneralSymbol> {
         let mut result = Vec::new();
         if let Some(f) = self.user_function_symbol_table.borrow().try_get(name) {
@@ -484,6 +638,13 @@ impl WorkSpace {
         self.variable_symbol_table.borrow().try_get(name)
     }
 
+    //  The top count values on the stack, deepest first, as a function call's arguments are before it pops them
+
+    pub fn peek_values(&self, count: usize) -> Vec<Value> {
+        let value_stack = self.value_stack.borrow();
+        value_stack[value_stack.len().saturating_sub(count)..].to_vec()
+    }
+
     pub fn try_peek_value(&self) -> Option<Value> {
         match self.value_stack.borrow().last() {
             Some(value) => Some(value.clone()),
@@ -509,4 +670,11 @@ impl WorkSpace {
         }
     }
 
+    pub fn unwind_invocations(&self, depth: usize) {
+        while self.get_invocation_depth() > depth {
+            if let Some(invocation) = self.invocation_stack.borrow_mut().pop() {
+                self.value_stack.borrow_mut().truncate(invocation.get_base_stack_size());
+            }
+        }
+    }
 }
@@ -1,8 +1,12 @@
 //  This module holds debugging aids
 
+use std::{collections::HashSet, rc::Rc};
+
 use strum_macros::{EnumString, EnumIter, Display};
 use strum::IntoEnumIterator;
 
+use crate::{execution::value::{Value, ValueEnvelope}, symbols::metadata::VariableDescription};
+
 use super::options::Options;
 
 
@@ -27,3 +31,86 @@ impl DebugOption {
         options
     }
 }
+
+//  A step resumes a stopped function and stops it again at a statement boundary: the next one anywhere (into),
+//  the next one that isn't in a function the stopped statement calls (over), or the next one in the stopped
+//  function's caller (out).  The depths are those of the invocation stack.
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Step {
+    Into,
+    Over(usize),
+    Out(usize),
+}
+
+impl Step {
+    pub fn is_complete(&self, depth: usize) -> bool {
+        match self {
+            Step::Into => true,
+            Step::Over(stopped_depth) => depth <= *stopped_depth,
+            Step::Out(stopped_depth) => depth < *stopped_depth,
+        }
+    }
+}
+
+//  A watchpoint holds onto the variable it was set on, so it follows that cell (a function's local, say) rather
+//  than whatever the name means where execution happens to be.  The cell, and the cells of its members, count
+//  the writes made to them, so checking before each statement costs nothing unless something was written.
+
+pub struct Watchpoint {
+    pub name: String,
+    pub variable: Rc<VariableDescription>,
+    value: String,
+    writes: Rc<std::cell::Cell<u64>>,
+    writes_seen: u64,
+}
+
+impl Watchpoint {
+    pub fn new(name: &str, variable: &Rc<VariableDescription>) -> Watchpoint {
+        let writes = Rc::new(std::cell::Cell::new(0));
+        watch_cells(&variable.cell.borrow().as_contents(), &writes, &mut HashSet::new());
+        Watchpoint { name: name.to_ascii_lowercase(), variable: variable.clone(), value: variable.to_string(), writes, writes_seen: 0 }
+    }
+
+    //  Returns a description of the change if the variable has been written since the last check
+
+    pub fn check(&mut self) -> Option<String> {
+        if self.writes.get() == self.writes_seen {
+            return None;
+        }
+        self.writes_seen = self.writes.get();
+
+        //  A write may have brought in new members, which need watching too
+
+        watch_cells(&self.variable.cell.borrow().as_contents(), &self.writes, &mut HashSet::new());
+        let value = self.variable.to_string();
+        let change = if value == self.value {
+            format!("{} was set to {} again", self.name, value)
+        } else {
+            format!("{} changed from {} to {}", self.name, self.value, value)
+        };
+        self.value = value;
+        Some(change)
+    }
+}
+
+fn watch_cells(envelope: &Rc<ValueEnvelope>, writes: &Rc<std::cell::Cell<u64>>, visited: &mut HashSet<*const ValueEnvelope>) {
+    if !visited.insert(Rc::as_ptr(envelope)) {
+        return;
+    }
+    envelope.watch(writes);
+    match &*envelope.as_ref_to_value() {
+        Value::LogicalLink(target) => watch_cells(target, writes, visited),
+        Value::Structure(structure) => {
+            for member in structure.as_values() {
+                watch_cells(&member.as_contents(), writes, visited);
+            }
+        },
+        Value::Sequence(seq) => {
+            for cell in &*seq.as_values() {
+                watch_cells(&cell.borrow().as_contents(), writes, visited);
+            }
+        },
+        _ => {},
+    }
+}
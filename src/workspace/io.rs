@@ -1,18 +1,296 @@
 //  This module contains the workspace file I/O routines
+//
+//  A workspace file is PPL source.  Since format 2, it starts with a header naming the format, followed by a
+//  manifest of everything the file defines, all as PPL comments:
+//
+//      ... PPL workspace, format 2
+//      ... datatypes: complex point
+//      ... functions: cadd
+//      ... operators: +
+//      ... variables: c z
+//
+//  The items follow, each on its own line except for functions, which are bracketed by lines holding just
+//  { and }.  Files without a header are format 1, which has the same items but no manifest.
+//
+//  copy() brings selected items from a file into the workspace without disturbing the rest of it.  The file is
+//  read into a scratch workspace, and the items wanted are written out again (under new names, if need be),
+//  tried out on a stand-in for the workspace and only then evaluated in the real one.
 
-use std::{fs::File, rc::Rc, io::Write, io::{Error, BufRead, BufReader}, collections::HashSet, path::PathBuf};
+use std::{fmt, fs::File, rc::Rc, io::Write, io::{Error, BufRead, BufReader}, collections::{hash_map::Entry, HashMap, HashSet}, path::PathBuf};
 
-use crate::{workspace::WorkSpace, 
-    symbols::{SymbolTable, 
-        help::Help, 
-        metadata::{self, MetaDataType, MetaStructure, MetaSequence, VariableDescription, FunctionDescription, MetaAlternate}, datatype::RootDataType}, 
-        execution::{value::{Value, sequence::SequenceInstance, structure::StructureInstance}, evaluate_internal}};
+use strum_macros::{EnumString, Display};
+
+use crate::{workspace::{GeneralSymbol, WorkSpace}, 
+    symbols::{SymbolTable, name::Name,
+        metadata::{self, MetaDataType, MetaDataTypeName, MetaStructure, MetaStructureMember, MetaSequence, VariableDescription, FunctionDescription, MetaAlternate, FunctionArgumentList}, datatype::RootDataType}, 
+        execution::{value::{Value, ValueEnvelope, sequence::SequenceInstance, structure::StructureInstance}, evaluate_internal}};
+use crate::{error::PplError, lexical::LineNumber};
 
 const  OPEN_BRACKET: &str = r#"{"#;
 const  CLOSE_BRACKET: &str = r#"}"#;
 
+const  HEADER_PREFIX: &str = "... PPL workspace, format ";
+const  FORMAT: u32 = 2;
+const  MANIFEST_DATATYPES: &str = "... datatypes:";
+const  MANIFEST_FUNCTIONS: &str = "... functions:";
+const  MANIFEST_OPERATORS: &str = "... operators:";
+const  MANIFEST_VARIABLES: &str = "... variables:";
+
+
+//  What copy() does when a name it's bringing in is already in use
+
+#[derive(Clone, Copy, Debug, Display, Eq, PartialEq, EnumString)]
+#[strum(ascii_case_insensitive)]
+pub enum CollisionPolicy {
+    Fail,
+    Overwrite,
+    Rename,
+}
+
+
+//  What a workspace file says it contains
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Manifest {
+    pub format: u32,
+    pub datatypes: Vec<String>,
+    pub functions: Vec<String>,
+    pub operators: Vec<String>,
+    pub variables: Vec<String>,
+}
+
+impl fmt::Display for Manifest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (title, names) in [("datatypes", &self.datatypes), ("functions", &self.functions),
+                ("operators", &self.operators), ("variables", &self.variables)] {
+            if !names.is_empty() {
+                writeln!(f, "{}: {}", title, names.join(" "))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Manifest {
+    fn from_workspace(workspace: &WorkSpace) -> Manifest {
+        let mut datatypes = workspace.datatype_symbol_table.borrow().get_all_names();
+        let system_datatypes = metadata::get_system_datatypes();
+        datatypes.retain(|name| !system_datatypes.contains(name));
+        datatypes.sort();
+
+        let mut functions = Vec::new();
+        let mut operators = Vec::new();
+        for (name, function) in workspace.user_function_symbol_table.borrow().get_all() {
+            if is_operator_binding(&name, &function) {
+                operators.push(name);
+            } else {
+                functions.push(name);
+            }
+        }
+        functions.sort();
+        operators.sort();
+
+        let mut variables = workspace.variable_symbol_table.borrow().get_all_names();
+        variables.sort();
+
+        Manifest { format: FORMAT, datatypes, functions, operators, variables }
+    }
+
+    fn parse_line(&mut self, line: &str) -> bool {
+        let (list, names) = if let Some(names) = line.strip_prefix(MANIFEST_DATATYPES) {
+            (&mut self.datatypes, names)
+        } else if let Some(names) = line.strip_prefix(MANIFEST_FUNCTIONS) {
+            (&mut self.functions, names)
+        } else if let Some(names) = line.strip_prefix(MANIFEST_OPERATORS) {
+            (&mut self.operators, names)
+        } else if let Some(names) = line.strip_prefix(MANIFEST_VARIABLES) {
+            (&mut self.variables, names)
+        } else {
+            return false;
+        };
+        list.extend(names.split_whitespace().map(String::from));
+        true
+    }
+
+    fn write(&self, f: &mut dyn Write) -> Result<(),Error> {
+        writeln!(f, "{}{}", HEADER_PREFIX, self.format)?;
+        for (prefix, names) in [(MANIFEST_DATATYPES, &self.datatypes), (MANIFEST_FUNCTIONS, &self.functions),
+                (MANIFEST_OPERATORS, &self.operators), (MANIFEST_VARIABLES, &self.variables)] {
+            write!(f, "{}", prefix)?;
+            for name in names {
+                write!(f, " {}", name)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+
+//  One definition read from a file, with the line it started on
+
+struct Item {
+    line_number: LineNumber,
+    source: String,
+}
+
+
+pub fn contents(filename: &str) -> Result<Manifest,PplError> {
+    //  Older files don't list what they hold, so the only way to find out is to read them somewhere harmless
+
+    let manifest = read_manifest(filename)?;
+    if manifest.format > 1 {
+        return Ok(manifest);
+    }
+    let scratch = WorkSpace::new();
+    read(filename, &scratch)?;
+    Ok(Manifest { format: manifest.format, ..Manifest::from_workspace(&scratch) })
+}
+
+pub fn copy(filename: &str, names: &[String], workspace: &WorkSpace) -> Result<Vec<String>,PplError> {
+    let scratch = WorkSpace::new();
+    read(filename, &scratch)?;
+    let available = Manifest::from_workspace(&scratch);
+
+    //  Decide what everything will be called before changing anything, so that a refusal leaves the
+    //  workspace as it was
+
+    let names: Vec<String> = if names.is_empty() {
+        available.datatypes.iter().chain(&available.functions).chain(&available.operators).chain(&available.variables).cloned().collect()
+    } else {
+        names.iter().map(|name| name.to_ascii_lowercase()).collect()
+    };
+    let policy = *workspace.copy_policy.borrow();
+    let mut renames = HashMap::new();
+    let mut collisions = Vec::new();
+    let mut overwrites = Vec::new();
+    for name in &names {
+        if !available.datatypes.contains(name) && !available.functions.contains(name) && !available.operators.contains(name) && !available.variables.contains(name) {
+            return Err(ppl_error!(Name, "{} is not in {}", name, filename));
+        }
+        let in_use = if available.operators.contains(name) {
+            workspace.try_get_user_function(name).is_some()
+        } else {
+            !matches!(workspace.try_get_any(name), GeneralSymbol::Unresolved(_))
+        };
+        if !in_use {
+            continue;
+        }
+        match policy {
+            CollisionPolicy::Fail => collisions.push(name.clone()),
+            CollisionPolicy::Overwrite => overwrites.push(name.clone()),
+            CollisionPolicy::Rename if available.operators.contains(name) => return Err(ppl_error!(Runtime, "The operator {} can't be renamed", name)),
+            CollisionPolicy::Rename => {
+                let new_name = (1..).map(|n| format!("{}.{}", name, n))
+                    .find(|candidate| matches!(workspace.try_get_any(candidate), GeneralSymbol::Unresolved(_)) && !names.contains(candidate))
+                    .unwrap();
+                renames.insert(name.clone(), new_name);
+            },
+        }
+    }
+    if !collisions.is_empty() {
+        return Err(ppl_error!(Name, "{} already defined", collisions.join(", ")));
+    }
+    let new_name = |name: &String| renames.get(name).unwrap_or(name).clone();
+
+    //  Whatever refers to a renamed item has to follow it: datatypes by their members, functions by their text
+    //  (a function's result is assigned to its name, so that includes its own), and variables by their values
+
+    let renamed_type = |datatype: &MetaDataTypeName| match renames.get(&datatype.as_string().to_ascii_lowercase()) {
+        Some(renamed) => MetaDataTypeName::from_string(renamed),
+        None => datatype.clone(),
+    };
+    let rename_all = |text: &str, only_datatypes: bool| renames.iter()
+        .filter(|(name, _)| !only_datatypes || available.datatypes.contains(name))
+        .fold(String::from(text), |text, (name, renamed)| rename_identifier(&text, name, renamed));
+
+    //  Write the items out again under their new names, in the order a saved file has them
+
+    let mut items = Vec::new();
+    for name in names.iter().filter(|name| available.datatypes.contains(name)) {
+        let mut source = Vec::new();
+        if let Some(datatype) = scratch.datatype_symbol_table.borrow().try_get(name) {
+            match datatype.root_data_type() {
+                RootDataType::Structure(structure) => write_structure_definition(&mut source, &MetaStructure {
+                    name: Name::from_string(&new_name(name)),
+                    members: structure.members.iter().map(|member| MetaStructureMember { name: member.name.clone(), data_type: renamed_type(&member.data_type) }).collect() }),
+                RootDataType::Sequence(seq) => write_sequence_definition(&mut source, &MetaSequence {
+                    name: Name::from_string(&new_name(name)),
+                    member_type: renamed_type(&seq.member_type),
+                    ..seq.clone() }),
+                RootDataType::Alternate(a) => write_alternate(&mut source, &MetaAlternate {
+                    name: Name::from_string(&new_name(name)),
+                    members: a.members.iter().map(renamed_type).collect() }),
+                _ => Ok(()),
+            }?;
+        }
+        items.push((name, source));
+    }
+    for name in names.iter().filter(|name| available.functions.contains(name)) {
+        let mut source = Vec::new();
+        if let Some(func) = scratch.try_get_user_function(name) {
+            write!(source, "{}", rename_all(&func.as_source(), false))?;
+        }
+        items.push((name, source));
+    }
+    for name in names.iter().filter(|name| available.operators.contains(name)) {
+        let mut source = Vec::new();
+        if let Some(func) = scratch.try_get_user_function(name) {
+            let function_name = new_name(&func.name.as_string().to_ascii_lowercase());
+            write_operator(&mut source, name, &Rc::new(FunctionDescription { name: Name::from_string(&function_name), ..(*func).clone() }))?;
+        }
+        items.push((name, source));
+    }
+    let mut source = Vec::new();
+    let variables: Vec<(String, Rc<VariableDescription>)> = names.iter()
+        .filter(|name| available.variables.contains(name))
+        .filter_map(|name| scratch.try_get_variable(name).map(|variable| (new_name(name), variable)))
+        .collect();
+    write_variables(&mut source, &variables)?;
+    let variable_source = rename_all(String::from_utf8_lossy(&source).as_ref(), true);
+
+    //  Try it all out on a stand-in for the workspace first, so that a failure partway through leaves the real
+    //  one as it was.  The stand-in gets the workspace's datatypes and functions, which is what the items can
+    //  depend on; anything that doesn't come back only makes the trial stricter.
+
+    let trial = WorkSpace::new();
+    let mut seed = Vec::new();
+    write_datatypes(&mut seed, workspace)?;
+    write_functions(&mut seed, workspace)?;
+    let (_, seed_items) = read_items(seed.as_slice(), "workspace")?;
+    for item in seed_items {
+        if evaluate_internal(item.source.as_str(), &trial).is_err() {
+            trial.unwind_invocations(0);
+        }
+    }
+    for target in [&trial, workspace] {
+        for name in &overwrites {
+            target.remove_symbol(name);
+        }
+        for (name, source) in &items {
+            evaluate_internal(String::from_utf8_lossy(source).as_ref(), target).map_err(|e| e.within(name, None))?;
+        }
+
+        //  Variables go in together, since they may be linked to one another
+
+        for line in variable_source.lines() {
+            evaluate_internal(line, target)?;
+        }
+    }
+
+    Ok(names.iter().map(|name| match renames.get(name) {
+        Some(renamed) => format!("{} as {}", name, renamed),
+        None => name.clone(),
+    }).collect())
+}
+
+fn is_operator_binding(name: &str, function: &Rc<FunctionDescription>) -> bool {
+    //  binary() and unary() file the function under the operator as well as under its own name
+
+    name != function.name.as_string().to_ascii_lowercase()
+}
 
-fn normalize_filename(filename: &String) -> PathBuf {
+fn normalize_filename(filename: &str) -> PathBuf {
     let mut path = PathBuf::from(filename);
     if path.extension().is_none() {
         path.set_extension("ppl");
@@ -20,71 +298,164 @@ fn normalize_filename(filename: &String) -> PathBuf {
     path
 }
 
-pub fn read(filename: &String, workspace: &WorkSpace) -> Result<(),String> {
-    let f = File::open(normalize_filename(filename)).map_err(|e| e.to_string())?;
-    let reader = BufReader::new(f);
-    let mut in_quoted_function_definition = false;
-    let mut function_definition = String::new();
-
-    for line in reader.lines() {
-        match line {
-            Ok(ref l) => match l.trim() {
-                OPEN_BRACKET => {
-                    if in_quoted_function_definition {
-                        return Err(format!("Malformed file, encounted unexpected {}", OPEN_BRACKET));
-                    } else {
-                        in_quoted_function_definition = true;
-                        function_definition.clear();
-                    }
-                },
-                CLOSE_BRACKET => {
-                    if in_quoted_function_definition {
-                        in_quoted_function_definition = false;
-                        evaluate_internal(function_definition.as_str(), workspace)?;
-                    } else {
-                        return Err(format!("Malformed file, encounted unexpected {}", CLOSE_BRACKET));
-                    }
-                },
-                _ => {
-                    if in_quoted_function_definition {
-                        function_definition += line.unwrap().as_str();
-                        function_definition += "\n";
-                    } else {
-                        evaluate_internal(line.unwrap().as_str(), workspace)?;
-                    }
-                },
+fn parse_header(line: &str) -> Result<Option<u32>,PplError> {
+    match line.trim().strip_prefix(HEADER_PREFIX) {
+        Some(format) => {
+            let format = format.trim().parse::<u32>().map_err(|_| ppl_error!(Io, "{} is not a valid workspace format", format.trim()))?;
+            if format > FORMAT {
+                return Err(ppl_error!(Io, "The workspace is format {}, but only formats up to {} can be read", format, FORMAT));
+            }
+            Ok(Some(format))
+        },
+        None => Ok(None),
+    }
+}
+
+pub fn read(filename: &str, workspace: &WorkSpace) -> Result<(),PplError> {
+    let path = normalize_filename(filename);
+    let (manifest, items) = read_items(BufReader::new(File::open(&path)?), &path.display().to_string())?;
+
+    //  Keep going after a bad item, so that one problem doesn't cost the rest of the workspace
+
+    let depth = workspace.get_invocation_depth();
+    let mut errors = Vec::new();
+    for item in items {
+        if let Err(e) = evaluate_internal(item.source.as_str(), workspace) {
+            errors.push(e.within(&path.display().to_string(), Some(item.line_number)));
+            workspace.unwind_invocations(depth);
+        }
+    }
+
+    //  A file that loaded cleanly but is missing something it lists has been truncated
+
+    if errors.is_empty() {
+        let missing: Vec<&String> = manifest.datatypes.iter()
+            .chain(&manifest.functions)
+            .chain(&manifest.operators)
+            .chain(&manifest.variables)
+            .filter(|name| matches!(workspace.try_get_any(name), GeneralSymbol::Unresolved(_)))
+            .collect();
+        if !missing.is_empty() {
+            errors.push(ppl_error!(Io, "the file is incomplete, missing {}",
+                missing.iter().map(|name| name.as_str()).collect::<Vec<&str>>().join(", ")).within(&path.display().to_string(), None));
+        }
+    }
+
+    //  A single problem keeps its kind; several are reported together
+
+    match errors.len() {
+        0 => Ok(()),
+        1 => Err(errors.remove(0)),
+        _ => Err(ppl_error!(Io, "{}", errors.iter().map(|e| e.to_string()).collect::<Vec<String>>().join("\n"))),
+    }
+}
+
+fn read_items(reader: impl BufRead, source_name: &str) -> Result<(Manifest, Vec<Item>),PplError> {
+    let mut manifest = Manifest { format: 1, ..Default::default() };
+    let mut items = Vec::new();
+    let mut function_definition: Option<Item> = None;
+
+    for (line_number, line) in (1..).zip(reader.lines()) {
+        let line = line?;
+        if line_number == 1 {
+            if let Some(format) = parse_header(&line).map_err(|e| e.within(source_name, Some(1)))? {
+                manifest.format = format;
+                continue;
+            }
+        }
+
+        match (line.trim(), &mut function_definition) {
+            (OPEN_BRACKET, None) => function_definition = Some(Item { line_number: line_number + 1, source: String::new() }),
+            (OPEN_BRACKET, Some(_)) => return Err(ppl_error!(Io, "Malformed file, encountered unexpected {}", OPEN_BRACKET).within(source_name, Some(line_number))),
+            (CLOSE_BRACKET, Some(_)) => items.push(function_definition.take().unwrap()),
+            (CLOSE_BRACKET, None) => return Err(ppl_error!(Io, "Malformed file, encountered unexpected {}", CLOSE_BRACKET).within(source_name, Some(line_number))),
+            (_, Some(item)) => {
+                item.source += line.as_str();
+                item.source += "\n";
+            },
+            ("", None) => {},
+            (trimmed, None) => {
+                if manifest.format > 1 && manifest.parse_line(trimmed) {
+                    continue;
+                }
+                items.push(Item { line_number, source: line });
             },
-            
-            Err(e) => return Err(e.to_string()),
         }
     }
 
-    Ok(())
+    if let Some(item) = function_definition {
+        return Err(ppl_error!(Io, "Malformed file, missing {}", CLOSE_BRACKET).within(source_name, Some(item.line_number - 1)));
+    }
+    Ok((manifest, items))
+}
+
+pub fn read_manifest(filename: &str) -> Result<Manifest,PplError> {
+    let path = normalize_filename(filename);
+    Ok(read_items(BufReader::new(File::open(&path)?), &path.display().to_string())?.0)
 }
 
-pub fn write(filename: &String, workspace: &WorkSpace) -> Result<(),String>{
-    let mut f = File::create(normalize_filename(filename)).map_err(|e| e.to_string())?;
-    write_datatypes(&mut f, workspace).map_err(|e| e.to_string())?;
-    write_symbol_table(&mut f, &*workspace.variable_symbol_table.borrow(), write_variable).map_err(|e| format!("{}", e)).map_err(|e| e.to_string())?;
-    write_symbol_table(&mut f, &*workspace.user_function_symbol_table.borrow(), write_function).map_err(|e| format!("{}", e)).map_err(|e| e.to_string())?;
+fn rename_identifier(text: &str, old_name: &str, new_name: &str) -> String {
+    //  Replaces whole identifiers only, and leaves string literals alone
+
+    if old_name == new_name {
+        return String::from(text);
+    }
+    let is_identifier_character = |c: char| c.is_alphanumeric() || c == '.';
+    let mut result = String::new();
+    let mut in_string = false;
+    let mut rest = text;
+    while let Some(c) = rest.chars().next() {
+        if c == '"' {
+            in_string = !in_string;
+        }
+        if !in_string && is_identifier_character(c) {
+            let length = rest.find(|c: char| !is_identifier_character(c)).unwrap_or(rest.len());
+            let identifier = &rest[..length];
+            result += if identifier.eq_ignore_ascii_case(old_name) { new_name } else { identifier };
+            rest = &rest[length..];
+        } else {
+            result.push(c);
+            rest = &rest[c.len_utf8()..];
+        }
+    }
+    result
+}
 
-    
+pub fn write(filename: &str, workspace: &WorkSpace) -> Result<(),PplError>{
+    let mut f = File::create(normalize_filename(filename))?;
+    Manifest::from_workspace(workspace).write(&mut f)?;
+    write_datatypes(&mut f, workspace)?;
+    write_functions(&mut f, workspace)?;
+    let mut variables = workspace.variable_symbol_table.borrow().get_all();
+    variables.sort_by(|a,b| a.0.cmp(&b.0));
+    write_variables(&mut f, &variables)?;
     Ok(())
 }
 
-fn write_alternate(f: &mut File, alternate: &MetaAlternate) -> Result<(),Error> {
+fn write_alternate(f: &mut dyn Write, alternate: &MetaAlternate) -> Result<(),Error> {
     writeln!(f, "{}", alternate)
 }
 
-fn write_datatype(f: &mut File, name: &String, processed_datatypes: &mut HashSet<String>, symbol_table: &SymbolTable<MetaDataType>) -> Result<(),Error> {
+fn write_datatype(f: &mut dyn Write, name: &String, processed_datatypes: &mut HashSet<String>, symbol_table: &SymbolTable<MetaDataType>) -> Result<(),Error> {
     if !processed_datatypes.contains(name) {
         let opt_datatype = symbol_table.try_get(name.as_str());
         if let Some(datatype) = opt_datatype {
             processed_datatypes.insert(name.clone());
+
+            //  Sequences and alternates need their member types defined first
+
             match &datatype.root_data_type() {
                 RootDataType::Structure(structure) => write_structure_definition(f, structure),
-                RootDataType::Sequence(seq) => write_sequence_definition(f, seq),
-                RootDataType::Alternate(a) => write_alternate(f, a),
+                RootDataType::Sequence(seq) => {
+                    write_datatype(f, &seq.member_type.as_string().to_ascii_lowercase(), processed_datatypes, symbol_table)?;
+                    write_sequence_definition(f, seq)
+                },
+                RootDataType::Alternate(a) => {
+                    for member in &a.members {
+                        write_datatype(f, &member.as_string().to_ascii_lowercase(), processed_datatypes, symbol_table)?;
+                    }
+                    write_alternate(f, a)
+                },
                 _ => Ok(())
             }
         } else {
@@ -95,7 +466,7 @@ fn write_datatype(f: &mut File, name: &String, processed_datatypes: &mut HashSet
     }
 }
 
-fn write_datatypes(f: &mut File, workspace: &WorkSpace) -> Result<(),Error> {
+fn write_datatypes(f: &mut dyn Write, workspace: &WorkSpace) -> Result<(),Error> {
     let mut datatype_names = workspace.datatype_symbol_table.borrow().get_all_names();
     datatype_names.sort();
 
@@ -105,86 +476,249 @@ fn write_datatypes(f: &mut File, workspace: &WorkSpace) -> Result<(),Error> {
     }
 
     for name in datatype_names {
-        write_datatype(f, &name, &mut processed_datatype_names, &*workspace.datatype_symbol_table.borrow())?;
+        write_datatype(f, &name, &mut processed_datatype_names, &workspace.datatype_symbol_table.borrow())?;
     }
 
     Ok(())
 }
 
-fn write_function(f: &mut File, _name: &String, func: &Rc<FunctionDescription>) -> Result<(),Error> {
+fn write_double(f: &mut dyn Write, v: f64) -> Result<(),Error> {
+    //  The shortest form that reads back as the same number, with a D exponent so that it stays a dbl
+
+    write!(f, "{}", format!("{:e}", v).replace('e', "D"))
+}
+
+fn write_function(f: &mut dyn Write, func: &Rc<FunctionDescription>) -> Result<(),Error> {
     writeln!(f, "{}", OPEN_BRACKET)?;
-    write!(f, "{}", func.as_source())?;
+    writeln!(f, "{}", func.as_source().trim_end())?;
     writeln!(f, "{}", CLOSE_BRACKET)
 }
 
-fn write_sequence_body(f: &mut File, seq: &SequenceInstance) -> Result<(),Error> {
+fn write_functions(f: &mut dyn Write, workspace: &WorkSpace) -> Result<(),Error> {
+    let mut functions = workspace.user_function_symbol_table.borrow().get_all();
+    functions.sort_by(|a,b| a.0.cmp(&b.0));
+
+    //  Operators are bound once the functions they use are back
+
+    for (name, func) in &functions {
+        if !is_operator_binding(name, func) {
+            write_function(f, func)?;
+        }
+    }
+    for (name, func) in &functions {
+        if is_operator_binding(name, func) {
+            write_operator(f, name, func)?;
+        }
+    }
+    Ok(())
+}
+
+fn write_operator(f: &mut dyn Write, name: &str, func: &Rc<FunctionDescription>) -> Result<(),Error> {
+    match &func.arguments {
+        FunctionArgumentList::Fixed(args) if args.len() == 1 => writeln!(f, "unary(\"{}\", {})", name, func.name),
+        _ => writeln!(f, "binary(\"{}\", {})", name, func.name),
+    }
+}
+
+fn write_real(f: &mut dyn Write, v: f32) -> Result<(),Error> {
+    write!(f, "real({})", format!("{:e}", v).replace('e', "E"))
+}
+
+fn write_sequence_body(f: &mut dyn Write, seq: &SequenceInstance, mut write_member: impl FnMut(&mut dyn Write, i32, &Rc<ValueEnvelope>) -> Result<(),Error>) -> Result<(),Error> {
+    if let Some(s) = as_string_literal(seq) {
+        return write!(f, "{}", s);
+    }
     write!(f, "{}(", seq.as_string())?;
     let mut separator = "";
-    for cell in &*seq.as_values() {
+    for (index, cell) in (seq.lower_bound()..).zip(&*seq.as_values()) {
         write!(f, "{}", separator)?;
-        write_value(f, &*cell.borrow().as_ref_to_value())?;
+        write_member(f, index, &cell.borrow().as_contents())?;
         separator = ", ";
     }
     write!(f, ")")
 }
 
-fn write_sequence_definition(f: &mut File, seq: &MetaSequence) -> Result<(),Error> {
+fn as_string_literal(seq: &SequenceInstance) -> Option<String> {
+    //  Strings are written as literals when they can be.  PPL literals have no escapes, so a string holding
+    //  a quote or a control character is written out character by character instead
+
+    if seq.as_string() != "string" {
+        return None;
+    }
+    let mut literal = String::from("\"");
+    for cell in &*seq.as_values() {
+        match &*cell.borrow().as_ref_to_value() {
+            Value::Char(c) if *c != '"' && !c.is_control() => literal.push(*c),
+            _ => return None,
+        }
+    }
+    literal.push('"');
+    Some(literal)
+}
+
+fn write_sequence_definition(f: &mut dyn Write, seq: &MetaSequence) -> Result<(),Error> {
     write!(f, "{}", seq)
 }
 
-fn write_structure_body(f: &mut File, structure: &StructureInstance) -> Result<(),Error> {
+fn write_structure_body(f: &mut dyn Write, structure: &StructureInstance, mut write_member: impl FnMut(&mut dyn Write, &str, &Rc<ValueEnvelope>) -> Result<(),Error>) -> Result<(),Error> {
     write!(f, "{}(", structure.as_string())?;
     let mut separator = "";
     for member in structure.as_values() {
         write!(f, "{}", separator)?;
-        write_value(f, &member.as_value())?;
+        write_member(f, member.as_string().as_str(), &member.as_contents())?;
         separator = ", ";
     }
     write!(f, ")")
 }
 
-fn write_structure_definition(f: &mut File, structure: &MetaStructure) -> Result<(),Error> {
+fn write_structure_definition(f: &mut dyn Write, structure: &MetaStructure) -> Result<(),Error> {
     write!(f, "{}", structure)
 }
 
-fn write_symbol_table<T: Help>(file: &mut File, symbol_table: &SymbolTable<T>, formatter: fn(&mut File, &String, &Rc<T>) -> Result<(),Error>) -> Result<(),Error> {
-    let symbols = symbol_table.get_all();
-    for (name, item) in &symbols {
-        formatter(file, name, item)?;
-    }
-    Ok(())
-}
+fn write_cell(f: &mut dyn Write, path: &str, envelope: &Rc<ValueEnvelope>, cells: &mut SharedCells) -> Result<(),Error> {
+    let envelope = cells.adopted(envelope);
+    let value = envelope.as_ref_to_value();
+    match &*value {
+        Value::LogicalLink(target) => {
+            //  A stand-in, until the link is remade
 
-fn write_variable(f: &mut File, name: &String, variable: &Rc<VariableDescription>) -> Result<(),Error> {
-    match &*variable.cell.borrow().as_ref_to_value() {
-        Value::Bool(v) => writeln!(f, "{}_bool({})", name, v),
-        Value::Int(v) => writeln!(f, "{}_int({})", name, v),
-        Value::Real(v) => writeln!(f, "{}_real({})", name, v),
-        Value::Double(v) => writeln!(f, "{}_dbl({})", name, v),
-        Value::Char(v) => writeln!(f, "{}_char('{})", name, v),
-        Value::Structure(structure) => {
-            write!(f, "{}_", name)?;
-            write_structure_body(f, structure)?;
-            writeln!(f, "")
+            write_value(f, &target.as_ref_to_value(), &mut vec![Rc::as_ptr(target)])?;
+            cells.relink(path, target);
+            Ok(())
         },
-        Value::Sequence(seq) => {
-            write!(f, "{}_", name)?;
-            write_sequence_body(f, seq)?;
-            writeln!(f, "")
-        }
-        _ => Ok(()),
+        Value::Structure(structure) => write_structure_body(f, structure, 
+            |f, selector, member| write_cell(f, &format!("{}[{}]", path, selector), member, cells)),
+        Value::Sequence(seq) => write_sequence_body(f, seq, 
+            |f, index, member| write_cell(f, &format!("{}[{}]", path, index), member, cells)),
+        v => write_value(f, v, &mut Vec::new()),
     }
 }
 
-fn write_value(f: &mut File, v: &Value) -> Result<(),Error> {
+fn write_value(f: &mut dyn Write, v: &Value, visiting: &mut Vec<*const ValueEnvelope>) -> Result<(),Error> {
     match v {
-        Value::Bool(v) => write!(f, "bool({})", v),
+        Value::Bool(v) => write!(f, "{}", v),
         Value::Int(v) => write!(f, "int({})", v),
-        Value::Real(v) => write!(f, "real({})", v),
-        Value::Double(v) => write!(f, "dbl({})", v),
+        Value::Int64(v) => write!(f, "int64({})", v),
+        #[cfg(feature = "bignum")]
+        Value::Bignum(v) => write!(f, "bignum({})", v),
+        Value::Rational(v) => write!(f, "rational({}, {})", v.numerator(), v.denominator()),
+        Value::Decimal(v) => write!(f, "decimal(\"{}\")", v),
+        Value::Real(v) => write_real(f, *v),
+        Value::Double(v) => {
+            write!(f, "dbl(")?;
+            write_double(f, *v)?;
+            write!(f, ")")
+        },
+        Value::Char(v) if v.is_control() => write!(f, "char({})", *v as u32),
         Value::Char(v) => write!(f, "'{}", v),
-        Value::Structure(structure) => write_structure_body(f, structure),
-        Value::Sequence(seq) => write_sequence_body(f, seq),
+        Value::Structure(structure) => write_structure_body(f, structure, 
+            |f, _, member| write_value(f, &member.as_ref_to_value(), visiting)),
+        Value::Sequence(seq) => write_sequence_body(f, seq, 
+            |f, _, member| write_value(f, &member.as_ref_to_value(), visiting)),
+        Value::LogicalLink(target) if visiting.contains(&Rc::as_ptr(target)) => write!(f, "int(0)"),
+        Value::LogicalLink(target) => {
+            visiting.push(Rc::as_ptr(target));
+            write_value(f, &target.as_ref_to_value(), visiting)?;
+            visiting.pop();
+            Ok(())
+        },
+        Value::ValueByReference(reference) => write_value(f, &reference.cell.borrow().as_ref_to_value(), visiting),
         _ => Ok(()),
     }
-}
\ No newline at end of file
+}
+
+fn write_variables(f: &mut dyn Write, variables: &[(String, Rc<VariableDescription>)]) -> Result<(),Error> {
+    let mut cells = SharedCells::locate_all(variables);
+    for (name, variable) in variables {
+        let envelope = variable.cell.borrow().as_contents();
+        if matches!(&*cells.adopted(&envelope).as_ref_to_value(), Value::Empty | Value::Selector(_) | Value::Symbol(_)) {
+            continue;
+        }
+        write!(f, "{}_", name)?;
+        write_cell(f, name, &envelope, &mut cells)?;
+        writeln!(f)?;
+    }
+
+    //  Once everything is back, remake the links
+
+    for (path, target) in &cells.relinks {
+        writeln!(f, "{}__{}", path, target)?;
+    }
+    Ok(())
+}
+
+
+//  noncopy makes a cell hold a link to another cell's envelope, which is how variables come to share values
+//  (and how a value can come to contain itself).  To save that, each envelope is given a home: the first
+//  place it's found, walking the variables in order without following links.  Its value is written there,
+//  and every link to it is written as a stand-in that's relinked to the home once all the variables have been
+//  read back.  An envelope that's only reachable through links (its variable has since been erased, say)
+//  is adopted by the first place that links to it.
+
+struct SharedCells {
+    homes: HashMap<*const ValueEnvelope, String>,
+    adoptions: HashMap<*const ValueEnvelope, Rc<ValueEnvelope>>,
+    links: Vec<(String, *const ValueEnvelope, Rc<ValueEnvelope>)>,
+    relinks: Vec<(String, String)>,
+}
+
+impl SharedCells {
+    fn adopted(&self, envelope: &Rc<ValueEnvelope>) -> Rc<ValueEnvelope> {
+        let mut envelope = envelope.clone();
+        while let Some(target) = self.adoptions.get(&Rc::as_ptr(&envelope)) {
+            envelope = target.clone();
+        }
+        envelope
+    }
+
+    fn locate(&mut self, path: String, envelope: &Rc<ValueEnvelope>) {
+        if let Entry::Vacant(home) = self.homes.entry(Rc::as_ptr(envelope)) {
+            home.insert(path.clone());
+            self.locate_members(&path, envelope);
+        }
+    }
+
+    fn locate_all(variables: &[(String, Rc<VariableDescription>)]) -> SharedCells {
+        let mut cells = SharedCells { homes: HashMap::new(), adoptions: HashMap::new(), links: Vec::new(), relinks: Vec::new() };
+        for (name, variable) in variables {
+            cells.locate(name.clone(), &variable.cell.borrow().as_contents());
+        }
+
+        //  Links found along the way may turn up more links, so this runs until there are no more
+
+        let mut next = 0;
+        while next < cells.links.len() {
+            let (path, link, target) = cells.links[next].clone();
+            if let Entry::Vacant(home) = cells.homes.entry(Rc::as_ptr(&target)) {
+                home.insert(path.clone());
+                cells.adoptions.insert(link, target.clone());
+                cells.locate_members(&path, &target);
+            }
+            next += 1;
+        }
+        cells
+    }
+
+    fn locate_members(&mut self, path: &str, envelope: &Rc<ValueEnvelope>) {
+        match &*envelope.as_ref_to_value() {
+            Value::LogicalLink(target) => self.links.push((String::from(path), Rc::as_ptr(envelope), target.clone())),
+            Value::Structure(structure) => {
+                for member in structure.as_values() {
+                    self.locate(format!("{}[{}]", path, member.as_string()), &member.as_contents());
+                }
+            },
+            Value::Sequence(seq) => {
+                for (index, cell) in (seq.lower_bound()..).zip(&*seq.as_values()) {
+                    self.locate(format!("{}[{}]", path, index), &cell.borrow().as_contents());
+                }
+            },
+            _ => {},
+        }
+    }
+
+    fn relink(&mut self, path: &str, target: &Rc<ValueEnvelope>) {
+        if let Some(home) = self.homes.get(&Rc::as_ptr(target)) {
+            self.relinks.push((String::from(path), home.clone()));
+        }
+    }
+}
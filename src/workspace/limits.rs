@@ -0,0 +1,66 @@
+//  This module holds the limits on what an evaluation may use, so that code that can't be trusted to finish
+//  (or to stop recursing) ends with an error rather than taking the process down with it.  Only the stack is
+//  limited at first: user functions don't use it, but evaluations nested in them (expect.error, breakpoint
+//  conditions) do, and running out of it aborts the process.  The default leaves room on a 2MB thread stack,
+//  the smallest Rust gives a thread by default.
+
+use crate::error::PplError;
+
+
+pub const DEFAULT_STACK_LIMIT: usize = 1024 * 1024;
+
+#[derive(Clone, Debug)]
+pub struct Limits {
+    pub nodes: Option<u64>,          //  Nodes executed by one evaluation from immediate mode
+    pub depth: Option<usize>,        //  User functions executing at once
+    pub values: Option<usize>,       //  Values on the value stack
+    pub stack: Option<usize>,        //  Bytes of the process stack used by the outermost evaluation
+    nodes_executed: u64,
+}
+
+impl Default for Limits {
+    fn default() -> Limits {
+        Limits::new()
+    }
+}
+
+impl Limits {
+    pub fn new() -> Limits {
+        Limits { nodes: None, depth: None, values: None, stack: Some(DEFAULT_STACK_LIMIT), nodes_executed: 0 }
+    }
+
+    //  Each evaluation from immediate mode gets the full node budget
+
+    pub fn start(&mut self) {
+        self.nodes_executed = 0;
+    }
+
+    pub fn count_node(&mut self) -> Result<(),PplError> {
+        self.nodes_executed += 1;
+        match self.nodes {
+            Some(nodes) if self.nodes_executed > nodes => Err(ppl_error!(Limit, "More than {} nodes executed", nodes)),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn check_depth(&self, depth: usize) -> Result<(),PplError> {
+        match self.depth {
+            Some(limit) if depth > limit => Err(ppl_error!(Limit, "More than {} functions called", limit)),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn check_values(&self, size: usize) -> Result<(),PplError> {
+        match self.values {
+            Some(limit) if size > limit => Err(ppl_error!(Limit, "More than {} values on the value stack", limit)),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn check_stack(&self, size: usize) -> Result<(),PplError> {
+        match self.stack {
+            Some(limit) if size > limit => Err(ppl_error!(Limit, "More than {}KB of stack used", limit / 1024)),
+            _ => Ok(()),
+        }
+    }
+}
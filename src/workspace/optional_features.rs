@@ -11,6 +11,7 @@ use super::options::Options;
 #[strum(ascii_case_insensitive)]
 pub enum Feature {
     LineNames,
+    PromoteOnOverflow,
     StringEscapes,
 }
 
@@ -1,6 +1,7 @@
 //  This module manages optional functionality
 
 use std::{collections:: HashMap, hash::Hash, str::FromStr, fmt::Display};
+use crate::error::PplError;
 
 
 #[derive(Debug)]
@@ -17,8 +18,8 @@ where T:Display, T:Eq, T:PartialEq, T:Hash, T:Clone, T:FromStr, <T as FromStr>::
         self.options.insert(option, false);
     }
 
-    pub fn clear_str(&mut self, option: &str) -> Result<(),String>{
-        self.options.insert(T::from_str(option).map_err(|e| format!("{}", e))?, false);
+    pub fn clear_str(&mut self, option: &str) -> Result<(),PplError>{
+        self.options.insert(T::from_str(option).map_err(|e| ppl_error!(Name, "{}", e))?, false);
         Ok(())
     }
 
@@ -35,8 +36,8 @@ where T:Display, T:Eq, T:PartialEq, T:Hash, T:Clone, T:FromStr, <T as FromStr>::
         }
     }
 
-    pub fn is_set_str(&self, option: &str) -> Result<bool,String> {
-        match self.options.get(&T::from_str(option).map_err(|e| format!("{}", e))?) {
+    pub fn is_set_str(&self, option: &str) -> Result<bool,PplError> {
+        match self.options.get(&T::from_str(option).map_err(|e| ppl_error!(Name, "{}", e))?) {
             Some(result) => Ok(*result),
             None => Ok(false),
         }
@@ -50,8 +51,8 @@ where T:Display, T:Eq, T:PartialEq, T:Hash, T:Clone, T:FromStr, <T as FromStr>::
         self.options.insert(option, true);
     }
 
-    pub fn set_str(&mut self, option: &str) -> Result<(),String> {
-        self.options.insert(T::from_str(option).map_err(|e| format!("{}", e))?, true);
+    pub fn set_str(&mut self, option: &str) -> Result<(),PplError> {
+        self.options.insert(T::from_str(option).map_err(|e| ppl_error!(Name, "{}", e))?, true);
         Ok(())
     }
 